@@ -2,39 +2,50 @@ use anyhow::{Context, Result};
 use clap::Parser;
 use tracing_subscriber::EnvFilter;
 
-use crate::args::{Cli, Commands};
+use crate::args::{BaseAction, Cli, Commands, DbAction, PrArgs};
 use crate::commands;
-use crate::db::Database;
+use crate::config::StackConfig;
+use crate::core::{StackBases, SyncPlanOptions};
+use crate::db::{Database, StackLock};
 use crate::git::Git;
-use crate::provider::GithubProvider;
+use crate::provider::{CachedProvider, MergeMethod, Provider, provider_from_env};
+use crate::util::url::{GITHUB_HOST, web_url_host};
 
 pub struct AppContext {
     cli: Cli,
     git: Git,
     db: Database,
+    config: StackConfig,
     base_branch: String,
+    bases: StackBases,
     base_remote: String,
-    provider: GithubProvider,
+    provider: Box<dyn Provider>,
 }
 
 impl AppContext {
-    fn build() -> Result<Self> {
-        let cli = Cli::parse();
-        let git = Git::discover()?;
+    fn build(cli: Cli, git: Git) -> Result<Self> {
+        let config = StackConfig::load(&git)?;
+        let git = git
+            .with_host_map(config.host_map.clone())
+            .with_remote_settings(config.remotes.clone());
         let git_dir = git.git_dir()?;
         let db_path = git_dir.join("stack.db");
         let db = Database::open(&db_path)?;
         let default_base = git.default_base_branch()?;
         db.set_base_branch_if_missing(&default_base)?;
-        let base_branch = db.repo_meta()?.base_branch;
+        let bases = StackBases::load(&db)?;
+        let base_branch = bases.default_branch().to_string();
         let base_remote = git.base_remote_for_stack(&base_branch)?;
-        let provider = GithubProvider::new(git.clone(), cli.global.debug);
+        let hostname = provider_hostname(&git, &base_remote, cli.global.hostname.as_deref())?;
+        let provider = provider_from_env(&git, cli.global.debug, hostname)?;
 
         Ok(Self {
             cli,
             git,
             db,
+            config,
             base_branch,
+            bases,
             base_remote,
             provider,
         })
@@ -55,33 +66,99 @@ pub fn run() -> Result<()> {
         .compact()
         .init();
 
-    let ctx = AppContext::build()?;
+    let cli = Cli::parse();
+    let git = Git::discover()?;
+    let _lock = if mutates(&cli) {
+        let command = std::env::args().collect::<Vec<_>>().join(" ");
+        Some(StackLock::acquire(
+            &git.git_dir()?.join("stack.db"),
+            &command,
+            cli.global.wait,
+        )?)
+    } else {
+        None
+    };
+    match &cli.command {
+        Some(Commands::Db(args)) => {
+            return commands::db::run(
+                &git.git_dir()?.join("stack.db"),
+                args,
+                cli.global.yes,
+                cli.global.porcelain,
+            );
+        }
+        Some(Commands::Init(args)) => {
+            return commands::init::run(
+                &git.git_dir()?.join("stack.db"),
+                &git,
+                args,
+                cli.global.porcelain,
+            );
+        }
+        _ => {}
+    }
+    let ctx = AppContext::build(cli, git)?;
     dispatch(&ctx)
 }
 
+/// Whether the command may rewrite branches, check out another branch or
+/// write stack metadata, and so must hold the `StackLock`. Previews and
+/// read-only views run alongside other commands.
+fn mutates(cli: &Cli) -> bool {
+    match &cli.command {
+        None => false,
+        Some(Commands::Track(args)) => !args.dry_run,
+        Some(Commands::Sync(args)) => !args.dry_run,
+        Some(Commands::Delete(args)) => !args.dry_run,
+        Some(Commands::Merge(args)) => !args.dry_run,
+        Some(Commands::Doctor(args)) => args.fix,
+        Some(Commands::Pr(args)) => args.action.is_some() || !(args.dry_run || args.preview_body),
+        Some(Commands::Import(args)) => !args.dry_run,
+        Some(Commands::Base(args)) => !matches!(args.action, BaseAction::List),
+        Some(Commands::Db(args)) => !matches!(args.action, DbAction::Info | DbAction::Backups),
+        Some(Commands::Completions(_) | Commands::Export(_) | Commands::History(_)) => false,
+        Some(_) => true,
+    }
+}
+
 fn dispatch(ctx: &AppContext) -> Result<()> {
+    let provider = CachedProvider::new(
+        ctx.provider.as_ref(),
+        &ctx.db,
+        ctx.config.cache_ttl_secs,
+        ctx.cli.global.offline,
+    );
+    let result = dispatch_command(ctx, &provider);
+    if !ctx.cli.global.porcelain
+        && let Some(freshness) = provider.freshness()
+    {
+        eprintln!("PR data: {}", freshness.describe());
+    }
+    result
+}
+
+fn dispatch_command(ctx: &AppContext, provider: &CachedProvider<'_>) -> Result<()> {
     match &ctx.cli.command {
         None => commands::stack::run(
             &ctx.db,
             &ctx.git,
             ctx.cli.global.porcelain,
             ctx.cli.global.interactive,
-            &ctx.base_branch,
+            &ctx.bases,
             &ctx.base_remote,
         ),
         Some(Commands::Create(args)) => commands::create::run(
             &ctx.db,
             &ctx.git,
-            &ctx.provider,
-            &args.parent,
-            &args.insert,
-            &args.name,
+            provider,
+            args,
+            &sync_plan_options(ctx),
             ctx.cli.global.porcelain,
         ),
         Some(Commands::Track(args)) => commands::track::run(
             &ctx.db,
             &ctx.git,
-            &ctx.provider,
+            provider,
             args,
             &ctx.base_branch,
             commands::track::TrackRunOptions {
@@ -95,13 +172,14 @@ fn dispatch(ctx: &AppContext) -> Result<()> {
         Some(Commands::Sync(args)) => commands::sync::run(
             &ctx.db,
             &ctx.git,
-            &ctx.provider,
-            &ctx.base_branch,
+            provider,
+            &ctx.bases,
             &ctx.base_remote,
             commands::sync::SyncRunOptions {
                 porcelain: ctx.cli.global.porcelain,
                 yes: ctx.cli.global.yes,
                 dry_run: args.dry_run,
+                plan: sync_plan_options(ctx),
             },
         ),
         Some(Commands::Doctor(args)) => {
@@ -112,32 +190,82 @@ fn dispatch(ctx: &AppContext) -> Result<()> {
             &ctx.git,
             args.branch.as_deref(),
             ctx.cli.global.porcelain,
-            &ctx.base_branch,
+            &ctx.bases,
             ctx.cli.global.yes,
         ),
         Some(Commands::Delete(args)) => commands::delete::run(
             &ctx.db,
             &ctx.git,
-            &ctx.provider,
+            provider,
             args,
+            &ctx.bases,
+            commands::delete::DeleteRunOptions {
+                porcelain: ctx.cli.global.porcelain,
+                yes: ctx.cli.global.yes,
+                plan: sync_plan_options(ctx),
+            },
+        ),
+        Some(Commands::Pr(PrArgs {
+            action: Some(action),
+            ..
+        })) => commands::pr_lifecycle::run(
+            &ctx.db,
+            &ctx.git,
+            provider,
+            action,
+            &ctx.bases,
             ctx.cli.global.porcelain,
-            ctx.cli.global.yes,
-            &ctx.base_branch,
         ),
         Some(Commands::Pr(args)) => commands::pr::run(
             &ctx.db,
             &ctx.git,
-            &ctx.provider,
+            provider,
             args,
-            ctx.cli.global.porcelain,
-            ctx.cli.global.yes,
-            ctx.cli.global.debug,
+            commands::pr::PrRunOptions {
+                porcelain: ctx.cli.global.porcelain,
+                debug: ctx.cli.global.debug,
+                body_style: ctx.config.pr_body.clone(),
+                title_prefix: ctx.config.pr_title_prefix.clone(),
+                inherit_metadata: ctx.config.pr_inherit_metadata,
+                order_status: ctx.config.order_status,
+            },
         ),
         Some(Commands::Push) => commands::push::run(
             &ctx.db,
             &ctx.git,
+            provider,
+            &ctx.bases,
+            commands::push::PushRunOptions {
+                porcelain: ctx.cli.global.porcelain,
+                order_status: ctx.config.order_status,
+            },
+        ),
+        Some(Commands::Merge(args)) => commands::merge::run(
+            &ctx.db,
+            &ctx.git,
+            provider,
+            args,
+            &ctx.bases,
+            &ctx.base_remote,
+            commands::merge::MergeRunOptions {
+                porcelain: ctx.cli.global.porcelain,
+                yes: ctx.cli.global.yes,
+                dry_run: args.dry_run,
+                method: args
+                    .method
+                    .as_deref()
+                    .and_then(MergeMethod::parse)
+                    .or(ctx.config.merge_method)
+                    .unwrap_or(MergeMethod::Merge),
+                plan: sync_plan_options(ctx),
+            },
+        ),
+        Some(Commands::Refresh) => commands::refresh::run(
+            &ctx.db,
+            &ctx.git,
+            provider,
+            &ctx.bases,
             ctx.cli.global.porcelain,
-            &ctx.base_branch,
         ),
         Some(Commands::Top) => commands::nav::run(
             &ctx.db,
@@ -164,5 +292,82 @@ fn dispatch(ctx: &AppContext) -> Result<()> {
             ctx.cli.global.porcelain,
         ),
         Some(Commands::Completions(args)) => commands::completions::run(args.shell),
+        Some(Commands::Metadata(args)) => commands::metadata::run(
+            &ctx.db,
+            &ctx.git,
+            &args.action,
+            &ctx.base_branch,
+            ctx.cli.global.porcelain,
+        ),
+        Some(Commands::Export(args)) => commands::export::run(&ctx.db, &ctx.git, args, &ctx.bases),
+        Some(Commands::Import(args)) => commands::import::run(
+            &ctx.db,
+            &ctx.git,
+            args,
+            &ctx.base_branch,
+            ctx.cli.global.porcelain,
+        ),
+        Some(Commands::Base(args)) => commands::base::run(
+            &ctx.db,
+            &ctx.git,
+            provider,
+            &args.action,
+            &ctx.bases,
+            ctx.cli.global.porcelain,
+        ),
+        Some(Commands::Annotate(args)) => {
+            commands::annotate::run(&ctx.db, &ctx.git, args, ctx.cli.global.porcelain)
+        }
+        Some(Commands::Freeze(args)) => commands::freeze::run(
+            &ctx.db,
+            &ctx.git,
+            args.branch.as_deref(),
+            &ctx.bases,
+            true,
+            ctx.cli.global.porcelain,
+        ),
+        Some(Commands::Unfreeze(args)) => commands::freeze::run(
+            &ctx.db,
+            &ctx.git,
+            args.branch.as_deref(),
+            &ctx.bases,
+            false,
+            ctx.cli.global.porcelain,
+        ),
+        Some(Commands::History(args)) => {
+            commands::history::run(&ctx.db, args, ctx.cli.global.porcelain)
+        }
+        Some(Commands::Db(_) | Commands::Init(_)) => {
+            unreachable!("db and init commands run before the database is opened")
+        }
+    }
+}
+
+/// `--hostname` when given, otherwise the base remote's host when it is not
+/// github.com, so `gh` talks to the right GitHub Enterprise instance.
+fn provider_hostname(
+    git: &Git,
+    base_remote: &str,
+    explicit: Option<&str>,
+) -> Result<Option<String>> {
+    if let Some(host) = explicit {
+        return Ok(Some(host.trim().to_ascii_lowercase()));
+    }
+    Ok(git
+        .remote_web_url(base_remote)?
+        .as_deref()
+        .and_then(web_url_host)
+        .filter(|host| !host.eq_ignore_ascii_case(GITHUB_HOST))
+        .map(str::to_ascii_lowercase))
+}
+
+fn sync_plan_options(ctx: &AppContext) -> SyncPlanOptions {
+    let config = &ctx.config;
+    SyncPlanOptions {
+        body_style: config.pr_body.clone(),
+        title_prefix: config.pr_title_prefix.clone(),
+        draft_policy: config.pr_draft_policy,
+        order_status: config.order_status,
+        offline: ctx.cli.global.offline,
     }
 }
@@ -34,10 +34,31 @@ pub struct GlobalArgs {
     pub interactive: bool,
     #[arg(long, global = true, help = "Print detailed provider/debug errors")]
     pub debug: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Use cached PR metadata only; never contact the provider"
+    )]
+    pub offline: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "HOST",
+        help = "GitHub host for provider calls (defaults to the base remote's mapped host)"
+    )]
+    pub hostname: Option<String>,
+    #[arg(
+        long,
+        global = true,
+        help = "Wait for another running stack command instead of failing"
+    )]
+    pub wait: bool,
 }
 
 #[derive(Debug, Subcommand)]
 pub enum Commands {
+    /// Choose the base (trunk) branch for this repository
+    Init(InitArgs),
     /// Add a branch to the stack
     Create(CreateArgs),
     /// Track existing branch relationships
@@ -50,10 +71,14 @@ pub enum Commands {
     Untrack(UntrackArgs),
     /// Delete a branch and splice it out of the stack
     Delete(DeleteArgs),
-    /// Create a pull request for the current branch
+    /// Create a pull request for the current branch, or change PR states
     Pr(PrArgs),
     /// Push tracked branches with force-with-lease
     Push,
+    /// Merge stack PRs bottom-up and restack the remaining branches
+    Merge(MergeArgs),
+    /// Re-fetch cached PR metadata for tracked branches
+    Refresh,
     /// Switch to the highest descendant in the current stack path
     Top,
     /// Switch to the stack root ancestor for the current branch
@@ -64,6 +89,24 @@ pub enum Commands {
     Down,
     /// Generate shell completion scripts
     Completions(CompletionsArgs),
+    /// Inspect the stack database and restore its backups
+    Db(DbArgs),
+    /// Share stack metadata through a git ref on the remote
+    Metadata(MetadataArgs),
+    /// Write tracked branches and parent links as a JSON document
+    Export(ExportArgs),
+    /// Recreate parent links from a `stack export` document
+    Import(ImportArgs),
+    /// List or change the base branches stacks sit on
+    Base(BaseArgs),
+    /// Set or show a branch's description, ticket and owner
+    Annotate(AnnotateArgs),
+    /// Stop sync, push and PR edits from rewriting a branch
+    Freeze(FreezeArgs),
+    /// Let sync, push and PR edits rewrite a frozen branch again
+    Unfreeze(FreezeArgs),
+    /// Show recorded sync runs and the ops each one executed
+    History(HistoryArgs),
 }
 
 #[derive(Debug, Args)]
@@ -133,15 +176,277 @@ pub struct DeleteArgs {
 }
 
 #[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct PrArgs {
-    #[arg(short = 't', long, help = "PR title")]
+    #[command(subcommand)]
+    pub action: Option<PrAction>,
+    #[arg(
+        short = 't',
+        long,
+        help = "PR title (defaults to the single commit subject or the branch name)"
+    )]
     pub title: Option<String>,
-    #[arg(short = 'b', long, help = "PR body")]
+    #[arg(
+        short = 'b',
+        long,
+        help = "PR body (defaults to commit messages plus the repo PR template)"
+    )]
     pub body: Option<String>,
     #[arg(short = 'd', long, help = "Create draft PR")]
     pub draft: bool,
     #[arg(short = 'n', long, help = "Preview command without calling gh")]
     pub dry_run: bool,
+    #[arg(long, help = "Print the PR body that would be used and exit")]
+    pub preview_body: bool,
+    #[arg(
+        short = 'e',
+        long,
+        help = "Edit the composed PR body in $EDITOR before pushing"
+    )]
+    pub edit: bool,
+    #[arg(
+        long = "reviewer",
+        value_name = "LOGIN",
+        value_delimiter = ',',
+        help = "Request a review from a user or org/team (repeatable)"
+    )]
+    pub reviewers: Vec<String>,
+    #[arg(
+        long = "label",
+        value_name = "NAME",
+        value_delimiter = ',',
+        help = "Add a label (repeatable)"
+    )]
+    pub labels: Vec<String>,
+    #[arg(
+        long = "assignee",
+        value_name = "LOGIN",
+        value_delimiter = ',',
+        help = "Assign a user (repeatable)"
+    )]
+    pub assignees: Vec<String>,
+    #[arg(long, value_name = "NAME", help = "Add the PR to a milestone")]
+    pub milestone: Option<String>,
+    #[arg(
+        long,
+        help = "Copy reviewers, labels, assignees and milestone from the parent branch's PR"
+    )]
+    pub inherit: bool,
+    #[arg(
+        long,
+        help = "Request reviews from the CODEOWNERS of the files this branch changes"
+    )]
+    pub codeowners: bool,
+    #[arg(
+        long,
+        help = "Create the PR through the provider instead of opening a compare link"
+    )]
+    pub create: bool,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum PrAction {
+    /// Mark PRs ready for review
+    Ready(PrTargetArgs),
+    /// Convert PRs back to drafts
+    Draft(PrTargetArgs),
+    /// Close PRs, keeping their branches
+    Close(PrTargetArgs),
+    /// Reopen closed PRs
+    Reopen(PrTargetArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct PrTargetArgs {
+    #[arg(
+        short = 'b',
+        long,
+        value_name = "BRANCH",
+        help = "Branch whose PR to change (defaults to current branch)"
+    )]
+    pub branch: Option<String>,
+    #[arg(short = 's', long, help = "Apply to every PR in the branch's stack")]
+    pub stack: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct MergeArgs {
+    #[arg(
+        short = 'u',
+        long,
+        value_name = "BRANCH",
+        help = "Last branch to merge (defaults to current branch)"
+    )]
+    pub until: Option<String>,
+    #[arg(
+        short = 'm',
+        long,
+        value_parser = ["merge", "squash", "rebase"],
+        help = "Merge method (defaults to stack.mergeMethod, then merge)"
+    )]
+    pub method: Option<String>,
+    #[arg(short = 'n', long, help = "Preview merge order without merging")]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct DbArgs {
+    #[command(subcommand)]
+    pub action: DbAction,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum DbAction {
+    /// Show the schema version and pending migrations without applying them
+    Info,
+    /// List the automatic snapshots taken before migrations and bulk rewrites
+    Backups,
+    /// Replace the database with a snapshot, previewing changed parent links
+    Restore(DbRestoreArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct DbRestoreArgs {
+    #[arg(help = "Backup id from `stack db backups`")]
+    pub id: u64,
+}
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    #[arg(long, help = "Only export the stack containing the current branch")]
+    pub stack: bool,
+    #[arg(
+        short = 'o',
+        long,
+        value_name = "FILE",
+        help = "Write to a file instead of stdout"
+    )]
+    pub output: Option<std::path::PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct ImportArgs {
+    #[arg(help = "Document written by `stack export` (`-` reads stdin)")]
+    pub file: std::path::PathBuf,
+    #[arg(
+        short = 'n',
+        long,
+        help = "Validate and show changes without applying them"
+    )]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct InitArgs {
+    #[arg(
+        long,
+        value_name = "BRANCH",
+        help = "Base branch to use (defaults to origin/HEAD, or prompts in a terminal)"
+    )]
+    pub base: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct BaseArgs {
+    #[command(subcommand)]
+    pub action: BaseAction,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum BaseAction {
+    /// Show each base branch and the stack roots sitting on it
+    List,
+    /// Change the trunk, or with --root move one stack onto another base
+    Set(BaseSetArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct BaseSetArgs {
+    #[arg(help = "New base branch (the trunk unless --root is given)")]
+    pub base: String,
+    #[arg(
+        long,
+        value_name = "BRANCH",
+        help = "Any branch of the stack to move; its stack root is updated"
+    )]
+    pub root: Option<String>,
+    #[arg(long, help = "Rebase the affected stack roots onto the new base")]
+    pub restack: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct FreezeArgs {
+    #[arg(help = "Branch to change (defaults to the current branch)")]
+    pub branch: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct HistoryArgs {
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 10,
+        help = "Number of recent runs to list"
+    )]
+    pub last: usize,
+    #[arg(
+        long,
+        value_name = "ID",
+        conflicts_with = "last",
+        help = "Show one run with its per-op timings and SHAs"
+    )]
+    pub run: Option<i64>,
+}
+
+#[derive(Debug, Args)]
+pub struct AnnotateArgs {
+    #[arg(help = "Branch to annotate (defaults to the current branch)")]
+    pub branch: Option<String>,
+    #[arg(
+        short = 'd',
+        long,
+        value_name = "TEXT",
+        help = "Free-text description (empty clears it)"
+    )]
+    pub description: Option<String>,
+    #[arg(
+        long,
+        value_name = "KEY",
+        help = "Issue or ticket key, e.g. ABC-123 (empty clears it)"
+    )]
+    pub ticket: Option<String>,
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Owner of the branch (empty clears it)"
+    )]
+    pub owner: Option<String>,
+    #[arg(long, help = "Clear all annotations before applying the other flags")]
+    pub clear: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct MetadataArgs {
+    #[command(subcommand)]
+    pub action: MetadataAction,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum MetadataAction {
+    /// Snapshot parent links and PR cache into refs/stack/metadata and push it
+    Push(MetadataRemoteArgs),
+    /// Fetch refs/stack/metadata and three-way merge it into the local stack
+    Pull(MetadataRemoteArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct MetadataRemoteArgs {
+    #[arg(
+        long,
+        value_name = "REMOTE",
+        help = "Remote to exchange metadata with (defaults to the push remote)"
+    )]
+    pub remote: Option<String>,
 }
 
 #[derive(Debug, Args)]
@@ -0,0 +1,64 @@
+use anyhow::{Result, anyhow};
+
+use crate::args::AnnotateArgs;
+use crate::db::{BranchAnnotations, Database};
+use crate::git::Git;
+
+pub fn run(db: &Database, git: &Git, args: &AnnotateArgs, porcelain: bool) -> Result<()> {
+    let branch = match &args.branch {
+        Some(branch) => branch.clone(),
+        None => git.current_branch()?,
+    };
+    let record = db
+        .branch_by_name(&branch)?
+        .ok_or_else(|| anyhow!("branch '{branch}' is not tracked; run `stack track` first"))?;
+
+    let previous = record.annotations();
+    let mut annotations = if args.clear {
+        BranchAnnotations::default()
+    } else {
+        previous.clone()
+    };
+    for (field, value) in [
+        (&mut annotations.description, &args.description),
+        (&mut annotations.ticket, &args.ticket),
+        (&mut annotations.owner, &args.owner),
+    ] {
+        if let Some(value) = value {
+            let value = value.trim();
+            *field = (!value.is_empty()).then(|| value.to_string());
+        }
+    }
+
+    let changed = annotations != previous;
+    if changed {
+        db.set_annotations(&branch, &annotations)?;
+    }
+
+    if porcelain {
+        return crate::views::print_json(&serde_json::json!({
+            "branch": branch,
+            "description": annotations.description,
+            "ticket": annotations.ticket,
+            "owner": annotations.owner,
+            "changed": changed,
+        }));
+    }
+    if changed {
+        println!("updated annotations for '{branch}'");
+    }
+    if annotations == BranchAnnotations::default() {
+        println!("'{branch}' has no annotations");
+        return Ok(());
+    }
+    for (label, value) in [
+        ("description", &annotations.description),
+        ("ticket", &annotations.ticket),
+        ("owner", &annotations.owner),
+    ] {
+        if let Some(value) = value {
+            println!("{label}: {value}");
+        }
+    }
+    Ok(())
+}
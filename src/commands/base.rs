@@ -0,0 +1,339 @@
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow};
+
+use crate::args::{BaseAction, BaseSetArgs};
+use crate::core::StackBases;
+use crate::db::{BranchRecord, Database};
+use crate::git::Git;
+use crate::provider::{PrState, Provider};
+
+pub fn run(
+    db: &Database,
+    git: &Git,
+    provider: &dyn Provider,
+    action: &BaseAction,
+    bases: &StackBases,
+    porcelain: bool,
+) -> Result<()> {
+    match action {
+        BaseAction::List => list(db, bases, porcelain),
+        BaseAction::Set(args) => set(db, git, provider, args, bases, porcelain),
+    }
+}
+
+/// Tracked branches whose parent is `base`, i.e. the roots of its stacks.
+fn roots_on<'a>(records: &'a [BranchRecord], base: &str) -> Vec<&'a str> {
+    let Some(base_id) = records.iter().find(|r| r.name == base).map(|r| r.id) else {
+        return Vec::new();
+    };
+    records
+        .iter()
+        .filter(|r| r.parent_branch_id == Some(base_id))
+        .map(|r| r.name.as_str())
+        .collect()
+}
+
+fn list(db: &Database, bases: &StackBases, porcelain: bool) -> Result<()> {
+    let records = db.list_branches()?;
+    if porcelain {
+        let out = bases
+            .names()
+            .into_iter()
+            .map(|base| {
+                serde_json::json!({
+                    "base": base,
+                    "default": base == bases.default_branch(),
+                    "roots": roots_on(&records, base),
+                })
+            })
+            .collect::<Vec<_>>();
+        return crate::views::print_json(&out);
+    }
+    for base in bases.names() {
+        let label = if base == bases.default_branch() {
+            " (default)"
+        } else {
+            ""
+        };
+        println!("{base}{label}");
+        let roots = roots_on(&records, base);
+        if roots.is_empty() {
+            println!("  (no stacks)");
+        }
+        for root in roots {
+            println!("  {root}");
+        }
+    }
+    Ok(())
+}
+
+fn set(
+    db: &Database,
+    git: &Git,
+    provider: &dyn Provider,
+    args: &BaseSetArgs,
+    bases: &StackBases,
+    porcelain: bool,
+) -> Result<()> {
+    let base = args.base.as_str();
+    if !git.branch_exists(base)? {
+        return Err(anyhow!(
+            "base branch '{base}' does not exist locally; create it first (e.g. `git branch {base} origin/{base}`)"
+        ));
+    }
+    let records = db.list_branches()?;
+    if let Some(record) = records.iter().find(|r| r.name == base)
+        && !bases.contains(base)
+        && record.parent_branch_id.is_some()
+    {
+        return Err(anyhow!(
+            "'{base}' is a stacked branch; choose a branch outside the stack as its base"
+        ));
+    }
+    let Some(branch) = args.root.as_deref() else {
+        return set_trunk(db, git, provider, args, bases, porcelain);
+    };
+
+    let by_id: HashMap<i64, &BranchRecord> = records.iter().map(|r| (r.id, r)).collect();
+    if bases.contains(branch) {
+        return Err(anyhow!(
+            "'{branch}' is a base branch; pass a branch of the stack to move"
+        ));
+    }
+    let mut root = records
+        .iter()
+        .find(|r| r.name == branch)
+        .ok_or_else(|| anyhow!("branch '{branch}' is not tracked; run `stack track` first"))?;
+    while let Some(parent) = root.parent_branch_id.and_then(|id| by_id.get(&id))
+        && !bases.contains(&parent.name)
+    {
+        root = parent;
+    }
+    let previous_base = bases.base_for(&records, &root.name).to_string();
+
+    let changed = previous_base != base;
+    if changed {
+        db.transaction(|| {
+            db.upsert_branch(base)?;
+            db.set_parent(&root.name, Some(base))?;
+            let declared = (base != bases.default_branch()).then_some(base);
+            db.set_stack_base(&root.name, declared)?;
+            release_unused_base(db, bases, &previous_base)
+        })?;
+    }
+
+    let mut retargeted = None;
+    if !root.frozen
+        && let Some(pr) = provider.resolve_pr_by_head(&root.name, root.cached_pr_number)?
+        && matches!(pr.state, PrState::Open)
+        && pr.base_ref_name.as_deref() != Some(base)
+    {
+        provider.update_pr_base(pr.number, base)?;
+        retargeted = Some(pr.number);
+    }
+
+    // Frozen roots keep their history and PR; only the recorded base changes.
+    let restacked = args.restack
+        && !root.frozen
+        && !restack_roots(git, &[&root.name], &previous_base, base)?.is_empty();
+
+    if porcelain {
+        return crate::views::print_json(&serde_json::json!({
+            "root": root.name,
+            "base": base,
+            "previous_base": previous_base,
+            "changed": changed,
+            "retargeted": retargeted,
+            "restacked": restacked,
+        }));
+    }
+    if !changed {
+        println!("stack '{}' already targets '{base}'", root.name);
+    } else {
+        println!(
+            "stack '{}' now targets '{base}' (was '{previous_base}')",
+            root.name
+        );
+    }
+    if let Some(number) = retargeted {
+        println!("retargeted PR #{number} to '{base}'");
+    }
+    if restacked {
+        println!(
+            "rebased '{}' onto '{base}'; run `stack sync` to restack its descendants",
+            root.name
+        );
+    } else if !git.is_ancestor(base, &root.name)? {
+        println!(
+            "'{}' is not based on '{base}' yet; rerun with --restack or rebase it, then run `stack sync`",
+            root.name
+        );
+    }
+    Ok(())
+}
+
+/// Stops tracking a declared base once no stack sits on it, so it is not
+/// mistaken for a stacked branch afterwards.
+fn release_unused_base(db: &Database, bases: &StackBases, base: &str) -> Result<()> {
+    if base == bases.default_branch() {
+        return Ok(());
+    }
+    let still_declared = db
+        .stack_base_declarations()?
+        .iter()
+        .any(|(_, declared)| declared == base);
+    if !still_declared && roots_on(&db.list_branches()?, base).is_empty() {
+        db.delete_branch(base)?;
+    }
+    Ok(())
+}
+
+/// Changes the repository default base: stacks on the old trunk are
+/// reparented onto `args.base`, and stacks that declared it as their own
+/// base fall back to the default.
+fn set_trunk(
+    db: &Database,
+    git: &Git,
+    provider: &dyn Provider,
+    args: &BaseSetArgs,
+    bases: &StackBases,
+    porcelain: bool,
+) -> Result<()> {
+    let base = args.base.as_str();
+    let previous_base = bases.default_branch().to_string();
+    let records = db.list_branches()?;
+    let roots = if base == previous_base {
+        Vec::new()
+    } else {
+        roots_on(&records, &previous_base)
+    };
+
+    if base != previous_base {
+        db.transaction(|| {
+            db.set_base_branch(base)?;
+            db.upsert_branch(base)?;
+            for root in &roots {
+                db.set_parent(root, Some(base))?;
+            }
+            for (root, declared) in db.stack_base_declarations()? {
+                if declared == base {
+                    db.set_stack_base(&root, None)?;
+                }
+            }
+            // The old trunk is an ordinary branch now; stop tracking it so it
+            // is never pushed or given a PR.
+            if roots_on(&db.list_branches()?, &previous_base).is_empty() {
+                db.delete_branch(&previous_base)?;
+            }
+            Ok(())
+        })?;
+    }
+
+    // Frozen roots keep their PRs untouched, like their history below.
+    let mut retargeted = Vec::new();
+    for record in records
+        .iter()
+        .filter(|r| roots.contains(&r.name.as_str()) && !r.frozen)
+    {
+        if let Some(pr) = provider.resolve_pr_by_head(&record.name, record.cached_pr_number)?
+            && matches!(pr.state, PrState::Open)
+            && pr.base_ref_name.as_deref() != Some(base)
+        {
+            provider.update_pr_base(pr.number, base)?;
+            retargeted.push(pr.number);
+        }
+    }
+
+    let restacked = if args.restack {
+        let unfrozen = roots
+            .iter()
+            .copied()
+            .filter(|root| !records.iter().any(|r| r.name == *root && r.frozen))
+            .collect::<Vec<_>>();
+        restack_roots(git, &unfrozen, &previous_base, base)?
+    } else {
+        Vec::new()
+    };
+
+    if porcelain {
+        return crate::views::print_json(&serde_json::json!({
+            "base": base,
+            "previous_base": previous_base,
+            "changed": base != previous_base,
+            "reparented": roots,
+            "retargeted": retargeted,
+            "restacked": restacked,
+        }));
+    }
+    if base == previous_base {
+        println!("trunk is already '{base}'");
+        return Ok(());
+    }
+    println!(
+        "trunk changed from '{previous_base}' to '{base}'; reparented {} stack root(s)",
+        roots.len()
+    );
+    for root in &roots {
+        if restacked.contains(root) {
+            println!("  {root} (rebased onto '{base}')");
+        } else {
+            println!("  {root}");
+        }
+    }
+    for number in &retargeted {
+        println!("retargeted PR #{number} to '{base}'");
+    }
+    let behind = roots
+        .iter()
+        .filter(|root| !restacked.contains(root))
+        .filter(|root| !git.is_ancestor(base, root).unwrap_or(true))
+        .count();
+    if behind > 0 {
+        println!(
+            "{behind} root(s) are not based on '{base}' yet; rerun with --restack or rebase them, then run `stack sync`"
+        );
+    } else if !restacked.is_empty() {
+        println!("run `stack sync` to restack their descendants");
+    }
+    Ok(())
+}
+
+/// Rebases each root not yet based on `base` from its fork point with
+/// `previous_base` (or with `base` when the old branch is gone, e.g. after a
+/// rename). Returns the rebased roots; the starting branch is checked out
+/// again afterwards, also when a conflicting rebase is aborted.
+fn restack_roots<'a>(
+    git: &Git,
+    roots: &[&'a str],
+    previous_base: &str,
+    base: &str,
+) -> Result<Vec<&'a str>> {
+    let starting_branch = git.current_branch()?;
+    let fork_ref = if git.branch_exists(previous_base)? {
+        previous_base
+    } else {
+        base
+    };
+    let mut restacked = Vec::new();
+    for &root in roots {
+        if git.is_ancestor(base, root)? {
+            continue;
+        }
+        let old_base = git.merge_base(root, fork_ref)?;
+        if let Err(err) = git.rebase_onto(root, &old_base, base) {
+            git.abort_rebase()?;
+            if !starting_branch.is_empty() {
+                git.checkout_branch(&starting_branch)?;
+            }
+            return Err(err.context(format!(
+                "failed to rebase '{root}' onto '{base}'; the rebase was aborted"
+            )));
+        }
+        restacked.push(root);
+    }
+    if !restacked.is_empty() && !starting_branch.is_empty() {
+        git.checkout_branch(&starting_branch)?;
+    }
+    Ok(restacked)
+}
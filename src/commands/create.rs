@@ -1,26 +1,28 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::io::{IsTerminal, stdin, stdout};
 
 use anyhow::{Context, Result, anyhow};
 use crossterm::style::Stylize;
 use dialoguer::{Input, Select, theme::ColorfulTheme};
 
-use crate::core::rank_parent_candidates;
+use crate::args::CreateArgs;
+use crate::core::{
+    StackBases, SyncPlanOptions, rank_parent_candidates, refresh_managed_prs, stack_branch_names,
+};
 use crate::db::{BranchRecord, Database};
 use crate::git::Git;
-use crate::provider::{PrState, Provider};
+use crate::provider::Provider;
 use crate::ui::interaction::prompt_or_cancel;
 use crate::ui::pickers::build_branch_picker_items;
-use crate::util::pr_body::{ManagedBranchRef, managed_pr_section, merge_managed_pr_section};
+use crate::util::pr_body::ManagedSectionFormat;
 use crate::util::terminal::osc8_hyperlink;
 
 pub fn run(
     db: &Database,
     git: &Git,
     provider: &dyn Provider,
-    parent_arg: &Option<String>,
-    insert_arg: &Option<String>,
-    name_arg: &Option<String>,
+    args: &CreateArgs,
+    plan: &SyncPlanOptions,
     porcelain: bool,
 ) -> Result<()> {
     let current = git.current_branch()?;
@@ -30,7 +32,7 @@ pub fn run(
     let picker_items = build_branch_picker_items(&parent_candidates, &current, &tracked);
     let theme = ColorfulTheme::default();
 
-    let (parent, inserted_before) = if let Some(insert_value) = insert_arg {
+    let (parent, inserted_before) = if let Some(insert_value) = &args.insert {
         let child = resolve_insert_target(&tracked, git, insert_value, porcelain, &theme)?;
         let child_record = tracked
             .iter()
@@ -44,7 +46,7 @@ pub fn run(
             .ok_or_else(|| anyhow!("cannot insert before '{child}': branch has no parent"))?;
         (parent, Some(child))
     } else {
-        let parent = if let Some(parent) = parent_arg {
+        let parent = if let Some(parent) = &args.parent {
             parent.clone()
         } else if parent_candidates.len() == 1 {
             let assumed = parent_candidates[0].clone();
@@ -85,7 +87,7 @@ pub fn run(
         return Err(anyhow!("parent branch does not exist in git: {parent}"));
     }
 
-    let child = if let Some(name) = name_arg {
+    let child = if let Some(name) = &args.name {
         name.clone()
     } else if stdout().is_terminal() && stdin().is_terminal() {
         prompt_or_cancel(
@@ -129,15 +131,21 @@ pub fn run(
     let create_url = String::new();
     db.set_sync_sha(&child, &child_sha)?;
 
-    if let Some(before) = inserted_before.as_deref() {
-        let base_branch = db.repo_meta()?.base_branch;
-        refresh_managed_pr_bodies(
-            db,
-            git,
-            provider,
-            &base_branch,
-            &[parent.clone(), before.to_string()],
-        )?;
+    // Chain sections only mention direct neighbours; other layouts and title
+    // prefixes depend on the whole stack.
+    let whole_stack = plan.title_prefix.is_some()
+        || plan.body_style.template.is_some()
+        || plan.body_style.format != ManagedSectionFormat::Chain;
+    if inserted_before.is_some() || plan.title_prefix.is_some() {
+        let bases = StackBases::load(db)?;
+        let targets = match inserted_before.as_deref() {
+            Some(before) if !whole_stack => vec![parent.clone(), before.to_string()],
+            _ => stack_branch_names(&db.list_branches()?, &bases, &child)
+                .into_iter()
+                .filter(|name| *name != child)
+                .collect(),
+        };
+        refresh_managed_prs(db, git, provider, &bases, plan, &targets)?;
     }
 
     let out = serde_json::json!({
@@ -236,100 +244,6 @@ fn resolve_insert_target(
     Ok(candidates[idx].clone())
 }
 
-fn refresh_managed_pr_bodies(
-    db: &Database,
-    git: &Git,
-    provider: &dyn Provider,
-    base_branch: &str,
-    branches: &[String],
-) -> Result<()> {
-    let tracked = db.list_branches()?;
-    let branch_exists: HashMap<String, bool> = tracked
-        .iter()
-        .map(|b| Ok((b.name.clone(), git.branch_exists(&b.name)?)))
-        .collect::<Result<HashMap<_, _>>>()?;
-    let metadata_targets: Vec<(&str, Option<i64>)> = tracked
-        .iter()
-        .filter(|branch| branch.name != base_branch)
-        .filter(|branch| branch_exists.get(&branch.name).copied().unwrap_or(false))
-        .map(|branch| (branch.name.as_str(), branch.cached_pr_number))
-        .collect();
-    let pr_by_branch = provider.resolve_prs_by_head(&metadata_targets)?;
-
-    let fallback_base_url = git
-        .remote_web_url("origin")?
-        .or_else(|| git.remote_web_url("upstream").ok().flatten());
-    let by_id: HashMap<i64, &BranchRecord> = tracked.iter().map(|r| (r.id, r)).collect();
-    let mut children: HashMap<i64, Vec<&BranchRecord>> = HashMap::new();
-    for branch in &tracked {
-        if let Some(parent_id) = branch.parent_branch_id {
-            children.entry(parent_id).or_default().push(branch);
-        }
-    }
-
-    let mut unique_targets = HashSet::new();
-    for branch_name in branches {
-        if !unique_targets.insert(branch_name.clone()) {
-            continue;
-        }
-        let Some(record) = tracked.iter().find(|r| r.name == *branch_name) else {
-            continue;
-        };
-        let Some(pr) = pr_by_branch.get(branch_name) else {
-            continue;
-        };
-        if !matches!(pr.state, PrState::Open) {
-            continue;
-        }
-
-        let pr_root = pr
-            .url
-            .as_deref()
-            .and_then(repo_root_from_pr_url)
-            .or(fallback_base_url.as_deref())
-            .ok_or_else(|| anyhow!("could not determine PR repository URL for '{branch_name}'"))?;
-        let parent_ref = record
-            .parent_branch_id
-            .and_then(|parent_id| by_id.get(&parent_id).copied())
-            .map(|parent| ManagedBranchRef {
-                branch: parent.name.clone(),
-                pr_number: pr_by_branch.get(&parent.name).map(|p| p.number),
-                pr_url: pr_by_branch.get(&parent.name).and_then(|p| p.url.clone()),
-            });
-        let first_child = children.get(&record.id).and_then(|items| {
-            items
-                .iter()
-                .map(|child| ManagedBranchRef {
-                    branch: child.name.clone(),
-                    pr_number: pr_by_branch.get(&child.name).map(|p| p.number),
-                    pr_url: pr_by_branch.get(&child.name).and_then(|p| p.url.clone()),
-                })
-                .min_by(|a, b| a.branch.cmp(&b.branch))
-        });
-        let base_commit_url = git
-            .merge_base(branch_name, base_branch)
-            .ok()
-            .map(|sha| format!("{}/commit/{sha}", pr_root.trim_end_matches('/')));
-        let managed = managed_pr_section(
-            pr_root,
-            base_branch,
-            base_commit_url.as_deref(),
-            parent_ref.as_ref(),
-            first_child.as_ref(),
-        );
-        let merged = merge_managed_pr_section(pr.body.as_deref(), &managed);
-        if pr.body.as_deref().map(str::trim) != Some(merged.trim()) {
-            provider.update_pr_body(pr.number, &merged)?;
-        }
-    }
-
-    Ok(())
-}
-
-fn repo_root_from_pr_url(url: &str) -> Option<&str> {
-    url.split_once("/pull/").map(|(root, _)| root)
-}
-
 fn db_summary_path(git: &Git) -> Result<String> {
     Ok(git.git_dir()?.join("stack.db").display().to_string())
 }
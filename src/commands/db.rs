@@ -0,0 +1,228 @@
+use std::io::{IsTerminal, stdin, stdout};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+
+use crate::args::{DbAction, DbArgs};
+use crate::db::backup::{self, BackupRecord, ParentLinkChange};
+use crate::db::{Database, MigrationRecord};
+use crate::ui::interaction::confirm_inline_yes_no;
+
+/// Runs before the database is opened so inspecting it never migrates it
+/// and a restore never races an open connection of this process.
+pub fn run(db_path: &Path, args: &DbArgs, yes: bool, porcelain: bool) -> Result<()> {
+    match &args.action {
+        DbAction::Info => info(db_path, porcelain),
+        DbAction::Backups => backups(db_path, porcelain),
+        DbAction::Restore(restore_args) => restore(db_path, restore_args.id, yes, porcelain),
+    }
+}
+
+fn info(db_path: &Path, porcelain: bool) -> Result<()> {
+    let info = Database::inspect(db_path)?;
+    if porcelain {
+        let records = |records: &[MigrationRecord]| {
+            records
+                .iter()
+                .map(|m| {
+                    serde_json::json!({
+                        "version": m.version,
+                        "description": m.description,
+                        "applied_at": m.applied_at,
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+        return crate::views::print_json(&serde_json::json!({
+            "path": db_path.display().to_string(),
+            "exists": db_path.exists(),
+            "schema_version": info.version,
+            "latest_version": info.latest_version,
+            "applied": records(&info.applied),
+            "pending": records(&info.pending),
+        }));
+    }
+
+    println!("database: {}", db_path.display());
+    if info.version > info.latest_version {
+        println!(
+            "schema version: {} (newer than this stack supports: {})",
+            info.version, info.latest_version
+        );
+    } else {
+        println!(
+            "schema version: {} (latest {})",
+            info.version, info.latest_version
+        );
+    }
+    if !info.applied.is_empty() {
+        println!("applied migrations:");
+        for m in &info.applied {
+            println!(
+                "  {:>3}  {} ({})",
+                m.version,
+                m.description,
+                m.applied_at.as_deref().unwrap_or("unknown")
+            );
+        }
+    }
+    if info.pending.is_empty() {
+        println!("pending migrations: none");
+    } else {
+        println!("pending migrations (applied by the next stack command):");
+        for m in &info.pending {
+            println!("  {:>3}  {}", m.version, m.description);
+        }
+    }
+    Ok(())
+}
+
+fn backups(db_path: &Path, porcelain: bool) -> Result<()> {
+    let backups = backup::list_backups(db_path)?;
+    if porcelain {
+        let backups = backups.iter().map(backup_json).collect::<Vec<_>>();
+        return crate::views::print_json(&serde_json::json!({
+            "directory": backup::backups_dir(db_path).display().to_string(),
+            "backups": backups,
+        }));
+    }
+    if backups.is_empty() {
+        println!(
+            "no backups yet; stack takes one before migrations, sync, `track --all` and `doctor --fix`"
+        );
+        return Ok(());
+    }
+    println!("backups in {}:", backup::backups_dir(db_path).display());
+    for b in backups.iter().rev() {
+        println!("  #{:<4} {}  {}", b.id, format_utc(b.created_at), b.reason);
+    }
+    Ok(())
+}
+
+fn restore(db_path: &Path, id: u64, yes: bool, porcelain: bool) -> Result<()> {
+    let target = backup::find_backup(db_path, id)?;
+    let changes = backup::parent_link_changes(db_path, &target)?;
+
+    if !porcelain {
+        println!(
+            "backup #{} ({}, {})",
+            target.id,
+            target.reason,
+            format_utc(target.created_at)
+        );
+        if changes.is_empty() {
+            println!("no parent links change");
+        } else {
+            println!("parent links that change:");
+            for change in &changes {
+                println!(
+                    "  {}: {} -> {}",
+                    change.branch,
+                    describe_link(change.current.as_ref()),
+                    describe_link(change.restored.as_ref())
+                );
+            }
+        }
+    }
+
+    let should_restore = if yes {
+        true
+    } else if stdout().is_terminal() && stdin().is_terminal() {
+        confirm_inline_yes_no(&format!("Restore backup #{id}?"))?
+    } else {
+        false
+    };
+
+    let mut saved = None;
+    if should_restore {
+        if db_path.exists() {
+            let conn = Connection::open(db_path)
+                .with_context(|| format!("failed to open sqlite at {}", db_path.display()))?;
+            saved = Some(backup::create_backup(&conn, db_path, "pre-restore")?);
+        }
+        backup::restore_backup(db_path, &target)?;
+        backup::prune_backups(db_path)?;
+    }
+
+    if porcelain {
+        let changes = changes.iter().map(change_json).collect::<Vec<_>>();
+        return crate::views::print_json(&serde_json::json!({
+            "backup": backup_json(&target),
+            "changes": changes,
+            "restored": should_restore,
+            "saved_backup": saved.as_ref().map(|b| b.id),
+        }));
+    }
+    match (should_restore, saved) {
+        (true, Some(saved)) => println!(
+            "restored backup #{id}; the previous database was saved as backup #{}",
+            saved.id
+        ),
+        (true, None) => println!("restored backup #{id}"),
+        (false, _) => println!("restore not applied; rerun with --yes to apply it"),
+    }
+    Ok(())
+}
+
+/// `main`, `(no parent)` or `untracked` for one side of a parent link.
+fn describe_link(link: Option<&Option<String>>) -> &str {
+    match link {
+        Some(Some(parent)) => parent,
+        Some(None) => "(no parent)",
+        None => "untracked",
+    }
+}
+
+fn backup_json(b: &BackupRecord) -> serde_json::Value {
+    serde_json::json!({
+        "id": b.id,
+        "created_at": b.created_at,
+        "reason": b.reason,
+        "path": b.path.display().to_string(),
+    })
+}
+
+fn change_json(change: &ParentLinkChange) -> serde_json::Value {
+    serde_json::json!({
+        "branch": change.branch,
+        "tracked": change.current.is_some(),
+        "parent": change.current.clone().flatten(),
+        "restored_tracked": change.restored.is_some(),
+        "restored_parent": change.restored.clone().flatten(),
+    })
+}
+
+/// `2026-02-22 14:05:09 UTC` for a Unix timestamp, without a date crate.
+fn format_utc(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Civil-from-days (Howard Hinnant), valid for the proleptic Gregorian calendar.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_utc;
+
+    #[test]
+    fn format_utc_renders_calendar_dates() {
+        assert_eq!(format_utc(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_utc(951_782_400), "2000-02-29 00:00:00 UTC");
+        assert_eq!(format_utc(1_771_769_109), "2026-02-22 14:05:09 UTC");
+    }
+}
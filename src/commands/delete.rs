@@ -5,26 +5,37 @@ use anyhow::{Result, anyhow};
 use dialoguer::{Select, theme::ColorfulTheme};
 
 use crate::args::DeleteArgs;
+use crate::core::{StackBases, SyncPlanOptions, refresh_managed_prs, stack_branch_names};
 use crate::db::{BranchRecord, Database};
 use crate::git::Git;
 use crate::provider::Provider;
 use crate::ui::interaction::{confirm_inline_yes_no, prompt_or_cancel};
 use crate::ui::pickers::build_delete_picker_items;
 
+pub struct DeleteRunOptions {
+    pub porcelain: bool,
+    pub yes: bool,
+    pub plan: SyncPlanOptions,
+}
+
 pub fn run(
     db: &Database,
     git: &Git,
     provider: &dyn Provider,
     args: &DeleteArgs,
-    porcelain: bool,
-    yes: bool,
-    base_branch: &str,
+    bases: &StackBases,
+    opts: DeleteRunOptions,
 ) -> Result<()> {
+    let DeleteRunOptions {
+        porcelain,
+        yes,
+        ref plan,
+    } = opts;
     let current = git.current_branch()?;
     let records = db.list_branches()?;
     let viable_names: Vec<String> = records
         .iter()
-        .filter(|r| r.name != base_branch)
+        .filter(|r| !bases.contains(&r.name))
         .map(|r| r.name.clone())
         .collect();
     let theme = ColorfulTheme::default();
@@ -66,7 +77,7 @@ pub fn run(
     let parent_name = branch
         .parent_branch_id
         .and_then(|id| by_id.get(&id).map(|b| b.name.as_str()))
-        .unwrap_or(base_branch)
+        .unwrap_or(bases.default_branch())
         .to_string();
 
     let mut pr_number = branch.cached_pr_number;
@@ -129,6 +140,13 @@ pub fn run(
 
     git.delete_local_branch(&branch.name)?;
     db.splice_out_branch(&branch.name)?;
+    // Every managed section mentions at least the deleted branch's
+    // neighbours, so the rest of its stack always needs a refresh.
+    let remaining: Vec<String> = stack_branch_names(&records, bases, &branch.name)
+        .into_iter()
+        .filter(|name| *name != branch.name)
+        .collect();
+    refresh_managed_prs(db, git, provider, bases, plan, &remaining)?;
 
     if porcelain {
         return crate::views::print_json(&serde_json::json!({
@@ -2,13 +2,18 @@ use std::collections::{HashMap, HashSet};
 
 use anyhow::Result;
 
+use crate::core::StackBases;
 use crate::db::{BranchRecord, Database};
 use crate::git::Git;
+use crate::util::url::github_repo_slug_from_web_url;
 use crate::views::DoctorIssueView;
 
 pub fn run(db: &Database, git: &Git, porcelain: bool, fix: bool) -> Result<()> {
+    if fix {
+        db.snapshot("doctor-fix");
+    }
     let mut records = db.list_branches()?;
-    let base_branch = db.repo_meta()?.base_branch;
+    let bases = StackBases::load(db)?;
     let mut issues = Vec::new();
     let mut clear_parent_fixes: HashSet<String> = HashSet::new();
     let mut clear_pr_cache_fixes: HashSet<String> = HashSet::new();
@@ -56,7 +61,7 @@ pub fn run(db: &Database, git: &Git, porcelain: bool, fix: bool) -> Result<()> {
     }
 
     for branch in &records {
-        if branch.name == base_branch && branch.parent_branch_id.is_some() {
+        if bases.contains(&branch.name) && branch.parent_branch_id.is_some() {
             issues.push(DoctorIssueView {
                 severity: "error".to_string(),
                 code: "base_has_parent".to_string(),
@@ -102,6 +107,8 @@ pub fn run(db: &Database, git: &Git, porcelain: bool, fix: bool) -> Result<()> {
         }
     }
 
+    issues.extend(remote_setting_issues(git, bases.default_branch())?);
+
     if fix {
         for branch_name in clear_parent_fixes {
             db.clear_parent(&branch_name)?;
@@ -132,6 +139,47 @@ pub fn run(db: &Database, git: &Git, porcelain: bool, fix: bool) -> Result<()> {
     Ok(())
 }
 
+/// Flags `stack.pushRemote`/`stack.baseRemote` naming missing remotes and a
+/// `stack.prRepo` that differs from the repository base is fetched from.
+fn remote_setting_issues(git: &Git, base_branch: &str) -> Result<Vec<DoctorIssueView>> {
+    let settings = git.remote_settings();
+    let mut issues = Vec::new();
+    for (key, remote) in [
+        ("stack.pushRemote", &settings.push_remote),
+        ("stack.baseRemote", &settings.base_remote),
+    ] {
+        if let Some(remote) = remote
+            && !git.has_remote(remote)?
+        {
+            issues.push(DoctorIssueView {
+                severity: "error".to_string(),
+                code: "unknown_remote".to_string(),
+                message: format!("{key} names remote '{remote}', which is not configured"),
+                branch: None,
+            });
+        }
+    }
+
+    if let Some(pr_repo) = &settings.pr_repo {
+        let base_remote = git.base_remote_for_stack(base_branch)?;
+        if let Some(base_repo) = git
+            .remote_web_url(&base_remote)?
+            .and_then(|url| github_repo_slug_from_web_url(&url))
+            && !base_repo.eq_ignore_ascii_case(pr_repo)
+        {
+            issues.push(DoctorIssueView {
+                severity: "warning".to_string(),
+                code: "pr_repo_mismatch".to_string(),
+                message: format!(
+                    "stack.prRepo is '{pr_repo}' but base branch '{base_branch}' is fetched from '{base_remote}' ({base_repo}); set stack.baseRemote to a remote of {pr_repo}"
+                ),
+                branch: None,
+            });
+        }
+    }
+    Ok(issues)
+}
+
 fn cycle_branches(records: &[BranchRecord]) -> HashSet<String> {
     let mut branches = HashSet::new();
     let mut by_id: HashMap<i64, &BranchRecord> = HashMap::new();
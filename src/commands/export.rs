@@ -0,0 +1,39 @@
+use std::fs;
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::args::ExportArgs;
+use crate::core::{StackBases, export_metadata, stack_branch_names};
+use crate::db::Database;
+use crate::git::Git;
+
+pub fn run(db: &Database, git: &Git, args: &ExportArgs, bases: &StackBases) -> Result<()> {
+    let mut doc = export_metadata(db)?;
+    if args.stack {
+        let current = git.current_branch()?;
+        if bases.contains(&current) {
+            return Err(anyhow!(
+                "no stack selected; switch to a stacked branch to use --stack"
+            ));
+        }
+        let records = db.list_branches()?;
+        let mut names = stack_branch_names(&records, bases, &current);
+        names.push(bases.base_for(&records, &current).to_string());
+        doc.branches.retain(|name, _| names.contains(name));
+        doc.pr_cache.retain(|name, _| names.contains(name));
+    }
+
+    let json = doc.to_json()?;
+    match &args.output {
+        Some(path) => {
+            fs::write(path, json).with_context(|| format!("failed to write {}", path.display()))?;
+            eprintln!(
+                "exported {} branch(es) to {}",
+                doc.branches.len(),
+                path.display()
+            );
+        }
+        None => print!("{json}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use anyhow::{Result, anyhow};
+
+use crate::core::StackBases;
+use crate::db::Database;
+use crate::git::Git;
+
+pub fn run(
+    db: &Database,
+    git: &Git,
+    branch: Option<&str>,
+    bases: &StackBases,
+    frozen: bool,
+    porcelain: bool,
+) -> Result<()> {
+    let branch = match branch {
+        Some(branch) => branch.to_string(),
+        None => git.current_branch()?,
+    };
+    if bases.contains(&branch) {
+        return Err(anyhow!(
+            "'{branch}' is a base branch; base branches are never rewritten"
+        ));
+    }
+    let record = db
+        .branch_by_name(&branch)?
+        .ok_or_else(|| anyhow!("branch '{branch}' is not tracked; run `stack track` first"))?;
+
+    let changed = record.frozen != frozen;
+    if changed {
+        db.set_frozen(&branch, frozen)?;
+    }
+
+    if porcelain {
+        return crate::views::print_json(&serde_json::json!({
+            "branch": branch,
+            "frozen": frozen,
+            "changed": changed,
+        }));
+    }
+    match (frozen, changed) {
+        (true, true) => {
+            println!("froze '{branch}'; sync only fast-forwards it and push and PR edits skip it")
+        }
+        (true, false) => println!("'{branch}' is already frozen"),
+        (false, true) => println!("unfroze '{branch}'"),
+        (false, false) => println!("'{branch}' is not frozen"),
+    }
+    Ok(())
+}
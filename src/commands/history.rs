@@ -0,0 +1,132 @@
+use anyhow::{Result, anyhow};
+
+use crate::args::HistoryArgs;
+use crate::db::{Database, SyncRunOpRecord, SyncRunRecord};
+
+pub fn run(db: &Database, args: &HistoryArgs, porcelain: bool) -> Result<()> {
+    if let Some(id) = args.run {
+        let run = db
+            .sync_run(id)?
+            .ok_or_else(|| anyhow!("no sync run #{id}; run `stack history` to list runs"))?;
+        let ops = db.sync_run_ops(id)?;
+        if porcelain {
+            return crate::views::print_json(&run_json(&run, &ops));
+        }
+        print_run(&run, &ops);
+        return Ok(());
+    }
+
+    let runs = db.sync_runs(args.last)?;
+    let mut entries = Vec::with_capacity(runs.len());
+    for run in runs {
+        let ops = db.sync_run_ops(run.id)?;
+        entries.push((run, ops));
+    }
+    if porcelain {
+        let runs = entries
+            .iter()
+            .map(|(run, ops)| run_json(run, ops))
+            .collect::<Vec<_>>();
+        return crate::views::print_json(&serde_json::json!({ "runs": runs }));
+    }
+    if entries.is_empty() {
+        println!("no sync runs recorded");
+        return Ok(());
+    }
+    for (run, ops) in &entries {
+        println!(
+            "#{:<4} {}  {:<8} {}",
+            run.id,
+            run.started_at,
+            run.status,
+            op_counts(ops)
+        );
+    }
+    Ok(())
+}
+
+fn print_run(run: &SyncRunRecord, ops: &[SyncRunOpRecord]) {
+    println!("sync run #{}: {}", run.id, run.status);
+    println!(
+        "started {}, finished {}",
+        run.started_at,
+        run.finished_at.as_deref().unwrap_or("never")
+    );
+    if ops.is_empty() {
+        println!("no ops recorded");
+    }
+    for op in ops {
+        let mut line = format!(
+            "  {:<8} {} {} {}",
+            op.status, op.kind, op.branch, op.details
+        );
+        if let Some(ms) = op.duration_ms {
+            line.push_str(&format!(" ({ms}ms)"));
+        }
+        if let (Some(before), Some(after)) = (&op.before_sha, &op.after_sha)
+            && before != after
+        {
+            line.push_str(&format!(" {} -> {}", short_sha(before), short_sha(after)));
+        }
+        println!("{line}");
+        if let Some(err) = &op.error {
+            println!("           error: {err}");
+        }
+    }
+    if let Some(err) = run_error(run) {
+        println!("error: {err}");
+    }
+}
+
+/// `3 ops (2 done, 1 skipped)`, counting each status that occurred.
+fn op_counts(ops: &[SyncRunOpRecord]) -> String {
+    if ops.is_empty() {
+        return "no ops recorded".to_string();
+    }
+    let parts = ["done", "skipped", "failed", "not_run"]
+        .iter()
+        .filter_map(|status| {
+            let count = ops.iter().filter(|op| op.status == *status).count();
+            (count > 0).then(|| format!("{count} {}", status.replace('_', " ")))
+        })
+        .collect::<Vec<_>>();
+    let noun = if ops.len() == 1 { "op" } else { "ops" };
+    format!("{} {noun} ({})", ops.len(), parts.join(", "))
+}
+
+fn run_error(run: &SyncRunRecord) -> Option<String> {
+    let summary: serde_json::Value = serde_json::from_str(run.summary_json.as_deref()?).ok()?;
+    summary["error"].as_str().map(str::to_string)
+}
+
+fn short_sha(sha: &str) -> &str {
+    &sha[..sha.len().min(7)]
+}
+
+fn run_json(run: &SyncRunRecord, ops: &[SyncRunOpRecord]) -> serde_json::Value {
+    let ops = ops
+        .iter()
+        .map(|op| {
+            serde_json::json!({
+                "position": op.position,
+                "kind": op.kind,
+                "branch": op.branch,
+                "onto": op.onto,
+                "details": op.details,
+                "status": op.status,
+                "duration_ms": op.duration_ms,
+                "before_sha": op.before_sha,
+                "after_sha": op.after_sha,
+                "error": op.error,
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::json!({
+        "id": run.id,
+        "started_at": run.started_at,
+        "finished_at": run.finished_at,
+        "status": run.status,
+        "error": run_error(run),
+        "ops": ops,
+    })
+}
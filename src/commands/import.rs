@@ -0,0 +1,174 @@
+use std::fs;
+use std::io::Read;
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::args::ImportArgs;
+use crate::core::{MetadataDoc, export_metadata};
+use crate::db::{Database, ParentUpdate};
+use crate::git::Git;
+
+struct LinkChange {
+    branch: String,
+    parent: Option<String>,
+    previous_parent: Option<String>,
+    tracked: bool,
+}
+
+pub fn run(
+    db: &Database,
+    git: &Git,
+    args: &ImportArgs,
+    base_branch: &str,
+    porcelain: bool,
+) -> Result<()> {
+    let raw = if args.file.as_os_str() == "-" {
+        let mut raw = String::new();
+        std::io::stdin()
+            .read_to_string(&mut raw)
+            .context("failed to read stack export from stdin")?;
+        raw
+    } else {
+        fs::read_to_string(&args.file)
+            .with_context(|| format!("failed to read {}", args.file.display()))?
+    };
+    let doc = MetadataDoc::from_json(&raw)?;
+    if doc.base_branch != base_branch {
+        eprintln!(
+            "warning: export uses base branch '{}' but this repository uses '{base_branch}'; keeping '{base_branch}'",
+            doc.base_branch
+        );
+    }
+
+    let mut missing = Vec::new();
+    for name in doc
+        .branches
+        .iter()
+        .flat_map(|(name, branch)| std::iter::once(name).chain(branch.parent.as_ref()))
+    {
+        if !missing.contains(name) && !git.branch_exists(name)? {
+            missing.push(name.clone());
+        }
+    }
+    if !missing.is_empty() {
+        missing.sort();
+        return Err(anyhow!(
+            "cannot import: {} missing in this clone; fetch or create them first",
+            missing
+                .iter()
+                .map(|name| format!("'{name}'"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    let current = export_metadata(db)?;
+    let changes = doc
+        .branches
+        .iter()
+        .filter_map(|(name, branch)| {
+            let existing = current.branches.get(name);
+            if existing.is_some_and(|e| e.parent == branch.parent) {
+                return None;
+            }
+            Some(LinkChange {
+                branch: name.clone(),
+                parent: branch.parent.clone(),
+                previous_parent: existing.and_then(|e| e.parent.clone()),
+                tracked: existing.is_some(),
+            })
+        })
+        .collect::<Vec<_>>();
+    let updates = changes
+        .iter()
+        .map(|change| ParentUpdate {
+            child_name: change.branch.clone(),
+            parent_name: change.parent.clone(),
+        })
+        .collect::<Vec<_>>();
+    db.check_parents_batch(&updates)
+        .context("cannot import: the exported links conflict with local links")?;
+
+    if !args.dry_run {
+        // One transaction, so a failure part-way leaves the database untouched.
+        db.transaction(|| {
+            db.set_parents_batch(&updates)?;
+            for (name, branch) in &doc.branches {
+                if branch.pr_number.is_some()
+                    && current
+                        .branches
+                        .get(name)
+                        .is_none_or(|e| e.pr_number != branch.pr_number)
+                {
+                    db.set_pr_cache(name, branch.pr_number, branch.pr_state.as_deref())?;
+                }
+                if branch.stack_base.is_some()
+                    && current
+                        .branches
+                        .get(name)
+                        .is_none_or(|e| e.stack_base != branch.stack_base)
+                {
+                    db.set_stack_base(name, branch.stack_base.as_deref())?;
+                }
+            }
+            for (branch, entry) in &doc.pr_cache {
+                let newer = current
+                    .pr_cache
+                    .get(branch)
+                    .is_none_or(|existing| entry.fetched_at > existing.fetched_at);
+                if newer {
+                    db.upsert_pr_cache_entry(&entry.to_entry(branch))?;
+                }
+            }
+            Ok(())
+        })?;
+    }
+
+    if porcelain {
+        let changes = changes
+            .iter()
+            .map(|change| {
+                serde_json::json!({
+                    "branch": change.branch,
+                    "parent": change.parent,
+                    "previous_parent": change.previous_parent,
+                    "newly_tracked": !change.tracked,
+                })
+            })
+            .collect::<Vec<_>>();
+        return crate::views::print_json(&serde_json::json!({
+            "dry_run": args.dry_run,
+            "base_branch": base_branch,
+            "changes": changes,
+        }));
+    }
+
+    if changes.is_empty() {
+        println!("nothing to import; all links already match");
+        return Ok(());
+    }
+    let verb = if args.dry_run { "would link" } else { "linked" };
+    for change in &changes {
+        let Some(parent) = change.parent.as_deref() else {
+            println!("{verb} '{}' as a root branch", change.branch);
+            continue;
+        };
+        match (&change.previous_parent, change.tracked) {
+            (_, false) => println!("{verb} '{}' -> '{parent}' (newly tracked)", change.branch),
+            (Some(previous), true) => println!(
+                "{verb} '{}' -> '{parent}' (was '{previous}')",
+                change.branch
+            ),
+            (None, true) => println!("{verb} '{}' -> '{parent}'", change.branch),
+        }
+    }
+    if args.dry_run {
+        println!(
+            "dry run: {} link(s) validated, nothing changed",
+            changes.len()
+        );
+    } else {
+        println!("imported {} link(s)", changes.len());
+    }
+    Ok(())
+}
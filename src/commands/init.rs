@@ -0,0 +1,71 @@
+use std::io::{IsTerminal, stdin, stdout};
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use dialoguer::{Select, theme::ColorfulTheme};
+
+use crate::args::InitArgs;
+use crate::db::Database;
+use crate::git::Git;
+use crate::ui::interaction::prompt_or_cancel;
+
+/// Runs before the base branch is defaulted so the first choice is the
+/// user's.
+pub fn run(db_path: &Path, git: &Git, args: &InitArgs, porcelain: bool) -> Result<()> {
+    let db = Database::open(db_path)?;
+    if let Some(meta) = db.try_repo_meta()? {
+        if args
+            .base
+            .as_deref()
+            .is_some_and(|base| base != meta.base_branch)
+        {
+            return Err(anyhow!(
+                "stack is already initialized with base branch '{}'; use `stack base set <branch>` to change it",
+                meta.base_branch
+            ));
+        }
+        return report(&meta.base_branch, false, porcelain);
+    }
+
+    let base = match &args.base {
+        Some(base) => base.clone(),
+        None => choose_base(git, porcelain)?,
+    };
+    if !git.branch_exists(&base)? {
+        return Err(anyhow!("base branch '{base}' does not exist locally"));
+    }
+    db.set_base_branch_if_missing(&base)?;
+    report(&base, true, porcelain)
+}
+
+fn choose_base(git: &Git, porcelain: bool) -> Result<String> {
+    let suggested = git.default_base_branch()?;
+    let branches = git.local_branches()?;
+    if porcelain || branches.len() < 2 || !(stdout().is_terminal() && stdin().is_terminal()) {
+        return Ok(suggested);
+    }
+    let default_idx = branches.iter().position(|b| *b == suggested).unwrap_or(0);
+    let idx = prompt_or_cancel(
+        Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Select the base branch stacks target (Enter to select, Ctrl-C to cancel)")
+            .items(&branches)
+            .default(default_idx)
+            .interact(),
+    )?;
+    Ok(branches[idx].clone())
+}
+
+fn report(base: &str, initialized: bool, porcelain: bool) -> Result<()> {
+    if porcelain {
+        return crate::views::print_json(&serde_json::json!({
+            "base_branch": base,
+            "initialized": initialized,
+        }));
+    }
+    if initialized {
+        println!("initialized stack with base branch '{base}'");
+    } else {
+        println!("stack already uses base branch '{base}'");
+    }
+    Ok(())
+}
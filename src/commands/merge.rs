@@ -0,0 +1,411 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{IsTerminal, stdin, stdout};
+use std::thread::sleep;
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+
+use crate::args::MergeArgs;
+use crate::core::{
+    StackBases, SyncOp, SyncPlan, SyncPlanOptions, build_sync_plan, execute_sync_plan,
+    stack_branch_names,
+};
+use crate::db::{BranchRecord, Database};
+use crate::git::Git;
+use crate::provider::{ChecksState, MergeMethod, PrInfo, PrState, Provider, ReviewState};
+use crate::ui::interaction::confirm_inline_yes_no;
+
+const MERGE_POLL_ATTEMPTS: u32 = 30;
+const MERGE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+pub struct MergeRunOptions {
+    pub porcelain: bool,
+    pub yes: bool,
+    pub dry_run: bool,
+    pub method: MergeMethod,
+    pub plan: SyncPlanOptions,
+}
+
+#[derive(Debug, Clone)]
+struct MergeStop {
+    branch: String,
+    pr_number: Option<i64>,
+    reason: String,
+}
+
+pub fn run(
+    db: &Database,
+    git: &Git,
+    provider: &dyn Provider,
+    args: &MergeArgs,
+    bases: &StackBases,
+    base_remote: &str,
+    opts: MergeRunOptions,
+) -> Result<()> {
+    let target = match &args.until {
+        Some(branch) => branch.clone(),
+        None => git.current_branch()?,
+    };
+    if target.trim().is_empty() || bases.contains(&target) {
+        return Err(anyhow!(
+            "no stacked branch to merge; switch to a stacked branch or pass --until <branch>"
+        ));
+    }
+
+    let records = db.list_branches()?;
+    let base_branch = bases.base_for(&records, &target);
+    let path = merge_path(&records, &target, base_branch)?;
+
+    if opts.dry_run {
+        return preview_merge(db, provider, &path, base_branch, &target, &opts);
+    }
+
+    let should_apply = if opts.yes {
+        true
+    } else if stdout().is_terminal() && stdin().is_terminal() {
+        confirm_inline_yes_no(&format!(
+            "Merge stack PRs up to '{target}' into '{base_branch}' using {}?",
+            opts.method.as_str()
+        ))?
+    } else {
+        false
+    };
+    if !should_apply {
+        if !opts.porcelain {
+            println!("merge not applied: confirmation declined; no changes made");
+        }
+        return Ok(());
+    }
+
+    let mut merged: Vec<(String, i64)> = Vec::new();
+    let mut stopped: Option<MergeStop> = None;
+
+    for branch in &path {
+        let record = db.branch_by_name(branch)?;
+        let cached_number = record.as_ref().and_then(|b| b.cached_pr_number);
+        let frozen = record.is_some_and(|b| b.frozen);
+        let Some(pr) = provider.resolve_pr_by_head(branch, cached_number)? else {
+            stopped = Some(MergeStop {
+                branch: branch.clone(),
+                pr_number: None,
+                reason: "no PR found".to_string(),
+            });
+            break;
+        };
+        match pr.state {
+            PrState::Merged => continue,
+            PrState::Open => {}
+            PrState::Closed | PrState::Unknown => {
+                stopped = Some(MergeStop {
+                    branch: branch.clone(),
+                    pr_number: Some(pr.number),
+                    reason: "PR is not open".to_string(),
+                });
+                break;
+            }
+        }
+
+        if let Some(reason) = merge_blocker(provider, &pr, frozen)? {
+            stopped = Some(MergeStop {
+                branch: branch.clone(),
+                pr_number: Some(pr.number),
+                reason,
+            });
+            break;
+        }
+
+        if pr.base_ref_name.as_deref() != Some(base_branch) {
+            provider.update_pr_base(pr.number, base_branch)?;
+            if !opts.porcelain {
+                println!(
+                    "retargeted PR #{} ('{branch}') to '{base_branch}'",
+                    pr.number
+                );
+            }
+        }
+
+        provider.merge_pr(pr.number, opts.method)?;
+        if !wait_for_merge(provider, branch, pr.number)? {
+            stopped = Some(MergeStop {
+                branch: branch.clone(),
+                pr_number: Some(pr.number),
+                reason: "merge did not complete in time".to_string(),
+            });
+            break;
+        }
+        if !opts.porcelain {
+            println!(
+                "merged PR #{} ('{branch}') via {}",
+                pr.number,
+                opts.method.as_str()
+            );
+        }
+        merged.push((branch.clone(), pr.number));
+
+        restack_after_merge(db, git, provider, bases, base_remote, branch, &opts.plan)?;
+
+        if branch == &target {
+            break;
+        }
+    }
+
+    if opts.porcelain {
+        let merged = merged
+            .iter()
+            .map(|(branch, number)| serde_json::json!({ "branch": branch, "pr_number": number }))
+            .collect::<Vec<_>>();
+        return crate::views::print_json(&serde_json::json!({
+            "until": target,
+            "method": opts.method.as_str(),
+            "merged": merged,
+            "stopped": stopped.as_ref().map(|stop| serde_json::json!({
+                "branch": stop.branch,
+                "pr_number": stop.pr_number,
+                "reason": stop.reason,
+            })),
+        }));
+    }
+
+    match stopped {
+        Some(stop) => println!(
+            "merge stopped at '{}'{}: {}",
+            stop.branch,
+            stop.pr_number
+                .map(|n| format!(" (PR #{n})"))
+                .unwrap_or_default(),
+            stop.reason
+        ),
+        None if merged.is_empty() => println!("nothing to merge up to '{target}'"),
+        None => println!("merged {} PR(s) up to '{target}'", merged.len()),
+    }
+    Ok(())
+}
+
+fn preview_merge(
+    db: &Database,
+    provider: &dyn Provider,
+    path: &[String],
+    base_branch: &str,
+    target: &str,
+    opts: &MergeRunOptions,
+) -> Result<()> {
+    let mut steps = Vec::new();
+    let mut stopped: Option<MergeStop> = None;
+    for branch in path {
+        let record = db.branch_by_name(branch)?;
+        let cached_number = record.as_ref().and_then(|b| b.cached_pr_number);
+        let frozen = record.is_some_and(|b| b.frozen);
+        let pr = provider.resolve_pr_by_head(branch, cached_number)?;
+        let blocker = match &pr {
+            None => Some("no PR found".to_string()),
+            Some(PrInfo {
+                state: PrState::Merged,
+                ..
+            }) => continue,
+            Some(
+                pr @ PrInfo {
+                    state: PrState::Open,
+                    ..
+                },
+            ) => merge_blocker(provider, pr, frozen)?,
+            Some(_) => Some("PR is not open".to_string()),
+        };
+        if let Some(reason) = blocker {
+            stopped = Some(MergeStop {
+                branch: branch.clone(),
+                pr_number: pr.map(|p| p.number),
+                reason,
+            });
+            break;
+        }
+        steps.push((branch.clone(), pr.map(|p| p.number).unwrap_or_default()));
+    }
+
+    if opts.porcelain {
+        let steps = steps
+            .iter()
+            .map(|(branch, number)| serde_json::json!({ "branch": branch, "pr_number": number }))
+            .collect::<Vec<_>>();
+        return crate::views::print_json(&serde_json::json!({
+            "until": target,
+            "method": opts.method.as_str(),
+            "dry_run": true,
+            "would_merge": steps,
+            "stopped": stopped.as_ref().map(|stop| serde_json::json!({
+                "branch": stop.branch,
+                "pr_number": stop.pr_number,
+                "reason": stop.reason,
+            })),
+        }));
+    }
+
+    for (branch, number) in &steps {
+        println!(
+            "would merge PR #{number} ('{branch}') into '{base_branch}' via {}",
+            opts.method.as_str()
+        );
+    }
+    if let Some(stop) = stopped {
+        println!(
+            "would stop at '{}'{}: {}",
+            stop.branch,
+            stop.pr_number
+                .map(|n| format!(" (PR #{n})"))
+                .unwrap_or_default(),
+            stop.reason
+        );
+    }
+    Ok(())
+}
+
+fn merge_path(records: &[BranchRecord], target: &str, base_branch: &str) -> Result<Vec<String>> {
+    let by_id: HashMap<i64, &BranchRecord> = records.iter().map(|r| (r.id, r)).collect();
+    let mut cursor = records
+        .iter()
+        .find(|r| r.name == target)
+        .ok_or_else(|| anyhow!("branch '{target}' is not tracked; run `stack track` first"))?;
+
+    let mut path = Vec::new();
+    let mut seen = HashSet::new();
+    loop {
+        if !seen.insert(cursor.id) {
+            return Err(anyhow!("detected a cycle while walking stack parents"));
+        }
+        path.push(cursor.name.clone());
+        match cursor.parent_branch_id.and_then(|id| by_id.get(&id)) {
+            Some(parent) if parent.name != base_branch => cursor = parent,
+            _ => break,
+        }
+    }
+    path.reverse();
+    Ok(path)
+}
+
+fn merge_blocker(provider: &dyn Provider, pr: &PrInfo, frozen: bool) -> Result<Option<String>> {
+    if frozen {
+        return Ok(Some("branch is frozen".to_string()));
+    }
+    if pr.is_draft {
+        return Ok(Some("PR is a draft".to_string()));
+    }
+    let status = provider.pr_merge_status(pr.number)?;
+    Ok(match (status.checks, status.review) {
+        (ChecksState::Failing, _) => Some("checks failing".to_string()),
+        (ChecksState::Pending, _) => Some("checks pending".to_string()),
+        (_, ReviewState::ChangesRequested) => Some("changes requested".to_string()),
+        (_, ReviewState::ReviewRequired) => Some("missing approvals".to_string()),
+        _ => None,
+    })
+}
+
+fn wait_for_merge(provider: &dyn Provider, branch: &str, pr_number: i64) -> Result<bool> {
+    for attempt in 0..MERGE_POLL_ATTEMPTS {
+        if attempt > 0 {
+            sleep(MERGE_POLL_INTERVAL);
+        }
+        if let Some(pr) = provider.resolve_pr_by_head(branch, Some(pr_number))?
+            && matches!(pr.state, PrState::Merged)
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn restack_after_merge(
+    db: &Database,
+    git: &Git,
+    provider: &dyn Provider,
+    bases: &StackBases,
+    base_remote: &str,
+    merged_branch: &str,
+    plan_options: &SyncPlanOptions,
+) -> Result<()> {
+    let records = db.list_branches()?;
+    let base_branch = bases.base_for(&records, merged_branch).to_string();
+    let base_branch = base_branch.as_str();
+    // Other stacks are left for `stack sync`; only this one moved.
+    let stack = stack_branch_names(&records, bases, merged_branch);
+    let plan_for_stack = || -> Result<SyncPlan> {
+        let mut plan = build_sync_plan(db, git, provider, bases, base_remote, plan_options)?;
+        plan.ops.retain(|op| {
+            op.branch()
+                .is_none_or(|branch| bases.contains(branch) || stack.iter().any(|b| b == branch))
+        });
+        Ok(plan)
+    };
+
+    // Restack while the children still point at the merged branch, so the
+    // plan drops only its commits. PR edits wait for the second plan below,
+    // except readying drafts: that needs the merged parent, which the
+    // reparented children no longer have.
+    let mut restack_plan = plan_for_stack()?;
+    restack_plan
+        .ops
+        .retain(|op| !op.edits_pr() || matches!(op, SyncOp::SetPrDraft { draft: false, .. }));
+    run_and_push(db, git, provider, &restack_plan, base_branch)?;
+
+    let records = db.list_branches()?;
+    if let Some(merged) = records.iter().find(|r| r.name == merged_branch) {
+        for child in records
+            .iter()
+            .filter(|r| r.parent_branch_id == Some(merged.id))
+        {
+            db.set_parent(&child.name, Some(base_branch))?;
+            if !child.frozen
+                && let Some(pr) =
+                    provider.resolve_pr_by_head(&child.name, child.cached_pr_number)?
+                && matches!(pr.state, PrState::Open)
+                && pr.base_ref_name.as_deref() != Some(base_branch)
+            {
+                provider.update_pr_base(pr.number, base_branch)?;
+            }
+        }
+    }
+
+    // With the children on the base, the stack sections and order statuses
+    // no longer list the merged branch.
+    run_and_push(db, git, provider, &plan_for_stack()?, base_branch)
+}
+
+/// Executes `plan` and force-pushes the branches it restacked.
+fn run_and_push(
+    db: &Database,
+    git: &Git,
+    provider: &dyn Provider,
+    plan: &SyncPlan,
+    base_branch: &str,
+) -> Result<()> {
+    execute_sync_plan(db, git, provider, plan)?;
+    for op in &plan.ops {
+        if let SyncOp::Restack { branch, .. } = op {
+            let remote = git.push_remote_for_branch(branch, base_branch)?;
+            git.push_branch_force_with_lease(&remote, branch)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_path_runs_bottom_up_and_excludes_base() {
+        let records = vec![
+            BranchRecord::test(1, "main", None),
+            BranchRecord::test(2, "feat/a", Some(1)),
+            BranchRecord::test(3, "feat/b", Some(2)),
+            BranchRecord::test(4, "feat/c", Some(3)),
+        ];
+        let path = merge_path(&records, "feat/b", "main").expect("path");
+        assert_eq!(path, vec!["feat/a".to_string(), "feat/b".to_string()]);
+    }
+
+    #[test]
+    fn merge_path_rejects_untracked_target() {
+        let records = vec![BranchRecord::test(1, "main", None)];
+        let err = merge_path(&records, "feat/x", "main").unwrap_err();
+        assert!(err.to_string().contains("not tracked"));
+    }
+}
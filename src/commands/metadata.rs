@@ -0,0 +1,185 @@
+use anyhow::{Context, Result};
+
+use crate::args::MetadataAction;
+use crate::core::{
+    METADATA_FILE, METADATA_REF, MetadataDoc, apply_metadata, changed_branches, export_metadata,
+    merge_metadata,
+};
+use crate::db::Database;
+use crate::git::Git;
+
+pub fn run(
+    db: &Database,
+    git: &Git,
+    action: &MetadataAction,
+    base_branch: &str,
+    porcelain: bool,
+) -> Result<()> {
+    match action {
+        MetadataAction::Push(args) => {
+            let remote = resolve_remote(git, args.remote.as_deref(), base_branch)?;
+            push(db, git, &remote, porcelain)
+        }
+        MetadataAction::Pull(args) => {
+            let remote = resolve_remote(git, args.remote.as_deref(), base_branch)?;
+            pull(db, git, &remote, porcelain)
+        }
+    }
+}
+
+fn resolve_remote(git: &Git, explicit: Option<&str>, base_branch: &str) -> Result<String> {
+    match explicit {
+        Some(remote) => Ok(remote.to_string()),
+        None => git.push_remote_for_branch(base_branch, base_branch),
+    }
+}
+
+/// Local copy of the remote's metadata ref, used as the merge side on pull.
+fn tracking_ref(remote: &str) -> String {
+    format!("refs/stack/remotes/{remote}/metadata")
+}
+
+fn read_doc(git: &Git, commit: &str) -> Result<MetadataDoc> {
+    MetadataDoc::from_json(&git.file_at(commit, METADATA_FILE)?)
+        .with_context(|| format!("failed to read stack metadata from {commit}"))
+}
+
+fn push(db: &Database, git: &Git, remote: &str, porcelain: bool) -> Result<()> {
+    let doc = export_metadata(db)?;
+    let local = git.resolve_commit(METADATA_REF)?;
+    let commit = match &local {
+        Some(commit) if read_doc(git, commit).ok().as_ref() == Some(&doc) => commit.clone(),
+        _ => {
+            let parents = local.iter().map(String::as_str).collect::<Vec<_>>();
+            let commit =
+                git.commit_file(METADATA_FILE, &doc.to_json()?, &parents, "stack metadata")?;
+            git.update_ref(METADATA_REF, &commit)?;
+            commit
+        }
+    };
+
+    git.run(["push", remote, &format!("{METADATA_REF}:{METADATA_REF}")])
+        .with_context(|| {
+            format!(
+                "failed to push stack metadata to '{remote}'; if it was rejected, run `stack metadata pull` first"
+            )
+        })?;
+    git.update_ref(&tracking_ref(remote), &commit)?;
+
+    if porcelain {
+        return crate::views::print_json(&serde_json::json!({
+            "remote": remote,
+            "commit": commit,
+            "branches": doc.branches.len(),
+        }));
+    }
+    println!(
+        "pushed stack metadata for {} branch(es) to '{remote}' ({METADATA_REF})",
+        doc.branches.len()
+    );
+    Ok(())
+}
+
+fn pull(db: &Database, git: &Git, remote: &str, porcelain: bool) -> Result<()> {
+    if !git.remote_has_ref(remote, METADATA_REF)? {
+        if porcelain {
+            return crate::views::print_json(&serde_json::json!({
+                "remote": remote,
+                "found": false,
+                "updated": [],
+                "conflicts": [],
+            }));
+        }
+        println!("no stack metadata on '{remote}' yet; run `stack metadata push` to publish it");
+        return Ok(());
+    }
+
+    let tracking = tracking_ref(remote);
+    git.run(["fetch", remote, &format!("+{METADATA_REF}:{tracking}")])?;
+    let remote_commit = git
+        .resolve_commit(&tracking)?
+        .with_context(|| format!("fetched {tracking} does not resolve"))?;
+    let remote_doc = read_doc(git, &remote_commit)?;
+
+    let local_commit = git.resolve_commit(METADATA_REF)?;
+    let base_doc = match local_commit
+        .as_deref()
+        .and_then(|local| git.merge_base(local, &remote_commit).ok())
+    {
+        Some(base) => read_doc(git, &base)?,
+        None => MetadataDoc::default(),
+    };
+    let local_doc = export_metadata(db)?;
+    if remote_doc.base_branch != local_doc.base_branch {
+        eprintln!(
+            "warning: '{remote}' metadata uses base branch '{}' but this repository uses '{}'",
+            remote_doc.base_branch, local_doc.base_branch
+        );
+    }
+
+    let merged = merge_metadata(&base_doc, &local_doc, &remote_doc);
+    apply_metadata(db, &merged.doc)?;
+    let updated = changed_branches(&local_doc, &merged.doc);
+
+    let remote_is_contained = match &local_commit {
+        Some(local) => git.is_ancestor(&remote_commit, local)?,
+        None => false,
+    };
+    if merged.doc == remote_doc {
+        git.update_ref(METADATA_REF, &remote_commit)?;
+    } else if !remote_is_contained {
+        let mut parents = local_commit.iter().map(String::as_str).collect::<Vec<_>>();
+        parents.push(&remote_commit);
+        let commit = git.commit_file(
+            METADATA_FILE,
+            &merged.doc.to_json()?,
+            &parents,
+            &format!("merge stack metadata from {remote}"),
+        )?;
+        git.update_ref(METADATA_REF, &commit)?;
+    }
+
+    if porcelain {
+        let conflicts = merged
+            .conflicts
+            .iter()
+            .map(|c| {
+                serde_json::json!({
+                    "branch": c.branch,
+                    "local_parent": c.local_parent,
+                    "remote_parent": c.remote_parent,
+                })
+            })
+            .collect::<Vec<_>>();
+        return crate::views::print_json(&serde_json::json!({
+            "remote": remote,
+            "found": true,
+            "updated": updated,
+            "conflicts": conflicts,
+        }));
+    }
+
+    println!(
+        "pulled stack metadata from '{remote}': {} branch(es) updated",
+        updated.len()
+    );
+    for branch in &updated {
+        println!("  {branch}");
+    }
+    for conflict in &merged.conflicts {
+        println!(
+            "conflict: '{}' has parent {} locally and {} on '{remote}'; kept the local parent",
+            conflict.branch,
+            describe_parent(conflict.local_parent.as_deref()),
+            describe_parent(conflict.remote_parent.as_deref()),
+        );
+    }
+    Ok(())
+}
+
+fn describe_parent(parent: Option<&str>) -> String {
+    match parent {
+        Some(parent) => format!("'{parent}'"),
+        None => "none".to_string(),
+    }
+}
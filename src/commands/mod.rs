@@ -1,10 +1,22 @@
+pub mod annotate;
+pub mod base;
 pub mod completions;
 pub mod create;
+pub mod db;
 pub mod delete;
 pub mod doctor;
+pub mod export;
+pub mod freeze;
+pub mod history;
+pub mod import;
+pub mod init;
+pub mod merge;
+pub mod metadata;
 pub mod nav;
 pub mod pr;
+pub mod pr_lifecycle;
 pub mod push;
+pub mod refresh;
 pub mod stack;
 pub mod sync;
 pub mod track;
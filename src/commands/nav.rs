@@ -4,6 +4,7 @@ use std::io::{IsTerminal, stdin, stdout};
 use anyhow::{Context, Result, anyhow};
 use dialoguer::{Select, theme::ColorfulTheme};
 
+use crate::core::StackBases;
 use crate::db::{BranchRecord, Database};
 use crate::git::Git;
 use crate::ui::interaction::prompt_or_cancel;
@@ -33,18 +34,18 @@ pub fn run(db: &Database, git: &Git, command: NavCommand, porcelain: bool) -> Re
         return Err(anyhow!("cannot navigate stack from detached HEAD"));
     }
 
-    let base_branch = db.repo_meta()?.base_branch;
-    if current == base_branch {
+    let bases = StackBases::load(db)?;
+    if bases.contains(&current) {
         return Err(anyhow!(
             "base branch '{}' is not part of stack navigation; switch to a stacked branch first",
-            base_branch
+            current
         ));
     }
 
     let tracked = db.list_branches()?;
     let nav_tracked: Vec<&BranchRecord> = tracked
         .iter()
-        .filter(|record| record.name != base_branch)
+        .filter(|record| !bases.contains(&record.name))
         .collect();
     let by_name: HashMap<&str, &BranchRecord> = nav_tracked
         .iter()
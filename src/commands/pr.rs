@@ -1,29 +1,31 @@
 use std::collections::HashMap;
+use std::fs;
 use std::io::{IsTerminal, stdout};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use anyhow::{Context, Result, anyhow};
 use crossterm::style::Stylize;
 
 use crate::args::PrArgs;
+use crate::core::{ManagedSectionBuilder, StackBases};
 use crate::db::{BranchRecord, Database};
-use crate::git::Git;
-use crate::provider::Provider;
-use crate::util::pr_body::{ManagedBranchRef, compose_branch_pr_body};
+use crate::git::{CommitMessage, Git};
+use crate::provider::{NewPr, PrInfo, PrMetadata, PrState, Provider};
+use crate::util::codeowners::{CodeOwners, reviewer_logins};
+use crate::util::pr_body::{ManagedSectionPlacement, ManagedSectionStyle, compose_branch_pr_body};
 use crate::util::pr_links::determine_pr_link_target;
+use crate::util::pr_title::PrTitlePrefix;
 use crate::util::terminal::osc8_hyperlink;
 use crate::util::url::{url_encode_compare_ref, url_encode_component};
 
-#[derive(Debug, Clone)]
-struct ManagedPrSection {
-    parent: Option<BranchPrRef>,
-    children: Vec<BranchPrRef>,
-}
-
-#[derive(Debug, Clone)]
-struct BranchPrRef {
-    branch: String,
-    pr_number: Option<i64>,
+pub struct PrRunOptions {
+    pub porcelain: bool,
+    pub debug: bool,
+    pub body_style: ManagedSectionStyle,
+    pub title_prefix: Option<PrTitlePrefix>,
+    pub inherit_metadata: bool,
+    pub order_status: bool,
 }
 
 pub fn run(
@@ -31,14 +33,21 @@ pub fn run(
     git: &Git,
     provider: &dyn Provider,
     args: &PrArgs,
-    porcelain: bool,
-    _yes: bool,
-    debug: bool,
+    opts: PrRunOptions,
 ) -> Result<()> {
+    let PrRunOptions {
+        porcelain,
+        debug,
+        ref body_style,
+        ref title_prefix,
+        inherit_metadata,
+        order_status,
+    } = opts;
     let current = git.current_branch()?;
     let records = db.list_branches()?;
     let by_id: HashMap<i64, &BranchRecord> = records.iter().map(|r| (r.id, r)).collect();
-    let default_base = db.repo_meta()?.base_branch;
+    let bases = StackBases::load(db)?;
+    let default_base = bases.base_for(&records, &current).to_string();
     let current_record = records.iter().find(|r| r.name == current);
     let (base, cached_number, non_stacked_reason): (String, Option<i64>, Option<String>) =
         match current_record {
@@ -48,38 +57,35 @@ pub fn run(
             {
                 Some(parent) => (parent, record.cached_pr_number, None),
                 None => (
-                    default_base,
+                    default_base.clone(),
                     record.cached_pr_number,
                     Some("branch is tracked but has no parent link".to_string()),
                 ),
             },
             None => (
-                default_base,
+                default_base.clone(),
                 None,
                 Some("branch is not tracked in the stack".to_string()),
             ),
         };
-    let managed_pr_section = current_record.and_then(|record| {
-        let parent = record.parent_branch_id.and_then(|parent_id| {
-            by_id.get(&parent_id).map(|r| BranchPrRef {
-                branch: r.name.clone(),
-                pr_number: r.cached_pr_number,
-            })
-        })?;
-        let mut children: Vec<BranchPrRef> = records
-            .iter()
-            .filter(|r| r.parent_branch_id == Some(record.id))
-            .map(|r| BranchPrRef {
-                branch: r.name.clone(),
-                pr_number: r.cached_pr_number,
-            })
-            .collect();
-        children.sort_by(|a, b| a.branch.cmp(&b.branch));
-        Some(ManagedPrSection {
-            parent: Some(parent),
-            children,
-        })
-    });
+    let cached_prs = cached_pr_map(&records);
+    let section_builder = ManagedSectionBuilder::new(&records, &cached_prs, &bases, body_style);
+    let commits = git.commit_messages(&base, &current).unwrap_or_default();
+    let title = args
+        .title
+        .clone()
+        .unwrap_or_else(|| default_pr_title(&commits, &current));
+    let title = match title_prefix
+        .as_ref()
+        .zip(section_builder.stack_position(&current))
+    {
+        Some((prefix, (index, total))) => prefix.apply(&title, index, total),
+        None => title,
+    };
+    let user_body = args
+        .body
+        .clone()
+        .or_else(|| default_pr_body(&commits, find_pr_template(git.root()).as_deref()));
 
     if current == base {
         let reason = format!(
@@ -104,6 +110,26 @@ pub fn run(
         );
     }
 
+    if args.preview_body {
+        let base_url = determine_pr_link_target(git, &base, &current)?.base_url;
+        let body = compose_pr_body(
+            &section_builder,
+            &base_url,
+            &current,
+            base_commit_url(git, &base_url, &current, &default_base).as_deref(),
+            user_body.as_deref(),
+        )?;
+        if porcelain {
+            return crate::views::print_json(&serde_json::json!({
+                "head": current,
+                "base": base,
+                "body": body,
+            }));
+        }
+        println!("{body}");
+        return Ok(());
+    }
+
     let existing = match provider.resolve_pr_by_head(&current, cached_number) {
         Ok(existing) => existing,
         Err(err) => {
@@ -121,10 +147,56 @@ pub fn run(
         }
     };
 
+    let mut metadata = PrMetadata::default();
+    if (args.inherit || inherit_metadata)
+        && !bases.contains(&base)
+        && let Some(parent_pr) = records
+            .iter()
+            .find(|r| r.name == base)
+            .and_then(|r| r.cached_pr_number)
+            .or_else(|| {
+                provider
+                    .resolve_pr_by_head(&base, None)
+                    .ok()
+                    .flatten()
+                    .map(|pr| pr.number)
+            })
+    {
+        match provider.pr_metadata(parent_pr) {
+            Ok(inherited) => metadata = inherited,
+            Err(err) => {
+                eprintln!("warning: could not read metadata of parent PR #{parent_pr}: {err}")
+            }
+        }
+    }
+    let owners = CodeOwners::owners_for_branch(git, &current, &base).unwrap_or_default();
+    if args.codeowners {
+        metadata.merge(PrMetadata {
+            reviewers: reviewer_logins(&owners),
+            ..Default::default()
+        });
+    } else if !owners.is_empty() && !porcelain {
+        eprintln!(
+            "suggested reviewers from CODEOWNERS: {} (pass --codeowners to request them)",
+            owners.join(", ")
+        );
+    }
+    metadata.merge(PrMetadata {
+        reviewers: args.reviewers.clone(),
+        labels: args.labels.clone(),
+        assignees: args.assignees.clone(),
+        milestone: args.milestone.clone(),
+    });
+
     let payload = serde_json::json!({
         "head": current,
         "base": base,
-        "title": args.title,
+        "title": title,
+        "reviewers": metadata.reviewers,
+        "labels": metadata.labels,
+        "assignees": metadata.assignees,
+        "milestone": metadata.milestone,
+        "codeowners": owners,
         "draft": args.draft,
         "dry_run": args.dry_run,
         "existing_pr_number": existing.as_ref().map(|pr| pr.number),
@@ -164,22 +236,105 @@ pub fn run(
         return Ok(());
     }
 
+    if current_record.is_some_and(|record| record.frozen) {
+        return Err(anyhow!(
+            "'{current}' is frozen; run `stack unfreeze` before pushing it or opening a PR"
+        ));
+    }
     let head = payload["head"].as_str().unwrap_or_default();
     let base_ref = payload["base"].as_str().unwrap_or_default();
-    let push_remote = git
-        .remote_for_branch(head)?
-        .or_else(|| git.remote_for_branch(base_ref).ok().flatten())
-        .unwrap_or_else(|| "origin".to_string());
+    let push_remote = git.push_remote_for_branch(head, &default_base)?;
+    let compose_body = || -> Result<String> {
+        let link_target = determine_pr_link_target(git, base_ref, head)?;
+        compose_pr_body(
+            &section_builder,
+            &link_target.base_url,
+            head,
+            base_commit_url(git, &link_target.base_url, head, &default_base).as_deref(),
+            user_body.as_deref(),
+        )
+    };
+    let edited_body = if args.edit {
+        Some(edit_pr_body(git, &compose_body()?)?)
+    } else {
+        None
+    };
     git.push_branch(&push_remote, head)?;
+    let link_target = determine_pr_link_target(git, base_ref, head)?;
+    let body = match edited_body {
+        Some(body) => body,
+        None => compose_body()?,
+    };
+    if args.create {
+        let created = provider.create_pr(&NewPr {
+            branch: head,
+            head_ref: &link_target.head_ref,
+            base: base_ref,
+            title: &title,
+            body: &body,
+            draft: args.draft,
+            metadata: &metadata,
+        })?;
+        db.set_pr_cache(head, Some(created.number), Some(created.state.as_str()))?;
+        let status = if order_status {
+            let mut prs = cached_prs.clone();
+            let parent_number = records
+                .iter()
+                .find(|r| r.name == base)
+                .and_then(|r| r.cached_pr_number);
+            if !bases.contains(&base)
+                && let Some(parent_pr) = provider.resolve_pr_by_head(&base, parent_number)?
+            {
+                prs.insert(base.clone(), parent_pr);
+            }
+            prs.insert(head.to_string(), created.clone());
+            crate::core::order_status(&records, &prs, &bases, head)
+        } else {
+            None
+        };
+        if let Some(status) = &status {
+            provider.set_pr_head_status(created.number, status)?;
+        }
+        if porcelain {
+            return crate::views::print_json(&serde_json::json!({
+                "head": payload["head"],
+                "base": payload["base"],
+                "push_remote": push_remote,
+                "number": created.number,
+                "url": created.url,
+            }));
+        }
+        println!("pushed '{head}' to '{push_remote}'");
+        match created.url.as_deref() {
+            Some(url) => println!("created PR #{}: {url}", created.number),
+            None => println!("created PR #{}", created.number),
+        }
+        if let Some(status) = status {
+            println!(
+                "set {} {}: {}",
+                status.context,
+                status.state.as_str(),
+                status.description
+            );
+        }
+        return Ok(());
+    }
+
+    if !metadata.reviewers.is_empty() {
+        eprintln!(
+            "warning: compare links cannot request reviewers ({}); pass --create to request them",
+            metadata.reviewers.join(", ")
+        );
+    }
     let url = build_pr_open_url(
-        git,
+        &link_target.base_url,
         base_ref,
-        head,
-        args.title.as_deref(),
-        args.body.as_deref(),
+        &link_target.head_ref,
+        Some(title.as_str()),
+        &body,
         args.draft,
-        managed_pr_section.as_ref(),
-    )?;
+        &metadata,
+    );
 
     if porcelain {
         return crate::views::print_json(&serde_json::json!({
@@ -235,50 +390,156 @@ fn format_existing_pr_ref(
 }
 
 fn build_pr_open_url(
-    git: &Git,
+    base_url: &str,
     base: &str,
-    head: &str,
+    head_ref: &str,
     title: Option<&str>,
-    body: Option<&str>,
+    body: &str,
     draft: bool,
-    managed: Option<&ManagedPrSection>,
-) -> Result<String> {
-    let link_target = determine_pr_link_target(git, base, head)?;
-    let base_url = link_target.base_url;
-    let head_ref = link_target.head_ref;
-    let base_commit_url = git
-        .merge_base(head, base)
-        .ok()
-        .map(|sha| format!("{}/commit/{sha}", base_url.trim_end_matches('/')));
+    metadata: &PrMetadata,
+) -> String {
     let mut params = vec!["expand=1".to_string()];
     if let Some(title) = title
         && !title.is_empty()
     {
         params.push(format!("title={}", url_encode_component(title)));
     }
-    if let Some(body) = compose_pr_body(
-        &base_url,
-        base,
-        head,
-        base_commit_url.as_deref(),
-        managed,
-        body,
-    )
-    .as_deref()
-        && !body.is_empty()
-    {
+    if !body.is_empty() {
         params.push(format!("body={}", url_encode_component(body)));
     }
+    for (key, values) in [
+        ("labels", &metadata.labels),
+        ("assignees", &metadata.assignees),
+    ] {
+        if !values.is_empty() {
+            params.push(format!("{key}={}", url_encode_component(&values.join(","))));
+        }
+    }
+    if let Some(milestone) = &metadata.milestone {
+        params.push(format!("milestone={}", url_encode_component(milestone)));
+    }
     if draft {
         params.push("draft=1".to_string());
     }
-    Ok(format!(
+    format!(
         "{}/compare/{}...{}?{}",
         base_url.trim_end_matches('/'),
         url_encode_compare_ref(base),
-        url_encode_compare_ref(&head_ref),
+        url_encode_compare_ref(head_ref),
         params.join("&")
-    ))
+    )
+}
+
+/// The single commit's subject, otherwise a title derived from the last
+/// segment of the branch name (`feat/add-parser` becomes `Add parser`).
+fn default_pr_title(commits: &[CommitMessage], branch: &str) -> String {
+    if let [commit] = commits
+        && !commit.subject.is_empty()
+    {
+        return commit.subject.clone();
+    }
+    let words = branch
+        .rsplit('/')
+        .next()
+        .unwrap_or(branch)
+        .split(['-', '_'])
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut chars = words.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => branch.to_string(),
+    }
+}
+
+/// A single commit contributes its message body; several commits are listed
+/// by subject. The repo PR template, when present, follows.
+fn default_pr_body(commits: &[CommitMessage], template: Option<&str>) -> Option<String> {
+    let from_commits = match commits {
+        [commit] => commit.body.clone(),
+        commits => commits
+            .iter()
+            .map(|commit| format!("- {}", commit.subject))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+    let parts = [from_commits.trim(), template.unwrap_or_default().trim()]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    (!parts.is_empty()).then(|| parts.join("\n\n"))
+}
+
+/// Finds the repo's pull request template in the locations GitHub checks,
+/// using the first file (by name) of a `PULL_REQUEST_TEMPLATE/` directory.
+fn find_pr_template(root: &Path) -> Option<String> {
+    for dir in [".github", "", "docs"] {
+        let dir = root.join(dir);
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut entries = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .collect::<Vec<_>>();
+        entries.sort();
+        let named = |path: &PathBuf, name: &str| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.eq_ignore_ascii_case(name))
+        };
+        if let Some(file) = entries
+            .iter()
+            .find(|path| path.is_file() && named(path, "pull_request_template.md"))
+        {
+            return fs::read_to_string(file).ok();
+        }
+        if let Some(template_dir) = entries
+            .iter()
+            .find(|path| path.is_dir() && named(path, "pull_request_template"))
+            && let Ok(templates) = fs::read_dir(template_dir)
+        {
+            let mut templates = templates
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+                .collect::<Vec<_>>();
+            templates.sort();
+            if let Some(first) = templates.first() {
+                return fs::read_to_string(first).ok();
+            }
+        }
+    }
+    None
+}
+
+/// Opens the composed body in the user's editor, like `git commit` does.
+fn edit_pr_body(git: &Git, body: &str) -> Result<String> {
+    let path = git.git_dir()?.join("STACK_PR_EDITMSG");
+    fs::write(&path, format!("{body}\n"))
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    let editor = git.editor()?;
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{editor} \"$@\""))
+        .arg(&editor)
+        .arg(&path)
+        .status()
+        .with_context(|| format!("failed to launch editor '{editor}'"))?;
+    if !status.success() {
+        return Err(anyhow!(
+            "editor '{editor}' exited with {status}; PR not opened"
+        ));
+    }
+    let edited =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    let _ = fs::remove_file(&path);
+    Ok(edited.trim().to_string())
+}
+
+fn base_commit_url(git: &Git, base_url: &str, head: &str, trunk: &str) -> Option<String> {
+    git.merge_base(head, trunk)
+        .ok()
+        .map(|sha| format!("{}/commit/{sha}", base_url.trim_end_matches('/')))
 }
 
 fn open_url_in_browser(url: &str) -> Result<()> {
@@ -317,69 +578,90 @@ fn open_url_in_browser(url: &str) -> Result<()> {
     Ok(())
 }
 
+/// Cached PR numbers and states stand in for live metadata when composing a
+/// new PR body, matching what the compare link can know without `gh`.
+fn cached_pr_map(records: &[BranchRecord]) -> HashMap<String, PrInfo> {
+    records
+        .iter()
+        .filter_map(|record| {
+            Some((
+                record.name.clone(),
+                PrInfo {
+                    number: record.cached_pr_number?,
+                    state: record
+                        .cached_pr_state
+                        .as_deref()
+                        .map(PrState::parse)
+                        .unwrap_or(PrState::Unknown),
+                    merge_commit_oid: None,
+                    base_ref_name: None,
+                    body: None,
+                    url: None,
+                    title: None,
+                    is_draft: false,
+                },
+            ))
+        })
+        .collect()
+}
+
 fn compose_pr_body(
+    sections: &ManagedSectionBuilder<'_>,
     base_url: &str,
-    base_branch: &str,
-    _head_branch: &str,
+    head: &str,
     base_commit_url: Option<&str>,
-    managed: Option<&ManagedPrSection>,
     user_body: Option<&str>,
-) -> Option<String> {
-    let parent = managed
-        .and_then(|m| m.parent.as_ref())
-        .map(|p| ManagedBranchRef {
-            branch: p.branch.clone(),
-            pr_number: p.pr_number,
-            pr_url: None,
-        });
-    let first_child = managed
-        .and_then(|m| m.children.first())
-        .map(|c| ManagedBranchRef {
-            branch: c.branch.clone(),
-            pr_number: c.pr_number,
-            pr_url: None,
-        });
-    Some(compose_branch_pr_body(
-        base_url,
-        base_branch,
-        base_commit_url,
-        parent.as_ref(),
-        first_child.as_ref(),
-        user_body,
-    ))
+) -> Result<String> {
+    // In comment mode the section is posted by `stack sync` once the PR exists.
+    if sections.placement() == ManagedSectionPlacement::Comment {
+        return Ok(user_body.map(str::trim).unwrap_or_default().to_string());
+    }
+    let managed = sections.section_for(head, base_url, base_commit_url)?;
+    Ok(compose_branch_pr_body(&managed, user_body))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn compose_pr_body_prepends_managed_section() {
-        let managed = ManagedPrSection {
-            parent: Some(BranchPrRef {
-                branch: "feat/parent".to_string(),
-                pr_number: Some(123),
-            }),
-            children: vec![
-                BranchPrRef {
-                    branch: "feat/child-a".to_string(),
-                    pr_number: Some(125),
-                },
-                BranchPrRef {
-                    branch: "feat/child-b".to_string(),
-                    pr_number: None,
-                },
-            ],
-        };
-        let body = compose_pr_body(
+    fn record(id: i64, name: &str, parent: Option<i64>, pr: Option<i64>) -> BranchRecord {
+        BranchRecord {
+            cached_pr_number: pr,
+            cached_pr_state: pr.map(|_| "open".to_string()),
+            ..BranchRecord::test(id, name, parent)
+        }
+    }
+
+    fn compose_for(
+        records: &[BranchRecord],
+        base: &str,
+        head: &str,
+        user_body: Option<&str>,
+    ) -> String {
+        let prs = cached_pr_map(records);
+        let style = ManagedSectionStyle::default();
+        let bases = StackBases::new(base);
+        let builder = ManagedSectionBuilder::new(records, &prs, &bases, &style);
+        compose_pr_body(
+            &builder,
             "https://github.com/acme/repo",
-            "feat/base",
-            "feat/head",
+            head,
             None,
-            Some(&managed),
-            Some("User body text"),
+            user_body,
         )
-        .expect("body should be present");
+        .expect("body should render")
+    }
+
+    #[test]
+    fn compose_pr_body_prepends_managed_section() {
+        let records = vec![
+            record(1, "feat/base", None, None),
+            record(2, "feat/parent", Some(1), Some(123)),
+            record(3, "feat/head", Some(2), None),
+            record(4, "feat/child-a", Some(3), Some(125)),
+            record(5, "feat/child-b", Some(3), None),
+        ];
+        let body = compose_for(&records, "feat/base", "feat/head", Some("User body text"));
         assert!(body.contains(
             "… → [#123](https://github.com/acme/repo/pull/123) → (this PR) → [#125](https://github.com/acme/repo/pull/125) → …"
         ));
@@ -390,15 +672,8 @@ mod tests {
 
     #[test]
     fn compose_pr_body_returns_user_body_when_unmanaged() {
-        let body = compose_pr_body(
-            "https://github.com/acme/repo",
-            "main",
-            "feat/demo",
-            None,
-            None,
-            Some("User body text"),
-        )
-        .expect("body should be present");
+        let records = vec![record(1, "main", None, None)];
+        let body = compose_for(&records, "main", "feat/demo", Some("User body text"));
         assert!(body.contains("[main](https://github.com/acme/repo/tree/main) → (this PR)"));
         assert!(body.contains(crate::util::pr_body::MANAGED_BODY_MARKER_START));
         assert!(body.contains(crate::util::pr_body::MANAGED_BODY_MARKER_END));
@@ -407,24 +682,55 @@ mod tests {
 
     #[test]
     fn compose_pr_body_omits_trailing_arrow_when_no_child_pr() {
-        let managed = ManagedPrSection {
-            parent: Some(BranchPrRef {
-                branch: "feat/parent".to_string(),
-                pr_number: Some(123),
-            }),
-            children: Vec::new(),
+        let records = vec![
+            record(1, "feat/base", None, None),
+            record(2, "feat/parent", Some(1), Some(123)),
+            record(3, "feat/head", Some(2), None),
+        ];
+        let body = compose_for(&records, "feat/base", "feat/head", None);
+        assert!(body.contains("… → [#123](https://github.com/acme/repo/pull/123) → (this PR)"));
+        assert!(!body.contains("(this PR) →"));
+    }
+
+    #[test]
+    fn default_pr_title_prefers_single_commit_subject_over_branch_name() {
+        let commit = |subject: &str| CommitMessage {
+            subject: subject.to_string(),
+            body: String::new(),
         };
-        let body = compose_pr_body(
+        assert_eq!(
+            default_pr_title(&[commit("Fix lexer")], "feat/lexer"),
+            "Fix lexer"
+        );
+        assert_eq!(
+            default_pr_title(&[commit("a"), commit("b")], "jf/feat/add-stack_parser"),
+            "Add stack parser"
+        );
+        assert_eq!(default_pr_title(&[], "wip"), "Wip");
+        assert_eq!(default_pr_body(&[], None), None);
+    }
+
+    #[test]
+    fn build_pr_open_url_passes_labels_assignees_and_milestone() {
+        let metadata = PrMetadata {
+            reviewers: vec!["alice".to_string()],
+            labels: vec!["bug".to_string(), "good first issue".to_string()],
+            assignees: vec!["bob".to_string()],
+            milestone: Some("v1".to_string()),
+        };
+        let url = build_pr_open_url(
             "https://github.com/acme/repo",
-            "feat/base",
-            "feat/head",
-            None,
-            Some(&managed),
+            "main",
+            "feat/x",
             None,
-        )
-        .expect("body should be present");
-        assert!(body.contains("… → [#123](https://github.com/acme/repo/pull/123) → (this PR)"));
-        assert!(!body.contains("(this PR) →"));
+            "",
+            false,
+            &metadata,
+        );
+        assert_eq!(
+            url,
+            "https://github.com/acme/repo/compare/main...feat/x?expand=1&labels=bug%2Cgood%20first%20issue&assignees=bob&milestone=v1"
+        );
     }
 
     #[test]
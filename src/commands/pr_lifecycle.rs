@@ -0,0 +1,173 @@
+use anyhow::{Result, anyhow};
+
+use crate::args::{PrAction, PrTargetArgs};
+use crate::core::{StackBases, stack_branch_names};
+use crate::db::Database;
+use crate::git::Git;
+use crate::provider::{PrInfo, PrState, Provider};
+
+#[derive(Debug, Clone, Copy)]
+enum Lifecycle {
+    Ready,
+    Draft,
+    Close,
+    Reopen,
+}
+
+impl Lifecycle {
+    fn as_str(self) -> &'static str {
+        match self {
+            Lifecycle::Ready => "ready",
+            Lifecycle::Draft => "draft",
+            Lifecycle::Close => "close",
+            Lifecycle::Reopen => "reopen",
+        }
+    }
+
+    /// Why `pr` cannot take this transition, or `None` when it can.
+    fn skip_reason(self, pr: &PrInfo) -> Option<String> {
+        match (self, &pr.state) {
+            (Lifecycle::Reopen, PrState::Closed) => None,
+            (Lifecycle::Reopen, state) => Some(format!("PR #{} is {}", pr.number, state.as_str())),
+            (_, PrState::Open) => match self {
+                Lifecycle::Ready if !pr.is_draft => {
+                    Some(format!("PR #{} is already ready for review", pr.number))
+                }
+                Lifecycle::Draft if pr.is_draft => {
+                    Some(format!("PR #{} is already a draft", pr.number))
+                }
+                _ => None,
+            },
+            (_, state) => Some(format!("PR #{} is {}", pr.number, state.as_str())),
+        }
+    }
+
+    fn apply(self, provider: &dyn Provider, pr_number: i64) -> Result<()> {
+        match self {
+            Lifecycle::Ready => provider.set_pr_draft(pr_number, false),
+            Lifecycle::Draft => provider.set_pr_draft(pr_number, true),
+            Lifecycle::Close => provider.close_pr(pr_number),
+            Lifecycle::Reopen => provider.reopen_pr(pr_number),
+        }
+    }
+
+    fn describe(self, pr_number: i64, branch: &str) -> String {
+        match self {
+            Lifecycle::Ready => format!("marked PR #{pr_number} ('{branch}') ready for review"),
+            Lifecycle::Draft => format!("converted PR #{pr_number} ('{branch}') to draft"),
+            Lifecycle::Close => format!("closed PR #{pr_number} ('{branch}')"),
+            Lifecycle::Reopen => format!("reopened PR #{pr_number} ('{branch}')"),
+        }
+    }
+}
+
+struct LifecycleResult {
+    branch: String,
+    pr_number: Option<i64>,
+    skipped: Option<String>,
+}
+
+pub fn run(
+    db: &Database,
+    git: &Git,
+    provider: &dyn Provider,
+    action: &PrAction,
+    bases: &StackBases,
+    porcelain: bool,
+) -> Result<()> {
+    let (lifecycle, args): (Lifecycle, &PrTargetArgs) = match action {
+        PrAction::Ready(args) => (Lifecycle::Ready, args),
+        PrAction::Draft(args) => (Lifecycle::Draft, args),
+        PrAction::Close(args) => (Lifecycle::Close, args),
+        PrAction::Reopen(args) => (Lifecycle::Reopen, args),
+    };
+    let branch = match &args.branch {
+        Some(branch) => branch.clone(),
+        None => git.current_branch()?,
+    };
+    if branch.trim().is_empty() || bases.contains(&branch) {
+        return Err(anyhow!(
+            "no stacked branch selected; switch to a stacked branch or pass --branch <branch>"
+        ));
+    }
+
+    let records = db.list_branches()?;
+    let targets = if args.stack {
+        stack_branch_names(&records, bases, &branch)
+    } else {
+        vec![branch.clone()]
+    };
+    let queries = targets
+        .iter()
+        .map(|name| {
+            let cached = records
+                .iter()
+                .find(|r| &r.name == name)
+                .and_then(|r| r.cached_pr_number);
+            (name.as_str(), cached)
+        })
+        .collect::<Vec<_>>();
+    let prs = provider.resolve_prs_by_head(&queries)?;
+    if !args.stack && !prs.contains_key(&branch) {
+        return Err(anyhow!("no PR found for '{branch}'"));
+    }
+
+    let mut results = Vec::new();
+    for name in &targets {
+        let Some(pr) = prs.get(name) else {
+            if !porcelain {
+                println!("skipped '{name}': no PR found");
+            }
+            results.push(LifecycleResult {
+                branch: name.clone(),
+                pr_number: None,
+                skipped: Some("no PR found".to_string()),
+            });
+            continue;
+        };
+        let frozen = records.iter().any(|r| &r.name == name && r.frozen);
+        let skipped = if frozen {
+            Some("branch is frozen".to_string())
+        } else {
+            lifecycle.skip_reason(pr)
+        };
+        if skipped.is_none() {
+            lifecycle.apply(provider, pr.number)?;
+            match lifecycle {
+                Lifecycle::Close => db.set_pr_cache(name, Some(pr.number), Some("closed"))?,
+                Lifecycle::Reopen => db.set_pr_cache(name, Some(pr.number), Some("open"))?,
+                Lifecycle::Ready | Lifecycle::Draft => {}
+            }
+        }
+        if !porcelain {
+            match &skipped {
+                Some(reason) => println!("skipped '{name}': {reason}"),
+                None => println!("{}", lifecycle.describe(pr.number, name)),
+            }
+        }
+        results.push(LifecycleResult {
+            branch: name.clone(),
+            pr_number: Some(pr.number),
+            skipped,
+        });
+    }
+
+    if porcelain {
+        let results = results
+            .iter()
+            .map(|result| {
+                serde_json::json!({
+                    "branch": result.branch,
+                    "pr_number": result.pr_number,
+                    "changed": result.skipped.is_none(),
+                    "skipped": result.skipped,
+                })
+            })
+            .collect::<Vec<_>>();
+        return crate::views::print_json(&serde_json::json!({
+            "action": lifecycle.as_str(),
+            "results": results,
+        }));
+    }
+    Ok(())
+}
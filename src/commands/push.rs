@@ -1,19 +1,33 @@
 use anyhow::Result;
 
-use crate::db::Database;
+use crate::core::{OrderStatusUpdate, StackBases, order_status_updates};
+use crate::db::{BranchRecord, Database};
 use crate::git::Git;
+use crate::provider::Provider;
 
-pub fn run(db: &Database, git: &Git, porcelain: bool, base_branch: &str) -> Result<()> {
+pub struct PushRunOptions {
+    pub porcelain: bool,
+    pub order_status: bool,
+}
+
+pub fn run(
+    db: &Database,
+    git: &Git,
+    provider: &dyn Provider,
+    bases: &StackBases,
+    opts: PushRunOptions,
+) -> Result<()> {
+    let porcelain = opts.porcelain;
     let records = db.list_branches()?;
-    let mut branches: Vec<(String, bool)> = records
+    let mut branches: Vec<(String, bool, bool)> = records
         .iter()
-        .filter(|record| record.name != base_branch)
+        .filter(|record| !bases.contains(&record.name))
         .map(|record| {
             let is_merged = record
                 .cached_pr_state
                 .as_deref()
                 .is_some_and(|state| state.eq_ignore_ascii_case("merged"));
-            (record.name.clone(), is_merged)
+            (record.name.clone(), is_merged, record.frozen)
         })
         .collect();
     branches.sort_by(|a, b| a.0.cmp(&b.0));
@@ -22,8 +36,13 @@ pub fn run(db: &Database, git: &Git, porcelain: bool, base_branch: &str) -> Resu
     let mut pushed = Vec::new();
     let mut skipped_missing = Vec::new();
     let mut skipped_merged = Vec::new();
+    let mut skipped_frozen = Vec::new();
 
-    for (branch, is_merged) in branches {
+    for (branch, is_merged, is_frozen) in branches {
+        if is_frozen {
+            skipped_frozen.push(branch);
+            continue;
+        }
         if is_merged {
             skipped_merged.push(branch);
             continue;
@@ -33,24 +52,49 @@ pub fn run(db: &Database, git: &Git, porcelain: bool, base_branch: &str) -> Resu
             continue;
         }
 
-        let remote = git
-            .remote_for_branch(&branch)?
-            .or_else(|| git.remote_for_branch(base_branch).ok().flatten())
-            .unwrap_or_else(|| "origin".to_string());
+        let remote = git.push_remote_for_branch(&branch, bases.base_for(&records, &branch))?;
         git.push_branch_force_with_lease(&remote, &branch)?;
         pushed.push((branch, remote));
     }
 
+    let mut order_statuses = Vec::new();
+    if opts.order_status && !pushed.is_empty() {
+        let pushed_names = pushed.iter().map(|(b, _)| b.as_str()).collect::<Vec<_>>();
+        for update in refresh_order_statuses(provider, &records, bases, &pushed_names)? {
+            if !porcelain {
+                println!(
+                    "set {} {} on PR #{} ('{}'): {}",
+                    update.status.context,
+                    update.status.state.as_str(),
+                    update.pr_number,
+                    update.branch,
+                    update.status.description
+                );
+            }
+            order_statuses.push(serde_json::json!({
+                "branch": update.branch,
+                "pr_number": update.pr_number,
+                "state": update.status.state.as_str(),
+                "description": update.status.description,
+            }));
+        }
+    }
+
     if porcelain {
         let pushed = pushed
             .iter()
             .map(|(branch, remote)| serde_json::json!({ "branch": branch, "remote": remote }))
             .collect::<Vec<_>>();
-        return crate::views::print_json(&serde_json::json!({
+        let mut out = serde_json::json!({
             "pushed": pushed,
             "skipped_missing": skipped_missing,
             "skipped_merged": skipped_merged,
-        }));
+            "skipped_frozen": skipped_frozen,
+        });
+        if opts.order_status {
+            out["order_statuses"] = serde_json::Value::Array(order_statuses);
+        }
+        return crate::views::print_json(&out);
     }
 
     if pushed.is_empty() {
@@ -73,6 +117,30 @@ pub fn run(db: &Database, git: &Git, porcelain: bool, base_branch: &str) -> Resu
             skipped_merged.join(", ")
         );
     }
+    if !skipped_frozen.is_empty() {
+        println!("skipped frozen branches: {}", skipped_frozen.join(", "));
+    }
 
     Ok(())
 }
+
+/// Pushing moves PR heads to commits without a `stack/order` status, so post
+/// the current one on every pushed branch with an open PR.
+fn refresh_order_statuses(
+    provider: &dyn Provider,
+    records: &[BranchRecord],
+    bases: &StackBases,
+    pushed: &[&str],
+) -> Result<Vec<OrderStatusUpdate>> {
+    let queries = records
+        .iter()
+        .filter(|record| !bases.contains(&record.name))
+        .map(|record| (record.name.as_str(), record.cached_pr_number))
+        .collect::<Vec<_>>();
+    let prs = provider.resolve_prs_by_head(&queries)?;
+    let updates = order_status_updates(provider, records, &prs, bases, pushed);
+    for update in &updates {
+        provider.set_pr_head_status(update.pr_number, &update.status)?;
+    }
+    Ok(updates)
+}
@@ -0,0 +1,67 @@
+use anyhow::Result;
+
+use crate::core::StackBases;
+use crate::db::Database;
+use crate::git::Git;
+use crate::provider::CachedProvider;
+
+pub fn run(
+    db: &Database,
+    git: &Git,
+    provider: &CachedProvider<'_>,
+    bases: &StackBases,
+    porcelain: bool,
+) -> Result<()> {
+    let mut targets = Vec::new();
+    for record in db.list_branches()? {
+        if !bases.contains(&record.name) && git.branch_exists(&record.name)? {
+            targets.push(record);
+        }
+    }
+    let queries = targets
+        .iter()
+        .map(|record| (record.name.as_str(), record.cached_pr_number))
+        .collect::<Vec<_>>();
+    let prs = provider.refresh(&queries)?;
+
+    let mut refreshed = Vec::new();
+    for record in &targets {
+        let pr = prs.get(&record.name);
+        db.set_pr_cache(
+            &record.name,
+            pr.map(|p| p.number),
+            pr.map(|p| p.state.as_str()),
+        )?;
+        refreshed.push((
+            record.name.clone(),
+            pr.map(|p| (p.number, p.state.as_str())),
+        ));
+    }
+
+    if porcelain {
+        let refreshed = refreshed
+            .iter()
+            .map(|(branch, pr)| {
+                serde_json::json!({
+                    "branch": branch,
+                    "pr_number": pr.map(|(number, _)| number),
+                    "pr_state": pr.map(|(_, state)| state),
+                })
+            })
+            .collect::<Vec<_>>();
+        return crate::views::print_json(&serde_json::json!({ "refreshed": refreshed }));
+    }
+
+    if refreshed.is_empty() {
+        println!("no tracked non-base branches to refresh");
+        return Ok(());
+    }
+    for (branch, pr) in &refreshed {
+        match pr {
+            Some((number, state)) => println!("'{branch}': PR #{number} ({state})"),
+            None => println!("'{branch}': no PR"),
+        }
+    }
+    println!("refreshed PR metadata for {} branch(es)", refreshed.len());
+    Ok(())
+}
@@ -3,10 +3,11 @@ use std::io::{IsTerminal, stdin, stdout};
 
 use anyhow::Result;
 
-use crate::core::{BranchLinkTarget, render_tree};
+use crate::core::{BranchLinkTarget, StackBases, render_tree};
 use crate::db::{BranchRecord, Database};
 use crate::git::Git;
 use crate::ui::tui;
+use crate::util::codeowners::CodeOwners;
 use crate::util::pr_links::determine_pr_link_target;
 use crate::views::{BranchView, print_json};
 
@@ -15,15 +16,14 @@ pub fn run(
     git: &Git,
     porcelain: bool,
     interactive: bool,
-    base_branch: &str,
+    bases: &StackBases,
     base_remote: &str,
 ) -> Result<()> {
     let records = db.list_branches()?;
-    let branch_views = to_branch_views(git, &records)?;
-
     if porcelain {
-        return print_json(&branch_views);
+        return print_json(&to_branch_views(git, &records, true)?);
     }
+    let branch_views = to_branch_views(git, &records, false)?;
 
     let is_tty = stdout().is_terminal() && stdin().is_terminal();
     if interactive && is_tty {
@@ -31,15 +31,15 @@ pub fn run(
     }
 
     let should_color = is_tty && std::env::var_os("NO_COLOR").is_none();
-    let pr_base_url = git.remote_web_url(base_remote)?;
-    let link_targets = build_branch_link_targets(git, &records, base_branch);
+    let pr_base_url = git.pr_repo_web_url(base_remote)?;
+    let link_targets = build_branch_link_targets(git, &records, bases);
     println!(
         "{}",
         render_tree(
             &records,
             should_color,
             pr_base_url.as_deref(),
-            base_branch,
+            bases,
             Some(&link_targets),
         )
     );
@@ -49,7 +49,7 @@ pub fn run(
 fn build_branch_link_targets(
     git: &Git,
     records: &[BranchRecord],
-    default_base_branch: &str,
+    bases: &StackBases,
 ) -> HashMap<String, BranchLinkTarget> {
     let by_id: HashMap<i64, String> = records.iter().map(|r| (r.id, r.name.clone())).collect();
     let mut out = HashMap::new();
@@ -57,8 +57,8 @@ fn build_branch_link_targets(
         let compare_base = rec
             .parent_branch_id
             .and_then(|id| by_id.get(&id).cloned())
-            .unwrap_or_else(|| default_base_branch.to_string());
-        if compare_base == rec.name {
+            .unwrap_or_else(|| bases.default_branch().to_string());
+        if compare_base == rec.name || bases.contains(&rec.name) {
             continue;
         }
         if let Ok(target) = determine_pr_link_target(git, &compare_base, &rec.name) {
@@ -74,7 +74,13 @@ fn build_branch_link_targets(
     out
 }
 
-fn to_branch_views(git: &Git, records: &[BranchRecord]) -> Result<Vec<BranchView>> {
+/// CODEOWNERS lookups diff every branch, so only porcelain output asks for
+/// them.
+fn to_branch_views(
+    git: &Git,
+    records: &[BranchRecord],
+    with_codeowners: bool,
+) -> Result<Vec<BranchView>> {
     let mut id_map: HashMap<i64, String> = HashMap::new();
     for rec in records {
         id_map.insert(rec.id, rec.name.clone());
@@ -84,13 +90,25 @@ fn to_branch_views(git: &Git, records: &[BranchRecord]) -> Result<Vec<BranchView
         .iter()
         .map(|rec| {
             let exists_in_git = git.branch_exists(&rec.name)?;
+            let parent = rec.parent_branch_id.and_then(|id| id_map.get(&id).cloned());
+            let codeowners = match &parent {
+                Some(parent) if with_codeowners && exists_in_git => {
+                    CodeOwners::owners_for_branch(git, &rec.name, parent).unwrap_or_default()
+                }
+                _ => Vec::new(),
+            };
             Ok(BranchView {
                 name: rec.name.clone(),
-                parent: rec.parent_branch_id.and_then(|id| id_map.get(&id).cloned()),
+                parent,
                 last_synced_head_sha: rec.last_synced_head_sha.clone(),
                 cached_pr_number: rec.cached_pr_number,
                 cached_pr_state: rec.cached_pr_state.clone(),
                 exists_in_git,
+                codeowners,
+                description: rec.description.clone(),
+                ticket: rec.ticket.clone(),
+                owner: rec.owner.clone(),
+                frozen: rec.frozen,
             })
         })
         .collect()
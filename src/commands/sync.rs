@@ -3,7 +3,7 @@ use std::io::{IsTerminal, stdin, stdout};
 use anyhow::Result;
 use crossterm::style::Stylize;
 
-use crate::core::build_sync_plan;
+use crate::core::{StackBases, SyncPlanOptions, build_sync_plan};
 use crate::db::Database;
 use crate::git::Git;
 use crate::provider::Provider;
@@ -13,23 +13,27 @@ pub struct SyncRunOptions {
     pub porcelain: bool,
     pub yes: bool,
     pub dry_run: bool,
+    pub plan: SyncPlanOptions,
 }
 
 pub fn run(
     db: &Database,
     git: &Git,
     provider: &dyn Provider,
-    base_branch: &str,
+    bases: &StackBases,
     base_remote: &str,
     opts: SyncRunOptions,
 ) -> Result<()> {
-    let plan = build_sync_plan(db, git, provider, base_branch, base_remote)?;
+    let plan = build_sync_plan(db, git, provider, bases, base_remote, &opts.plan)?;
     let plan_view = plan.to_view();
 
     if opts.porcelain {
         crate::views::print_json(&plan_view)?;
     } else {
-        println!("sync base: {}", plan.base_branch);
+        match bases.names().as_slice() {
+            [_] => println!("sync base: {}", plan.base_branch),
+            names => println!("sync bases: {}", names.join(", ")),
+        }
         let use_color = stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
         for op in &plan_view.operations {
             if use_color {
@@ -37,6 +41,7 @@ pub fn run(
                     "fetch" => op.kind.as_str().blue().bold().to_string(),
                     "restack" => op.kind.as_str().yellow().bold().to_string(),
                     "update_sha" => op.kind.as_str().cyan().to_string(),
+                    "skip" => op.kind.as_str().dark_grey().to_string(),
                     _ => op.kind.clone(),
                 };
                 println!("- {}: {} {}", kind, op.branch.as_str().green(), op.details);
@@ -65,6 +70,7 @@ pub fn run(
         return Ok(());
     }
 
+    db.snapshot("sync");
     crate::core::execute_sync_plan(db, git, provider, &plan)?;
     if !opts.porcelain {
         println!("sync completed");
@@ -84,7 +90,16 @@ pub fn run(
     };
 
     if should_push {
-        crate::commands::push::run(db, git, false, base_branch)?;
+        crate::commands::push::run(
+            db,
+            git,
+            provider,
+            bases,
+            crate::commands::push::PushRunOptions {
+                porcelain: false,
+                order_status: opts.plan.order_status,
+            },
+        )?;
     }
 
     Ok(())
@@ -5,7 +5,7 @@ use anyhow::{Result, anyhow};
 use dialoguer::{Select, theme::ColorfulTheme};
 
 use crate::args::TrackArgs;
-use crate::core::rank_parent_candidates;
+use crate::core::{StackBases, rank_parent_candidates};
 use crate::db::{BranchRecord, Database, ParentUpdate};
 use crate::git::Git;
 use crate::provider::{PrState, Provider};
@@ -92,11 +92,12 @@ pub fn run(
     let mut unresolved = Vec::new();
     let mut warnings = Vec::new();
 
+    let bases = StackBases::load(db)?;
     let mut assumed_target: Option<String> = None;
     let targets: Vec<String> = if args.all {
         local
             .iter()
-            .filter(|b| b.as_str() != base_branch)
+            .filter(|b| !bases.contains(b))
             .cloned()
             .collect()
     } else if let Some(branch) = &args.branch {
@@ -104,7 +105,7 @@ pub fn run(
     } else {
         let viable_names: Vec<String> = local
             .iter()
-            .filter(|b| b.as_str() != base_branch)
+            .filter(|b| !bases.contains(b))
             .cloned()
             .collect();
         if viable_names.is_empty() {
@@ -163,7 +164,7 @@ pub fn run(
         if !local_set.contains(&target) {
             return Err(anyhow!("branch '{}' does not exist in git", target));
         }
-        if target == base_branch {
+        if bases.contains(&target) {
             skipped.push(TrackSkip {
                 branch: target,
                 reason: "base branch is not eligible for tracking".to_string(),
@@ -340,6 +341,9 @@ pub fn run(
 
     let applied = !opts.dry_run && !apply_changes.is_empty();
     if applied {
+        if args.all {
+            db.snapshot("track-all");
+        }
         let updates: Vec<ParentUpdate> = apply_changes
             .iter()
             .map(|c| ParentUpdate {
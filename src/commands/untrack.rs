@@ -3,6 +3,7 @@ use std::io::{IsTerminal, stdin, stdout};
 use anyhow::{Result, anyhow};
 use dialoguer::{Select, theme::ColorfulTheme};
 
+use crate::core::StackBases;
 use crate::db::Database;
 use crate::git::Git;
 use crate::ui::interaction::{confirm_inline_yes_no, prompt_or_cancel};
@@ -13,14 +14,14 @@ pub fn run(
     git: &Git,
     branch_arg: Option<&str>,
     porcelain: bool,
-    base_branch: &str,
+    bases: &StackBases,
     yes: bool,
 ) -> Result<()> {
     let current = git.current_branch()?;
     let records = db.list_branches()?;
     let viable_names: Vec<String> = records
         .iter()
-        .filter(|r| r.name != base_branch)
+        .filter(|r| !bases.contains(&r.name))
         .map(|r| r.name.clone())
         .collect();
 
@@ -28,7 +29,7 @@ pub fn run(
     let branch = if let Some(branch) = branch_arg {
         branch.to_string()
     } else if viable_names.is_empty() {
-        base_branch.to_string()
+        bases.default_branch().to_string()
     } else if viable_names.len() == 1 {
         let assumed = viable_names[0].clone();
         if !porcelain {
@@ -74,7 +75,7 @@ pub fn run(
         }
     }
 
-    if branch == base_branch {
+    if bases.contains(&branch) {
         let payload = serde_json::json!({
             "branch": branch,
             "action": "untrack",
@@ -84,7 +85,7 @@ pub fn run(
         if porcelain {
             crate::views::print_json(&payload)?;
         } else {
-            println!("base branch '{base_branch}' remains tracked as stack root; no changes made");
+            println!("base branch '{branch}' remains tracked as stack root; no changes made");
         }
         return Ok(());
     }
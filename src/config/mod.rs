@@ -0,0 +1,176 @@
+use std::fs;
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::core::validate_managed_template;
+use crate::git::{Git, RemoteSettings};
+use crate::provider::MergeMethod;
+use crate::util::pr_body::{ManagedSectionFormat, ManagedSectionPlacement, ManagedSectionStyle};
+use crate::util::pr_title::PrTitlePrefix;
+use crate::util::url::HostMap;
+
+#[derive(Debug, Clone, Default)]
+pub struct StackConfig {
+    pub merge_method: Option<MergeMethod>,
+    pub cache_ttl_secs: u64,
+    pub pr_body: ManagedSectionStyle,
+    pub pr_title_prefix: Option<PrTitlePrefix>,
+    pub pr_inherit_metadata: bool,
+    pub pr_draft_policy: bool,
+    pub order_status: bool,
+    pub host_map: HostMap,
+    pub remotes: RemoteSettings,
+}
+
+impl StackConfig {
+    pub fn load(git: &Git) -> Result<Self> {
+        let merge_method = git
+            .config_value("stack.mergeMethod")?
+            .map(|raw| {
+                MergeMethod::parse(&raw).ok_or_else(|| {
+                    anyhow!("invalid stack.mergeMethod '{raw}'; expected merge, squash or rebase")
+                })
+            })
+            .transpose()?;
+        let cache_ttl_secs = git
+            .config_value("stack.cacheTtl")?
+            .map(|raw| {
+                raw.trim().parse::<u64>().map_err(|_| {
+                    anyhow!("invalid stack.cacheTtl '{raw}'; expected a number of seconds")
+                })
+            })
+            .transpose()?
+            .unwrap_or_default();
+        let pr_body_format = git
+            .config_value("stack.prBodyFormat")?
+            .map(|raw| {
+                ManagedSectionFormat::parse(&raw).ok_or_else(|| {
+                    anyhow!("invalid stack.prBodyFormat '{raw}'; expected chain, list or table")
+                })
+            })
+            .transpose()?
+            .unwrap_or_default();
+        let pr_section_placement = git
+            .config_value("stack.prSectionMode")?
+            .map(|raw| {
+                ManagedSectionPlacement::parse(&raw).ok_or_else(|| {
+                    anyhow!("invalid stack.prSectionMode '{raw}'; expected body or comment")
+                })
+            })
+            .transpose()?
+            .unwrap_or_default();
+        let pr_body_template = match git.config_value("stack.prBodyTemplate")? {
+            Some(template) => Some(template),
+            None => git
+                .config_value("stack.prBodyTemplateFile")?
+                .map(|path| {
+                    let path = git.root().join(path.trim());
+                    fs::read_to_string(&path).with_context(|| {
+                        format!("failed to read stack.prBodyTemplateFile {}", path.display())
+                    })
+                })
+                .transpose()?,
+        };
+        if let Some(template) = &pr_body_template {
+            validate_managed_template(template).context("invalid PR body template")?;
+        }
+
+        let ticket_url = git
+            .config_value("stack.ticketUrl")?
+            .map(|raw| {
+                let pattern = raw.trim();
+                if pattern.contains("{ticket}") {
+                    Ok(pattern.to_string())
+                } else {
+                    Err(anyhow!(
+                        "invalid stack.ticketUrl '{raw}'; expected a URL containing {{ticket}}"
+                    ))
+                }
+            })
+            .transpose()?;
+
+        let pr_title_prefix = git
+            .config_value("stack.prTitlePrefix")?
+            .map(|raw| PrTitlePrefix::parse(&raw).context("invalid stack.prTitlePrefix"))
+            .transpose()?
+            .flatten();
+        let pr_inherit_metadata = git
+            .config_value("stack.prInheritMetadata")?
+            .map(|raw| {
+                parse_bool(&raw).ok_or_else(|| {
+                    anyhow!("invalid stack.prInheritMetadata '{raw}'; expected true or false")
+                })
+            })
+            .transpose()?
+            .unwrap_or_default();
+        let pr_draft_policy = git
+            .config_value("stack.prDraftPolicy")?
+            .map(|raw| {
+                parse_bool(&raw).ok_or_else(|| {
+                    anyhow!("invalid stack.prDraftPolicy '{raw}'; expected true or false")
+                })
+            })
+            .transpose()?
+            .unwrap_or_default();
+        let order_status = git
+            .config_value("stack.orderStatus")?
+            .map(|raw| {
+                parse_bool(&raw).ok_or_else(|| {
+                    anyhow!("invalid stack.orderStatus '{raw}'; expected true or false")
+                })
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        let host_map = HostMap::parse(
+            git.config_values("stack.hostMapping")?
+                .iter()
+                .map(String::as_str),
+        )?;
+
+        let pr_repo = git
+            .config_value("stack.prRepo")?
+            .map(|raw| {
+                let repo = raw.trim().trim_end_matches(".git");
+                match repo.split_once('/') {
+                    Some((owner, name))
+                        if !owner.is_empty() && !name.is_empty() && !name.contains('/') =>
+                    {
+                        Ok(repo.to_string())
+                    }
+                    _ => Err(anyhow!("invalid stack.prRepo '{raw}'; expected owner/name")),
+                }
+            })
+            .transpose()?;
+        let remotes = RemoteSettings {
+            push_remote: git.config_value("stack.pushRemote")?,
+            base_remote: git.config_value("stack.baseRemote")?,
+            pr_repo,
+        };
+
+        Ok(Self {
+            merge_method,
+            cache_ttl_secs,
+            pr_body: ManagedSectionStyle {
+                format: pr_body_format,
+                template: pr_body_template,
+                placement: pr_section_placement,
+                ticket_url,
+            },
+            pr_title_prefix,
+            pr_inherit_metadata,
+            pr_draft_policy,
+            order_status,
+            host_map,
+            remotes,
+        })
+    }
+}
+
+fn parse_bool(raw: &str) -> Option<bool> {
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
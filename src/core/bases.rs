@@ -0,0 +1,103 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+
+use crate::db::{BranchRecord, Database};
+
+/// Branches stacks can sit on: the repository default base plus every base
+/// declared by a stack root (e.g. `release/*` or long-lived integration
+/// branches). Base branches are tracked as parentless roots and are never
+/// pushed, restacked or given PRs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackBases {
+    default: String,
+    declared: Vec<String>,
+}
+
+impl StackBases {
+    pub fn new(default: &str) -> Self {
+        Self {
+            default: default.to_string(),
+            declared: Vec::new(),
+        }
+    }
+
+    pub fn load(db: &Database) -> Result<Self> {
+        let mut bases = Self::new(&db.repo_meta()?.base_branch);
+        for (_, base) in db.stack_base_declarations()? {
+            bases = bases.with_declared(&base);
+        }
+        Ok(bases)
+    }
+
+    pub fn with_declared(mut self, base: &str) -> Self {
+        if base != self.default && !self.declared.iter().any(|b| b == base) {
+            self.declared.push(base.to_string());
+            self.declared.sort();
+        }
+        self
+    }
+
+    pub fn default_branch(&self) -> &str {
+        &self.default
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        name == self.default || self.declared.iter().any(|b| b == name)
+    }
+
+    /// The default base first, then declared bases in name order.
+    pub fn names(&self) -> Vec<&str> {
+        std::iter::once(self.default.as_str())
+            .chain(self.declared.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// The base `branch`'s stack sits on: the first base reached by walking
+    /// up its parent links, or the default base when none is reached.
+    pub fn base_for(&self, records: &[BranchRecord], branch: &str) -> &str {
+        let by_name: HashMap<&str, &BranchRecord> =
+            records.iter().map(|r| (r.name.as_str(), r)).collect();
+        let by_id: HashMap<i64, &str> = records.iter().map(|r| (r.id, r.name.as_str())).collect();
+        let mut cursor = branch;
+        let mut seen = HashSet::new();
+        while seen.insert(cursor) {
+            if let Some(base) = self.names().into_iter().find(|b| *b == cursor) {
+                return base;
+            }
+            let Some(parent) = by_name
+                .get(cursor)
+                .and_then(|r| r.parent_branch_id)
+                .and_then(|id| by_id.get(&id))
+            else {
+                break;
+            };
+            cursor = parent;
+        }
+        &self.default
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_for_walks_up_to_the_nearest_base() {
+        let records = vec![
+            BranchRecord::test(1, "main", None),
+            BranchRecord::test(2, "feat/a", Some(1)),
+            BranchRecord::test(3, "release/1.0", None),
+            BranchRecord::test(4, "fix/a", Some(3)),
+            BranchRecord::test(5, "fix/b", Some(4)),
+            BranchRecord::test(6, "loose", None),
+        ];
+        let bases = StackBases::new("main").with_declared("release/1.0");
+        assert_eq!(bases.base_for(&records, "feat/a"), "main");
+        assert_eq!(bases.base_for(&records, "fix/b"), "release/1.0");
+        assert_eq!(bases.base_for(&records, "release/1.0"), "release/1.0");
+        assert_eq!(bases.base_for(&records, "loose"), "main");
+        assert_eq!(bases.names(), ["main", "release/1.0"]);
+        assert!(!bases.contains("fix/a"));
+    }
+}
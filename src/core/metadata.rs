@@ -0,0 +1,494 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::db::{Database, ParentUpdate, PrCacheEntry};
+
+/// Git ref holding the shared metadata snapshot commits.
+pub const METADATA_REF: &str = "refs/stack/metadata";
+/// File inside each snapshot commit's tree.
+pub const METADATA_FILE: &str = "metadata.json";
+const METADATA_FORMAT_VERSION: u32 = 1;
+
+/// Portable snapshot of the stack database: parent links and cached PR data
+/// keyed by branch name. Local-only state such as sync SHAs is left out.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetadataDoc {
+    pub version: u32,
+    pub base_branch: String,
+    pub branches: BTreeMap<String, BranchMetadata>,
+    #[serde(default)]
+    pub pr_cache: BTreeMap<String, PrCacheMetadata>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BranchMetadata {
+    pub parent: Option<String>,
+    #[serde(default)]
+    pub pr_number: Option<i64>,
+    #[serde(default)]
+    pub pr_state: Option<String>,
+    /// Declared base branch when this branch roots a stack on a non-default
+    /// base.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stack_base: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrCacheMetadata {
+    pub number: Option<i64>,
+    pub state: Option<String>,
+    pub merge_commit_oid: Option<String>,
+    pub base_ref_name: Option<String>,
+    pub body: Option<String>,
+    pub url: Option<String>,
+    pub title: Option<String>,
+    #[serde(default)]
+    pub is_draft: bool,
+    pub fetched_at: i64,
+}
+
+/// Both sides changed `branch`'s parent differently; the local parent was
+/// kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataConflict {
+    pub branch: String,
+    pub local_parent: Option<String>,
+    pub remote_parent: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MetadataMerge {
+    pub doc: MetadataDoc,
+    pub conflicts: Vec<MetadataConflict>,
+}
+
+impl PrCacheMetadata {
+    pub fn to_entry(&self, branch: &str) -> PrCacheEntry {
+        PrCacheEntry {
+            branch: branch.to_string(),
+            number: self.number,
+            state: self.state.clone(),
+            merge_commit_oid: self.merge_commit_oid.clone(),
+            base_ref_name: self.base_ref_name.clone(),
+            body: self.body.clone(),
+            url: self.url.clone(),
+            title: self.title.clone(),
+            is_draft: self.is_draft,
+            fetched_at: self.fetched_at,
+        }
+    }
+}
+
+impl MetadataDoc {
+    pub fn from_json(raw: &str) -> Result<Self> {
+        let doc: Self = serde_json::from_str(raw).context("invalid stack metadata document")?;
+        if doc.version > METADATA_FORMAT_VERSION {
+            return Err(anyhow!(
+                "stack metadata format {} is newer than this stack supports ({METADATA_FORMAT_VERSION})",
+                doc.version
+            ));
+        }
+        Ok(doc)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)? + "\n")
+    }
+}
+
+/// Snapshot of the current database.
+pub fn export_metadata(db: &Database) -> Result<MetadataDoc> {
+    let records = db.list_branches()?;
+    let declared = db
+        .stack_base_declarations()?
+        .into_iter()
+        .collect::<BTreeMap<_, _>>();
+    let branches = records
+        .iter()
+        .map(|record| {
+            let parent = record
+                .parent_branch_id
+                .and_then(|id| records.iter().find(|r| r.id == id))
+                .map(|r| r.name.clone());
+            (
+                record.name.clone(),
+                BranchMetadata {
+                    parent,
+                    pr_number: record.cached_pr_number,
+                    pr_state: record.cached_pr_state.clone(),
+                    stack_base: declared.get(&record.name).cloned(),
+                },
+            )
+        })
+        .collect();
+    let pr_cache = db
+        .list_pr_cache_entries()?
+        .into_iter()
+        .map(|entry| {
+            (
+                entry.branch,
+                PrCacheMetadata {
+                    number: entry.number,
+                    state: entry.state,
+                    merge_commit_oid: entry.merge_commit_oid,
+                    base_ref_name: entry.base_ref_name,
+                    body: entry.body,
+                    url: entry.url,
+                    title: entry.title,
+                    is_draft: entry.is_draft,
+                    fetched_at: entry.fetched_at,
+                },
+            )
+        })
+        .collect();
+    Ok(MetadataDoc {
+        version: METADATA_FORMAT_VERSION,
+        base_branch: db.repo_meta()?.base_branch,
+        branches,
+        pr_cache,
+    })
+}
+
+/// Three-way merge of two metadata snapshots against their common ancestor.
+/// Branch presence and each field merge independently: a side that left a
+/// value unchanged takes the other side's change. When both sides changed a
+/// parent link differently the local link wins and a conflict is reported.
+/// PR cache entries keep the most recently fetched copy.
+pub fn merge_metadata(
+    base: &MetadataDoc,
+    local: &MetadataDoc,
+    remote: &MetadataDoc,
+) -> MetadataMerge {
+    let names = base
+        .branches
+        .keys()
+        .chain(local.branches.keys())
+        .chain(remote.branches.keys())
+        .collect::<BTreeSet<_>>();
+
+    let mut branches = BTreeMap::new();
+    let mut conflicts = Vec::new();
+    for name in names {
+        let (b, l, r) = (
+            base.branches.get(name),
+            local.branches.get(name),
+            remote.branches.get(name),
+        );
+        let (present, _) = merge3(&b.is_some(), &l.is_some(), &r.is_some());
+        if !present {
+            continue;
+        }
+        let (parent, local_parent, remote_parent) = merge_field(b, l, r, |m| m.parent.clone());
+        if let (Some(local_parent), Some(remote_parent)) = (local_parent, remote_parent) {
+            conflicts.push(MetadataConflict {
+                branch: name.clone(),
+                local_parent,
+                remote_parent,
+            });
+        }
+        let (pr_number, _, _) = merge_field(b, l, r, |m| m.pr_number);
+        let (pr_state, _, _) = merge_field(b, l, r, |m| m.pr_state.clone());
+        let (stack_base, _, _) = merge_field(b, l, r, |m| m.stack_base.clone());
+        branches.insert(
+            name.clone(),
+            BranchMetadata {
+                parent,
+                pr_number,
+                pr_state,
+                stack_base,
+            },
+        );
+    }
+
+    break_remote_cycles(&mut branches, &mut conflicts, base, local);
+
+    let mut pr_cache = local.pr_cache.clone();
+    for (branch, entry) in &remote.pr_cache {
+        let newer = pr_cache
+            .get(branch)
+            .is_none_or(|current| entry.fetched_at > current.fetched_at);
+        if newer {
+            pr_cache.insert(branch.clone(), entry.clone());
+        }
+    }
+    pr_cache.retain(|branch, _| branches.contains_key(branch));
+
+    MetadataMerge {
+        doc: MetadataDoc {
+            version: METADATA_FORMAT_VERSION,
+            base_branch: local.base_branch.clone(),
+            branches,
+            pr_cache,
+        },
+        conflicts,
+    }
+}
+
+/// Field-wise merging can still join two individually valid edits into a
+/// cycle (local moves `a` under `b` while remote moves `b` under `a`). Each
+/// cycle is broken by restoring the local parent of a branch whose link came
+/// from the remote side, reported as a conflict like any other.
+fn break_remote_cycles(
+    branches: &mut BTreeMap<String, BranchMetadata>,
+    conflicts: &mut Vec<MetadataConflict>,
+    base: &MetadataDoc,
+    local: &MetadataDoc,
+) {
+    let local_parent = |name: &str| {
+        local
+            .branches
+            .get(name)
+            .or_else(|| base.branches.get(name))
+            .and_then(|b| b.parent.clone())
+    };
+    while let Some(cycle) = find_cycle(branches) {
+        let Some(name) = cycle
+            .into_iter()
+            .find(|name| branches[name].parent != local_parent(name))
+        else {
+            break;
+        };
+        let branch = branches.get_mut(&name).expect("cycle member is merged");
+        let remote_parent = std::mem::replace(&mut branch.parent, local_parent(&name));
+        conflicts.retain(|c| c.branch != name);
+        conflicts.push(MetadataConflict {
+            local_parent: branch.parent.clone(),
+            remote_parent,
+            branch: name,
+        });
+    }
+    conflicts.sort_by(|a, b| a.branch.cmp(&b.branch));
+}
+
+/// Members of the first parent-link cycle found, sorted by name.
+fn find_cycle(branches: &BTreeMap<String, BranchMetadata>) -> Option<Vec<String>> {
+    for start in branches.keys() {
+        let mut path: Vec<&String> = Vec::new();
+        let mut cursor = Some(start);
+        while let Some(name) = cursor {
+            if let Some(pos) = path.iter().position(|seen| *seen == name) {
+                let mut cycle = path[pos..].iter().map(|n| (*n).clone()).collect::<Vec<_>>();
+                cycle.sort();
+                return Some(cycle);
+            }
+            path.push(name);
+            cursor = branches.get(name).and_then(|b| b.parent.as_ref());
+        }
+    }
+    None
+}
+
+/// Merges one field of a branch present after the merge. Values are compared
+/// as `Option`s so a branch added on both sides (absent in base) conflicts
+/// instead of silently taking one side; a side without the branch has not
+/// touched the field. On conflict the local and remote values are returned
+/// alongside the (local) result.
+fn merge_field<T: Clone + PartialEq + Default>(
+    base: Option<&BranchMetadata>,
+    local: Option<&BranchMetadata>,
+    remote: Option<&BranchMetadata>,
+    get: fn(&BranchMetadata) -> T,
+) -> (T, Option<T>, Option<T>) {
+    let base = base.map(get);
+    let local = local.map(get).or_else(|| base.clone());
+    let remote = remote.map(get).or_else(|| base.clone());
+    match merge3(&base, &local, &remote) {
+        (merged, true) => (merged.unwrap_or_default(), local, remote),
+        (merged, false) => (merged.unwrap_or_default(), None, None),
+    }
+}
+
+/// Takes the side that changed relative to `base`; `true` when both changed
+/// differently (local is kept).
+fn merge3<T: Clone + PartialEq>(base: &T, local: &T, remote: &T) -> (T, bool) {
+    if local == remote || remote == base {
+        (local.clone(), false)
+    } else if local == base {
+        (remote.clone(), false)
+    } else {
+        (local.clone(), true)
+    }
+}
+
+/// Branches whose links or cached PR fields differ between two snapshots,
+/// including added and removed ones.
+pub fn changed_branches(before: &MetadataDoc, after: &MetadataDoc) -> Vec<String> {
+    before
+        .branches
+        .keys()
+        .chain(after.branches.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter(|name| before.branches.get(*name) != after.branches.get(*name))
+        .cloned()
+        .collect()
+}
+
+/// Rewrites the database to match `doc`: branches missing from it are
+/// removed, parent links and cached PR data are replaced.
+/// Runs in one transaction, so a failure leaves the database untouched.
+pub fn apply_metadata(db: &Database, doc: &MetadataDoc) -> Result<()> {
+    db.transaction(|| apply_metadata_changes(db, doc))
+}
+
+fn apply_metadata_changes(db: &Database, doc: &MetadataDoc) -> Result<()> {
+    let current = export_metadata(db)?;
+    for name in current.branches.keys() {
+        if !doc.branches.contains_key(name) {
+            db.delete_branch(name)?;
+        }
+    }
+
+    let updates = doc
+        .branches
+        .iter()
+        .filter(|(name, branch)| {
+            current
+                .branches
+                .get(*name)
+                .is_none_or(|existing| existing.parent != branch.parent)
+        })
+        .map(|(name, branch)| ParentUpdate {
+            child_name: name.clone(),
+            parent_name: branch.parent.clone(),
+        })
+        .collect::<Vec<_>>();
+    db.set_parents_batch(&updates)
+        .context("stack metadata has conflicting parent links")?;
+
+    for (name, branch) in &doc.branches {
+        let cached = current
+            .branches
+            .get(name)
+            .map(|b| (b.pr_number, b.pr_state.as_deref()));
+        if cached != Some((branch.pr_number, branch.pr_state.as_deref())) {
+            db.set_pr_cache(name, branch.pr_number, branch.pr_state.as_deref())?;
+        }
+        if current.branches.get(name).map(|b| &b.stack_base) != Some(&branch.stack_base) {
+            db.set_stack_base(name, branch.stack_base.as_deref())?;
+        }
+    }
+
+    for branch in current.pr_cache.keys() {
+        if !doc.pr_cache.contains_key(branch) {
+            db.delete_pr_cache_entry(branch)?;
+        }
+    }
+    for (branch, entry) in &doc.pr_cache {
+        if current.pr_cache.get(branch) == Some(entry) {
+            continue;
+        }
+        db.upsert_pr_cache_entry(&entry.to_entry(branch))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(links: &[(&str, Option<&str>)]) -> MetadataDoc {
+        MetadataDoc {
+            version: METADATA_FORMAT_VERSION,
+            base_branch: "main".to_string(),
+            branches: links
+                .iter()
+                .map(|(name, parent)| {
+                    (
+                        name.to_string(),
+                        BranchMetadata {
+                            parent: parent.map(str::to_string),
+                            ..BranchMetadata::default()
+                        },
+                    )
+                })
+                .collect(),
+            pr_cache: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn merge_takes_each_sides_changes_and_keeps_local_on_conflict() {
+        let base = doc(&[
+            ("main", None),
+            ("a", Some("main")),
+            ("b", Some("a")),
+            ("c", Some("main")),
+            ("gone", Some("main")),
+        ]);
+        let local = doc(&[
+            ("main", None),
+            ("a", Some("main")),
+            ("b", Some("main")),
+            ("c", Some("a")),
+            ("local-new", Some("a")),
+        ]);
+        let remote = doc(&[
+            ("main", None),
+            ("a", Some("main")),
+            ("b", Some("a")),
+            ("c", Some("b")),
+            ("gone", Some("main")),
+            ("remote-new", Some("b")),
+        ]);
+
+        let merged = merge_metadata(&base, &local, &remote);
+        let parents = merged
+            .doc
+            .branches
+            .iter()
+            .map(|(name, b)| (name.as_str(), b.parent.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parents,
+            [
+                ("a", Some("main")),
+                ("b", Some("main")),
+                ("c", Some("a")),
+                ("local-new", Some("a")),
+                ("main", None),
+                ("remote-new", Some("b")),
+            ]
+        );
+        assert_eq!(
+            merged.conflicts,
+            [MetadataConflict {
+                branch: "c".to_string(),
+                local_parent: Some("a".to_string()),
+                remote_parent: Some("b".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn merge_reports_cycles_formed_by_opposite_reparents() {
+        let base = doc(&[("main", None), ("a", Some("main")), ("b", Some("main"))]);
+        let local = doc(&[("main", None), ("a", Some("b")), ("b", Some("main"))]);
+        let remote = doc(&[("main", None), ("a", Some("main")), ("b", Some("a"))]);
+
+        let merged = merge_metadata(&base, &local, &remote);
+        assert_eq!(merged.doc.branches["a"].parent.as_deref(), Some("b"));
+        assert_eq!(merged.doc.branches["b"].parent.as_deref(), Some("main"));
+        assert_eq!(
+            merged.conflicts,
+            [MetadataConflict {
+                branch: "b".to_string(),
+                local_parent: Some("main".to_string()),
+                remote_parent: Some("a".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn metadata_round_trips_through_json_and_rejects_newer_formats() {
+        let original = doc(&[("main", None), ("a", Some("main"))]);
+        let parsed = MetadataDoc::from_json(&original.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, original);
+        let err =
+            MetadataDoc::from_json(r#"{"version": 99, "base_branch": "main", "branches": {}}"#)
+                .unwrap_err();
+        assert!(err.to_string().contains("newer than this stack supports"));
+    }
+}
@@ -1,7 +1,23 @@
+mod bases;
+mod metadata;
+mod order_status;
 mod parents;
+mod pr_lifecycle;
+mod pr_section;
 mod render;
 mod sync;
 
+pub use bases::StackBases;
+pub use metadata::{
+    METADATA_FILE, METADATA_REF, MetadataDoc, apply_metadata, changed_branches, export_metadata,
+    merge_metadata,
+};
+pub use order_status::{OrderStatusUpdate, order_status, order_status_updates};
 pub use parents::rank_parent_candidates;
+pub use pr_lifecycle::{draft_policy_changes, stack_branch_names};
+pub use pr_section::{
+    ManagedSectionBuilder, ManagedSectionUpdate, managed_section_updates, refresh_managed_prs,
+    validate_managed_template,
+};
 pub use render::{BranchLinkTarget, render_tree};
-pub use sync::{build_sync_plan, execute_sync_plan};
+pub use sync::{SyncOp, SyncPlan, SyncPlanOptions, build_sync_plan, execute_sync_plan};
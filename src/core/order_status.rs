@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use crate::core::StackBases;
+use crate::db::BranchRecord;
+use crate::provider::{CommitStatus, CommitStatusState, PrInfo, PrState, Provider};
+
+/// Commit status context that branch protection can require so stacked PRs
+/// merge bottom-up.
+pub const ORDER_STATUS_CONTEXT: &str = "stack/order";
+
+/// A `stack/order` status to post on one PR's head commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderStatusUpdate {
+    pub branch: String,
+    pub pr_number: i64,
+    pub status: CommitStatus,
+}
+
+/// The `stack/order` status `branch`'s open PR should carry: success once it
+/// targets base or its parent's PR merged, pending otherwise.
+pub fn order_status(
+    records: &[BranchRecord],
+    pr_by_branch: &HashMap<String, PrInfo>,
+    bases: &StackBases,
+    branch: &str,
+) -> Option<CommitStatus> {
+    let pr = pr_by_branch.get(branch)?;
+    if !matches!(pr.state, PrState::Open) {
+        return None;
+    }
+    let base_branch = bases.base_for(records, branch);
+    let parent = records
+        .iter()
+        .find(|r| r.name == branch)
+        .and_then(|r| r.parent_branch_id)
+        .and_then(|id| records.iter().find(|r| r.id == id))
+        .map(|r| r.name.as_str())
+        .unwrap_or(base_branch);
+    let targets_base = pr.base_ref_name.as_deref() == Some(base_branch);
+    let (state, description) = match pr_by_branch.get(parent) {
+        _ if targets_base || parent == base_branch => {
+            (CommitStatusState::Success, format!("targets {base_branch}"))
+        }
+        Some(parent_pr) if matches!(parent_pr.state, PrState::Merged) => (
+            CommitStatusState::Success,
+            format!("parent #{} merged", parent_pr.number),
+        ),
+        Some(parent_pr) => (
+            CommitStatusState::Pending,
+            format!("merge #{} ({parent}) first", parent_pr.number),
+        ),
+        None => (
+            CommitStatusState::Pending,
+            format!("parent '{parent}' has no PR yet"),
+        ),
+    };
+    Some(CommitStatus {
+        context: ORDER_STATUS_CONTEXT.to_string(),
+        state,
+        description,
+    })
+}
+
+/// Statuses for `branches` that differ from what their PR heads carry.
+/// Failures to read current statuses are reported as warnings and the status
+/// is posted anyway.
+pub fn order_status_updates(
+    provider: &dyn Provider,
+    records: &[BranchRecord],
+    pr_by_branch: &HashMap<String, PrInfo>,
+    bases: &StackBases,
+    branches: &[&str],
+) -> Vec<OrderStatusUpdate> {
+    let mut updates = Vec::new();
+    for &branch in branches {
+        let Some(status) = order_status(records, pr_by_branch, bases, branch) else {
+            continue;
+        };
+        let pr_number = pr_by_branch[branch].number;
+        match provider.pr_head_statuses(pr_number) {
+            Ok(current) if current.contains(&status) => continue,
+            Ok(_) => {}
+            Err(err) => {
+                eprintln!("warning: could not read commit statuses of PR #{pr_number}: {err}")
+            }
+        }
+        updates.push(OrderStatusUpdate {
+            branch: branch.to_string(),
+            pr_number,
+            status,
+        });
+    }
+    updates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pr(number: i64, state: PrState, base: &str) -> PrInfo {
+        PrInfo {
+            number,
+            state,
+            merge_commit_oid: None,
+            base_ref_name: Some(base.to_string()),
+            body: None,
+            url: None,
+            title: None,
+            is_draft: false,
+        }
+    }
+
+    #[test]
+    fn order_status_is_pending_until_parent_merges() {
+        let records = vec![
+            BranchRecord::test(1, "main", None),
+            BranchRecord::test(2, "a", Some(1)),
+            BranchRecord::test(3, "b", Some(2)),
+            BranchRecord::test(4, "c", Some(3)),
+        ];
+        let mut prs = HashMap::from([
+            ("a".to_string(), pr(1, PrState::Open, "main")),
+            ("b".to_string(), pr(2, PrState::Open, "a")),
+            ("c".to_string(), pr(3, PrState::Open, "b")),
+        ]);
+        let state_of = |prs: &HashMap<String, PrInfo>, branch| {
+            order_status(&records, prs, &StackBases::new("main"), branch)
+                .map(|s| (s.state, s.description))
+        };
+        assert_eq!(
+            state_of(&prs, "a"),
+            Some((CommitStatusState::Success, "targets main".to_string()))
+        );
+        assert_eq!(
+            state_of(&prs, "b"),
+            Some((CommitStatusState::Pending, "merge #1 (a) first".to_string()))
+        );
+
+        prs.insert("a".to_string(), pr(1, PrState::Merged, "main"));
+        assert_eq!(state_of(&prs, "a"), None);
+        assert_eq!(
+            state_of(&prs, "b"),
+            Some((CommitStatusState::Success, "parent #1 merged".to_string()))
+        );
+        assert_eq!(
+            state_of(&prs, "c"),
+            Some((CommitStatusState::Pending, "merge #2 (b) first".to_string()))
+        );
+    }
+}
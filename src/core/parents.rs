@@ -44,6 +44,10 @@ mod tests {
                 last_synced_head_sha: None,
                 cached_pr_number: None,
                 cached_pr_state: None,
+                description: None,
+                ticket: None,
+                owner: None,
+                frozen: false,
             },
             BranchRecord {
                 id: 2,
@@ -52,6 +56,10 @@ mod tests {
                 last_synced_head_sha: None,
                 cached_pr_number: None,
                 cached_pr_state: None,
+                description: None,
+                ticket: None,
+                owner: None,
+                frozen: false,
             },
         ];
         let local = vec![
@@ -0,0 +1,190 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::core::StackBases;
+use crate::db::BranchRecord;
+use crate::provider::{PrInfo, PrState};
+
+/// Tracked branches of the stack containing `branch`, from the root above
+/// base through every descendant (children in name order). An untracked
+/// branch is its own stack.
+pub fn stack_branch_names(
+    records: &[BranchRecord],
+    bases: &StackBases,
+    branch: &str,
+) -> Vec<String> {
+    let by_id: HashMap<i64, &BranchRecord> = records.iter().map(|r| (r.id, r)).collect();
+    let Some(mut root) = records.iter().find(|r| r.name == branch) else {
+        return vec![branch.to_string()];
+    };
+    if bases.contains(&root.name) {
+        return Vec::new();
+    }
+    let mut seen = HashSet::from([root.id]);
+    while let Some(parent) = root.parent_branch_id.and_then(|id| by_id.get(&id))
+        && !bases.contains(&parent.name)
+        && seen.insert(parent.id)
+    {
+        root = parent;
+    }
+
+    let mut out = Vec::new();
+    let mut visited = HashSet::new();
+    let mut pending = vec![root];
+    while let Some(record) = pending.pop() {
+        if !visited.insert(record.id) {
+            continue;
+        }
+        out.push(record.name.clone());
+        let mut children: Vec<&BranchRecord> = records
+            .iter()
+            .filter(|r| r.parent_branch_id == Some(record.id) && !bases.contains(&r.name))
+            .collect();
+        children.sort_by(|a, b| b.name.cmp(&a.name));
+        pending.extend(children);
+    }
+    out
+}
+
+/// A draft/ready switch requested by the stack draft policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DraftChange {
+    pub branch: String,
+    pub pr_number: i64,
+    pub draft: bool,
+}
+
+/// Keeps every open PR with an open PR somewhere below it as a draft, and
+/// marks a draft ready once no open PR remains below it, whether its parent
+/// merged, closed or was spliced out onto base.
+pub fn draft_policy_changes(
+    records: &[BranchRecord],
+    pr_by_branch: &HashMap<String, PrInfo>,
+    bases: &StackBases,
+) -> Vec<DraftChange> {
+    let by_id: HashMap<i64, &BranchRecord> = records.iter().map(|r| (r.id, r)).collect();
+    let is_state = |branch: &str, wanted: fn(&PrState) -> bool| {
+        pr_by_branch.get(branch).is_some_and(|pr| wanted(&pr.state))
+    };
+
+    let mut changes = Vec::new();
+    for record in records {
+        if bases.contains(&record.name) {
+            continue;
+        }
+        let Some(pr) = pr_by_branch.get(&record.name) else {
+            continue;
+        };
+        if !matches!(pr.state, PrState::Open) {
+            continue;
+        }
+
+        let parent = record
+            .parent_branch_id
+            .and_then(|id| by_id.get(&id))
+            .filter(|parent| !bases.contains(&parent.name));
+        let mut blocked = false;
+        let mut seen = HashSet::from([record.id]);
+        let mut ancestor = parent;
+        while let Some(current) = ancestor
+            && seen.insert(current.id)
+        {
+            if is_state(&current.name, |s| matches!(s, PrState::Open)) {
+                blocked = true;
+                break;
+            }
+            ancestor = current
+                .parent_branch_id
+                .and_then(|id| by_id.get(&id))
+                .filter(|next| !bases.contains(&next.name));
+        }
+
+        let draft = if blocked && !pr.is_draft {
+            true
+        } else if !blocked && pr.is_draft {
+            false
+        } else {
+            continue;
+        };
+        changes.push(DraftChange {
+            branch: record.name.clone(),
+            pr_number: pr.number,
+            draft,
+        });
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pr(number: i64, state: PrState, is_draft: bool) -> PrInfo {
+        PrInfo {
+            number,
+            state,
+            merge_commit_oid: None,
+            base_ref_name: None,
+            body: None,
+            url: None,
+            title: None,
+            is_draft,
+        }
+    }
+
+    fn records() -> Vec<BranchRecord> {
+        vec![
+            BranchRecord::test(1, "main", None),
+            BranchRecord::test(2, "a", Some(1)),
+            BranchRecord::test(3, "b", Some(2)),
+            BranchRecord::test(4, "c", Some(3)),
+            BranchRecord::test(5, "d", Some(2)),
+            BranchRecord::test(6, "other", Some(1)),
+        ]
+    }
+
+    #[test]
+    fn stack_branch_names_walk_from_root_through_descendants() {
+        let records = records();
+        let bases = StackBases::new("main");
+        assert_eq!(
+            stack_branch_names(&records, &bases, "c"),
+            ["a", "b", "c", "d"]
+        );
+        assert_eq!(stack_branch_names(&records, &bases, "other"), ["other"]);
+        assert_eq!(stack_branch_names(&records, &bases, "loose"), ["loose"]);
+        assert!(stack_branch_names(&records, &bases, "main").is_empty());
+    }
+
+    #[test]
+    fn draft_policy_drafts_upper_prs_and_readies_once_nothing_below_is_open() {
+        let records = records();
+        let prs = HashMap::from([
+            ("a".to_string(), pr(1, PrState::Merged, false)),
+            ("b".to_string(), pr(2, PrState::Open, true)),
+            ("c".to_string(), pr(3, PrState::Open, false)),
+            ("d".to_string(), pr(4, PrState::Open, false)),
+            ("other".to_string(), pr(5, PrState::Open, true)),
+        ]);
+        let changes = draft_policy_changes(&records, &prs, &StackBases::new("main"));
+        assert_eq!(
+            changes,
+            [
+                DraftChange {
+                    branch: "b".to_string(),
+                    pr_number: 2,
+                    draft: false,
+                },
+                DraftChange {
+                    branch: "c".to_string(),
+                    pr_number: 3,
+                    draft: true,
+                },
+                DraftChange {
+                    branch: "other".to_string(),
+                    pr_number: 5,
+                    draft: false,
+                },
+            ]
+        );
+    }
+}
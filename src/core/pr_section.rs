@@ -0,0 +1,704 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::core::{StackBases, SyncPlanOptions, draft_policy_changes, order_status_updates};
+use crate::db::{BranchRecord, Database};
+use crate::git::Git;
+use crate::provider::{PrInfo, PrState, Provider};
+use crate::util::pr_body::{
+    ManagedBranchRef, ManagedSectionFormat, ManagedSectionPlacement, ManagedSectionStyle,
+    StackSectionEntry, format_pr_chain_node, is_managed_comment, managed_chain_line, managed_notes,
+    managed_pr_section, managed_stack_lines, managed_stack_section, merge_managed_pr_section,
+    strip_managed_pr_section, ticket_link, wrap_managed_section,
+};
+use crate::util::pr_title::PrTitlePrefix;
+use crate::util::template::{check_template, render_template};
+use crate::util::url::{escape_markdown_link_label, url_encode_branch_path};
+
+/// Variables available to `stack.prBodyTemplate`.
+pub const MANAGED_TEMPLATE_VARIABLES: &[&str] = &[
+    "branch",
+    "base",
+    "base_link",
+    "base_commit_url",
+    "parent",
+    "parent_branch",
+    "parent_pr",
+    "parent_state",
+    "parent_state_icon",
+    "children",
+    "first_child",
+    "stack_index",
+    "stack_size",
+    "chain",
+    "stack_list",
+    "stack_table",
+    "description",
+    "ticket",
+    "ticket_link",
+    "owner",
+];
+
+/// Checks a template for syntax errors and unknown variables.
+pub fn validate_managed_template(template: &str) -> Result<()> {
+    let vars = MANAGED_TEMPLATE_VARIABLES
+        .iter()
+        .map(|name| (*name, String::new()))
+        .collect::<HashMap<_, _>>();
+    check_template(template, &vars)
+}
+
+/// Builds the stack-managed PR body section for tracked branches from one
+/// snapshot of branch records and resolved PR metadata.
+pub struct ManagedSectionBuilder<'a> {
+    records: &'a [BranchRecord],
+    by_id: HashMap<i64, &'a BranchRecord>,
+    pr_by_branch: &'a HashMap<String, PrInfo>,
+    bases: &'a StackBases,
+    style: &'a ManagedSectionStyle,
+}
+
+impl<'a> ManagedSectionBuilder<'a> {
+    pub fn new(
+        records: &'a [BranchRecord],
+        pr_by_branch: &'a HashMap<String, PrInfo>,
+        bases: &'a StackBases,
+        style: &'a ManagedSectionStyle,
+    ) -> Self {
+        Self {
+            records,
+            by_id: records.iter().map(|r| (r.id, r)).collect(),
+            pr_by_branch,
+            bases,
+            style,
+        }
+    }
+
+    pub fn placement(&self) -> ManagedSectionPlacement {
+        self.style.placement
+    }
+
+    /// Renders the section for `branch`, which need not be tracked.
+    pub fn section_for(
+        &self,
+        branch: &str,
+        pr_root: &str,
+        base_commit_url: Option<&str>,
+    ) -> Result<String> {
+        let record = self.records.iter().find(|r| r.name == branch);
+        let parent = record
+            .and_then(|r| r.parent_branch_id)
+            .and_then(|id| self.by_id.get(&id).copied());
+        let children = record.map(|r| self.children_of(r.id)).unwrap_or_default();
+        let base = self.bases.base_for(self.records, branch);
+
+        if let Some(template) = self.style.template.as_deref() {
+            let vars =
+                self.template_vars(branch, record, parent, &children, pr_root, base_commit_url);
+            let rendered = render_template(template, &vars)
+                .context("failed to render stack.prBodyTemplate")?;
+            return Ok(wrap_managed_section(&rendered));
+        }
+
+        let ticket_link = record.and_then(|r| self.ticket_link(r));
+        let notes = record.and_then(|r| {
+            managed_notes(
+                r.description.as_deref(),
+                ticket_link.as_deref(),
+                r.owner.as_deref(),
+            )
+        });
+        Ok(match self.style.format {
+            ManagedSectionFormat::Chain => managed_pr_section(
+                pr_root,
+                base,
+                base_commit_url,
+                parent.map(|p| self.branch_ref(p)).as_ref(),
+                children.first().map(|c| self.branch_ref(c)).as_ref(),
+                notes.as_deref(),
+            ),
+            ManagedSectionFormat::List | ManagedSectionFormat::Table => managed_stack_section(
+                pr_root,
+                base,
+                base_commit_url,
+                &self.stack_entries(branch, record),
+                self.style.format,
+                notes.as_deref(),
+            ),
+        })
+    }
+
+    /// 1-based position of `branch` on its line of the stack, with the length
+    /// of that line. See [`line_position`].
+    pub fn stack_position(&self, branch: &str) -> Option<(usize, usize)> {
+        let record = self.records.iter().find(|r| r.name == branch)?;
+        line_position(&self.stack_entries(branch, Some(record)))
+    }
+
+    /// The prefixed title for `pr`, or `None` when it is already current or
+    /// the title is unknown.
+    pub fn prefixed_title(
+        &self,
+        branch: &str,
+        pr: &PrInfo,
+        prefix: &PrTitlePrefix,
+    ) -> Option<String> {
+        let title = pr.title.as_deref()?;
+        let (index, total) = self.stack_position(branch)?;
+        let prefixed = prefix.apply(title, index, total);
+        (prefixed != title).then_some(prefixed)
+    }
+
+    fn template_vars(
+        &self,
+        branch: &str,
+        record: Option<&BranchRecord>,
+        parent: Option<&BranchRecord>,
+        children: &[&BranchRecord],
+        pr_root: &str,
+        base_commit_url: Option<&str>,
+    ) -> HashMap<&'static str, String> {
+        let root = pr_root.trim_end_matches('/');
+        let base = self.bases.base_for(self.records, branch);
+        let base_link = format!(
+            "[{}]({})",
+            escape_markdown_link_label(base),
+            base_commit_url
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("{root}/tree/{}", url_encode_branch_path(base)))
+        );
+        let parent_ref = parent.map(|p| self.branch_ref(p));
+        let parent_pr = parent.and_then(|p| self.pr_by_branch.get(&p.name));
+        let child_refs = children
+            .iter()
+            .map(|c| self.branch_ref(c))
+            .collect::<Vec<_>>();
+        let entries = self.stack_entries(branch, record);
+        let (stack_index, stack_size) = line_position(&entries).unwrap_or_default();
+
+        HashMap::from([
+            ("branch", branch.to_string()),
+            ("base", base.to_string()),
+            ("base_link", base_link),
+            (
+                "base_commit_url",
+                base_commit_url.unwrap_or_default().to_string(),
+            ),
+            (
+                "parent",
+                parent_ref
+                    .as_ref()
+                    .map(|p| format_pr_chain_node(root, p))
+                    .unwrap_or_default(),
+            ),
+            (
+                "parent_branch",
+                parent.map(|p| p.name.clone()).unwrap_or_default(),
+            ),
+            (
+                "parent_pr",
+                parent_pr
+                    .map(|p| format!("#{}", p.number))
+                    .unwrap_or_default(),
+            ),
+            (
+                "parent_state",
+                parent_pr
+                    .map(|p| p.state.as_str().to_string())
+                    .unwrap_or_default(),
+            ),
+            (
+                "parent_state_icon",
+                parent
+                    .map(|_| state_icon(parent_pr.map(|p| &p.state)).to_string())
+                    .unwrap_or_default(),
+            ),
+            (
+                "children",
+                child_refs
+                    .iter()
+                    .map(|c| format_pr_chain_node(root, c))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            (
+                "first_child",
+                child_refs
+                    .first()
+                    .map(|c| format_pr_chain_node(root, c))
+                    .unwrap_or_default(),
+            ),
+            ("stack_index", stack_index.to_string()),
+            ("stack_size", stack_size.to_string()),
+            (
+                "chain",
+                managed_chain_line(
+                    root,
+                    base,
+                    base_commit_url,
+                    parent_ref.as_ref(),
+                    child_refs.first(),
+                ),
+            ),
+            (
+                "stack_list",
+                managed_stack_lines(
+                    root,
+                    base,
+                    base_commit_url,
+                    &entries,
+                    ManagedSectionFormat::List,
+                ),
+            ),
+            (
+                "stack_table",
+                managed_stack_lines(
+                    root,
+                    base,
+                    base_commit_url,
+                    &entries,
+                    ManagedSectionFormat::Table,
+                ),
+            ),
+            (
+                "description",
+                record
+                    .and_then(|r| r.description.clone())
+                    .unwrap_or_default(),
+            ),
+            (
+                "ticket",
+                record.and_then(|r| r.ticket.clone()).unwrap_or_default(),
+            ),
+            (
+                "ticket_link",
+                record.and_then(|r| self.ticket_link(r)).unwrap_or_default(),
+            ),
+            (
+                "owner",
+                record.and_then(|r| r.owner.clone()).unwrap_or_default(),
+            ),
+        ])
+    }
+
+    fn ticket_link(&self, record: &BranchRecord) -> Option<String> {
+        record
+            .ticket
+            .as_deref()
+            .map(|ticket| ticket_link(ticket, self.style.ticket_url.as_deref()))
+    }
+
+    fn branch_ref(&self, record: &BranchRecord) -> ManagedBranchRef {
+        let pr = self.pr_by_branch.get(&record.name);
+        ManagedBranchRef {
+            branch: record.name.clone(),
+            pr_number: pr.map(|p| p.number),
+            pr_url: pr.and_then(|p| p.url.clone()),
+        }
+    }
+
+    fn children_of(&self, id: i64) -> Vec<&'a BranchRecord> {
+        let mut children: Vec<&BranchRecord> = self
+            .records
+            .iter()
+            .filter(|r| r.parent_branch_id == Some(id) && !self.bases.contains(&r.name))
+            .collect();
+        children.sort_by(|a, b| a.name.cmp(&b.name));
+        children
+    }
+
+    fn stack_entries(&self, branch: &str, record: Option<&BranchRecord>) -> Vec<StackSectionEntry> {
+        let Some(current) = record else {
+            let pr = self.pr_by_branch.get(branch);
+            return vec![StackSectionEntry {
+                branch: ManagedBranchRef {
+                    branch: branch.to_string(),
+                    pr_number: pr.map(|p| p.number),
+                    pr_url: pr.and_then(|p| p.url.clone()),
+                },
+                depth: 0,
+                title: pr.and_then(|p| p.title.clone()),
+                state: pr.map(|p| p.state.as_str().to_string()),
+                current: true,
+            }];
+        };
+
+        let mut root = current;
+        let mut seen = HashSet::from([root.id]);
+        while let Some(parent) = root.parent_branch_id.and_then(|id| self.by_id.get(&id))
+            && !self.bases.contains(&parent.name)
+            && seen.insert(parent.id)
+        {
+            root = parent;
+        }
+
+        let mut entries = Vec::new();
+        let mut visited = HashSet::new();
+        let mut stack = vec![(root, 0usize)];
+        while let Some((record, depth)) = stack.pop() {
+            if !visited.insert(record.id) {
+                continue;
+            }
+            let pr = self.pr_by_branch.get(&record.name);
+            entries.push(StackSectionEntry {
+                branch: self.branch_ref(record),
+                depth,
+                title: pr.and_then(|p| p.title.clone()),
+                state: pr.map(|p| p.state.as_str().to_string()),
+                current: record.id == current.id,
+            });
+            for child in self.children_of(record.id).into_iter().rev() {
+                stack.push((child, depth + 1));
+            }
+        }
+        entries
+    }
+}
+
+/// A change needed to publish the managed section on one PR.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManagedSectionUpdate {
+    Body(String),
+    /// Creates the managed comment when `comment_id` is `None`.
+    Comment {
+        comment_id: Option<i64>,
+        body: String,
+    },
+}
+
+/// Works out which body/comment edits bring `pr` up to date with `section`.
+///
+/// In comment mode any section left in the body is stripped, and failures to
+/// list comments are reported as warnings so the rest of the stack proceeds.
+/// Offline, comments are not looked up at all.
+pub fn managed_section_updates(
+    provider: &dyn Provider,
+    pr: &PrInfo,
+    section: &str,
+    placement: ManagedSectionPlacement,
+    offline: bool,
+) -> Vec<ManagedSectionUpdate> {
+    let mut updates = Vec::new();
+    match placement {
+        ManagedSectionPlacement::Body => {
+            let merged = merge_managed_pr_section(pr.body.as_deref(), section);
+            if pr.body.as_deref().map(str::trim) != Some(merged.trim()) {
+                updates.push(ManagedSectionUpdate::Body(merged));
+            }
+        }
+        ManagedSectionPlacement::Comment => {
+            if let Some(stripped) = pr.body.as_deref().and_then(strip_managed_pr_section) {
+                updates.push(ManagedSectionUpdate::Body(stripped));
+            }
+            if offline {
+                return updates;
+            }
+            let comments = match provider.list_pr_comments(pr.number) {
+                Ok(comments) => comments,
+                Err(err) => {
+                    eprintln!(
+                        "warning: could not load comments for PR #{}: {err}",
+                        pr.number
+                    );
+                    return updates;
+                }
+            };
+            match comments.iter().find(|c| is_managed_comment(&c.body)) {
+                Some(comment) if comment.body.trim() == section.trim() => {}
+                existing => updates.push(ManagedSectionUpdate::Comment {
+                    comment_id: existing.map(|c| c.id),
+                    body: section.to_string(),
+                }),
+            }
+        }
+    }
+    updates
+}
+
+/// Immediately re-publishes the managed section, and the title prefix, draft
+/// policy and order status when configured, on the open PRs of `branches`.
+/// Frozen branches are skipped.
+pub fn refresh_managed_prs(
+    db: &Database,
+    git: &Git,
+    provider: &dyn Provider,
+    bases: &StackBases,
+    options: &SyncPlanOptions,
+    branches: &[String],
+) -> Result<()> {
+    let tracked = db.list_branches()?;
+    let branch_exists: HashMap<String, bool> = tracked
+        .iter()
+        .map(|b| Ok((b.name.clone(), git.branch_exists(&b.name)?)))
+        .collect::<Result<HashMap<_, _>>>()?;
+    let metadata_targets: Vec<(&str, Option<i64>)> = tracked
+        .iter()
+        .filter(|branch| !bases.contains(&branch.name))
+        .filter(|branch| branch_exists.get(&branch.name).copied().unwrap_or(false))
+        .map(|branch| (branch.name.as_str(), branch.cached_pr_number))
+        .collect();
+    let pr_by_branch = provider.resolve_prs_by_head(&metadata_targets)?;
+
+    let fallback_base_url = git
+        .pr_repo_web_url(
+            git.remote_settings()
+                .base_remote
+                .as_deref()
+                .unwrap_or("origin"),
+        )?
+        .or_else(|| git.remote_web_url("upstream").ok().flatten());
+    let section_builder =
+        ManagedSectionBuilder::new(&tracked, &pr_by_branch, bases, &options.body_style);
+
+    let draft_changes = if options.draft_policy {
+        draft_policy_changes(&tracked, &pr_by_branch, bases)
+    } else {
+        Vec::new()
+    };
+
+    let mut unique_targets = HashSet::new();
+    for branch_name in branches {
+        if !unique_targets.insert(branch_name.clone()) {
+            continue;
+        }
+        let Some(record) = tracked.iter().find(|r| r.name == *branch_name && !r.frozen) else {
+            continue;
+        };
+        let Some(pr) = pr_by_branch.get(branch_name) else {
+            continue;
+        };
+        if !matches!(pr.state, PrState::Open) {
+            continue;
+        }
+
+        let pr_root = pr
+            .url
+            .as_deref()
+            .and_then(repo_root_from_pr_url)
+            .or(fallback_base_url.as_deref())
+            .ok_or_else(|| anyhow!("could not determine PR repository URL for '{branch_name}'"))?;
+        let base_commit_url = git
+            .merge_base(branch_name, bases.base_for(&tracked, branch_name))
+            .ok()
+            .map(|sha| format!("{}/commit/{sha}", pr_root.trim_end_matches('/')));
+        let managed =
+            section_builder.section_for(&record.name, pr_root, base_commit_url.as_deref())?;
+        for update in managed_section_updates(
+            provider,
+            pr,
+            &managed,
+            options.body_style.placement,
+            options.offline,
+        ) {
+            match update {
+                ManagedSectionUpdate::Body(body) => provider.update_pr_body(pr.number, &body)?,
+                ManagedSectionUpdate::Comment {
+                    comment_id: Some(id),
+                    body,
+                } => provider.update_pr_comment(id, &body)?,
+                ManagedSectionUpdate::Comment {
+                    comment_id: None,
+                    body,
+                } => provider.create_pr_comment(pr.number, &body)?,
+            }
+        }
+        if let Some(prefix) = &options.title_prefix
+            && let Some(title) = section_builder.prefixed_title(&record.name, pr, prefix)
+        {
+            provider.update_pr_title(pr.number, &title)?;
+        }
+        if let Some(change) = draft_changes.iter().find(|c| c.branch == *branch_name) {
+            provider.set_pr_draft(change.pr_number, change.draft)?;
+        }
+        if options.order_status {
+            let targets = [branch_name.as_str()];
+            for update in order_status_updates(provider, &tracked, &pr_by_branch, bases, &targets) {
+                provider.set_pr_head_status(update.pr_number, &update.status)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 1-based position of the current entry among the branches that merge
+/// with it: its ancestors and descendants with an open PR, plus itself even
+/// before its PR exists. Siblings and branches without open PRs are not
+/// counted. `entries` must be in [`ManagedSectionBuilder::stack_entries`]
+/// order.
+fn line_position(entries: &[StackSectionEntry]) -> Option<(usize, usize)> {
+    let current = entries.iter().position(|e| e.current)?;
+    let depth = entries[current].depth;
+    let mut ancestors = Vec::new();
+    let mut wanted = depth;
+    for entry in entries[..current].iter().rev() {
+        if entry.depth < wanted {
+            ancestors.push(entry);
+            wanted = entry.depth;
+        }
+    }
+    let descendants = entries[current + 1..]
+        .iter()
+        .take_while(|e| e.depth > depth);
+    let is_open = |e: &&StackSectionEntry| e.state.as_deref() == Some("open");
+    let above = ancestors.into_iter().filter(is_open).count();
+    let below = descendants.filter(is_open).count();
+    Some((above + 1, above + 1 + below))
+}
+
+pub(crate) fn repo_root_from_pr_url(url: &str) -> Option<&str> {
+    url.split_once("/pull/").map(|(root, _)| root)
+}
+
+fn state_icon(state: Option<&PrState>) -> &'static str {
+    match state {
+        Some(PrState::Open) => "🟢",
+        Some(PrState::Merged) => "🟣",
+        Some(PrState::Closed) => "🔴",
+        Some(PrState::Unknown) | None => "⚪",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pr(number: i64, state: PrState, title: &str) -> PrInfo {
+        PrInfo {
+            number,
+            state,
+            merge_commit_oid: None,
+            base_ref_name: None,
+            body: None,
+            url: None,
+            title: Some(title.to_string()),
+            is_draft: false,
+        }
+    }
+
+    fn records() -> Vec<BranchRecord> {
+        vec![
+            BranchRecord::test(1, "main", None),
+            BranchRecord::test(2, "feat/a", Some(1)),
+            BranchRecord::test(3, "feat/c", Some(2)),
+            BranchRecord::test(4, "feat/b", Some(2)),
+            BranchRecord::test(5, "feat/b2", Some(4)),
+            BranchRecord::test(6, "other", Some(1)),
+        ]
+    }
+
+    #[test]
+    fn stack_entries_cover_whole_tree_from_root_in_name_order() {
+        let records = records();
+        let prs = HashMap::from([("feat/b".to_string(), pr(13, PrState::Open, "Add b"))]);
+        let style = ManagedSectionStyle {
+            format: ManagedSectionFormat::List,
+            ..Default::default()
+        };
+        let bases = StackBases::new("main");
+        let builder = ManagedSectionBuilder::new(&records, &prs, &bases, &style);
+        let entries = builder.stack_entries("feat/b2", Some(&records[4]));
+        let names = entries
+            .iter()
+            .map(|e| (e.branch.branch.as_str(), e.depth, e.current))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                ("feat/a", 0, false),
+                ("feat/b", 1, false),
+                ("feat/b2", 2, true),
+                ("feat/c", 1, false),
+            ]
+        );
+        assert_eq!(entries[1].title.as_deref(), Some("Add b"));
+        assert_eq!(entries[1].state.as_deref(), Some("open"));
+    }
+
+    #[test]
+    fn template_renders_parent_position_and_base_variables() {
+        let records = records();
+        let prs = HashMap::from([
+            ("feat/a".to_string(), pr(12, PrState::Open, "Add a")),
+            ("feat/b".to_string(), pr(13, PrState::Merged, "Add b")),
+            ("feat/c".to_string(), pr(14, PrState::Open, "Add c")),
+        ]);
+        let style = ManagedSectionStyle {
+            format: ManagedSectionFormat::Chain,
+            template: Some(
+                "### Stack {{stack_index}}/{{stack_size}} into {{base_link}}\n{{#parent_pr}}{{parent_state_icon}} merge after {{parent_pr}}{{/parent_pr}}{{^children}}last in stack{{/children}}"
+                    .to_string(),
+            ),
+            ..Default::default()
+        };
+        let bases = StackBases::new("main");
+        let builder = ManagedSectionBuilder::new(&records, &prs, &bases, &style);
+        let section = builder
+            .section_for(
+                "feat/b2",
+                "https://github.com/acme/repo",
+                Some("https://github.com/acme/repo/commit/abc"),
+            )
+            .unwrap();
+        assert_eq!(
+            section,
+            wrap_managed_section(
+                "### Stack 2/2 into [main](https://github.com/acme/repo/commit/abc)\n🟣 merge after #13last in stack"
+            )
+        );
+    }
+
+    #[test]
+    fn stack_position_counts_open_prs_on_the_branch_line_only() {
+        let records = records();
+        let prs = HashMap::from([
+            ("feat/a".to_string(), pr(12, PrState::Open, "Add a")),
+            ("feat/c".to_string(), pr(14, PrState::Open, "Add c")),
+            ("feat/b2".to_string(), pr(15, PrState::Open, "Add b2")),
+        ]);
+        let style = ManagedSectionStyle::default();
+        let bases = StackBases::new("main");
+        let builder = ManagedSectionBuilder::new(&records, &prs, &bases, &style);
+        // feat/b has no PR and feat/c is a sibling line.
+        assert_eq!(builder.stack_position("feat/b2"), Some((2, 2)));
+        assert_eq!(builder.stack_position("feat/a"), Some((1, 3)));
+        assert_eq!(builder.stack_position("feat/c"), Some((2, 2)));
+    }
+
+    #[test]
+    fn built_in_section_lists_annotations_with_linked_ticket() {
+        let mut records = records();
+        records[1].description = Some("Parser rewrite".to_string());
+        records[1].ticket = Some("ABC-123".to_string());
+        records[1].owner = Some("@alice".to_string());
+        let prs = HashMap::new();
+        let style = ManagedSectionStyle {
+            ticket_url: Some("https://issues.example.com/browse/{ticket}".to_string()),
+            ..Default::default()
+        };
+        let bases = StackBases::new("main");
+        let builder = ManagedSectionBuilder::new(&records, &prs, &bases, &style);
+        let section = builder
+            .section_for("feat/a", "https://github.com/acme/repo", None)
+            .unwrap();
+        assert!(section.contains(
+            "Parser rewrite\n\n**Ticket:** [ABC-123](https://issues.example.com/browse/ABC-123) · **Owner:** @alice\n<hr />"
+        ));
+
+        let plain = builder
+            .section_for("feat/c", "https://github.com/acme/repo", None)
+            .unwrap();
+        assert!(!plain.contains("**Ticket:**"));
+    }
+
+    #[test]
+    fn validate_managed_template_flags_unknown_variables() {
+        assert!(validate_managed_template("{{chain}} {{#parent_pr}}x{{/parent_pr}}").is_ok());
+        let err = validate_managed_template("{{parent_title}}").unwrap_err();
+        assert!(err.to_string().contains("parent_title"));
+        let err = validate_managed_template("{{#parent_pr}}after {{parent_titel}}{{/parent_pr}}")
+            .unwrap_err();
+        assert!(err.to_string().contains("parent_titel"));
+    }
+}
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 
 use crossterm::style::Stylize;
 
+use crate::core::StackBases;
 use crate::db::BranchRecord;
 use crate::util::url::{
     escape_markdown_link_label, url_encode_branch_path, url_encode_compare_ref,
@@ -18,7 +19,7 @@ pub fn render_tree(
     branches: &[BranchRecord],
     color: bool,
     pr_base_url: Option<&str>,
-    default_base_branch: &str,
+    bases: &StackBases,
     link_targets: Option<&HashMap<String, BranchLinkTarget>>,
 ) -> String {
     let mut out = String::new();
@@ -37,7 +38,7 @@ pub fn render_tree(
         by_id: &'a HashMap<i64, &'a BranchRecord>,
         color: bool,
         pr_base_url: Option<&'a str>,
-        default_base_branch: &'a str,
+        bases: &'a StackBases,
         link_targets: Option<&'a HashMap<String, BranchLinkTarget>>,
     }
 
@@ -68,7 +69,12 @@ pub fn render_tree(
                     parent_name,
                     &child_names,
                     &node.name,
-                    ctx.default_base_branch,
+                    // Base branches have no PR of their own.
+                    if ctx.bases.contains(&node.name) {
+                        &node.name
+                    } else {
+                        ctx.bases.default_branch()
+                    },
                     ctx.color,
                 );
                 let mut line = format!("{prefix}{connector} {branch_name}");
@@ -78,6 +84,15 @@ pub fn render_tree(
                 }
                 line.push(' ');
                 line.push_str(&sync);
+                if node.frozen {
+                    line.push(' ');
+                    line.push_str(&if ctx.color {
+                        format!("[{}]", "FROZEN".blue().bold())
+                    } else {
+                        "[FROZEN]".to_string()
+                    });
+                }
+                line.push_str(&render_annotations(node, ctx.color));
                 line.push_str(&pr_link);
                 out.push_str(&line);
                 out.push('\n');
@@ -96,7 +111,7 @@ pub fn render_tree(
         by_id: &by_id,
         color,
         pr_base_url,
-        default_base_branch,
+        bases,
         link_targets,
     };
     walk(&mut out, None, "", &ctx);
@@ -142,6 +157,38 @@ fn render_sync_state(has_sha: bool, color: bool) -> String {
     }
 }
 
+/// ` [TICKET] @owner — description`, skipping unset fields.
+fn render_annotations(node: &BranchRecord, color: bool) -> String {
+    let mut out = String::new();
+    if let Some(ticket) = &node.ticket {
+        let badge = format!("[{ticket}]");
+        out.push(' ');
+        out.push_str(&if color {
+            badge.blue().to_string()
+        } else {
+            badge
+        });
+    }
+    if let Some(owner) = &node.owner {
+        let owner = format!("@{}", owner.trim_start_matches('@'));
+        out.push(' ');
+        out.push_str(&if color {
+            owner.dark_grey().to_string()
+        } else {
+            owner
+        });
+    }
+    if let Some(description) = &node.description {
+        out.push_str(" — ");
+        out.push_str(&if color {
+            description.as_str().italic().to_string()
+        } else {
+            description.clone()
+        });
+    }
+    out
+}
+
 fn render_pr_link(
     pr_base_url: Option<&str>,
     link_target: Option<&BranchLinkTarget>,
@@ -257,6 +304,10 @@ mod tests {
                 last_synced_head_sha: Some("abc".to_string()),
                 cached_pr_number: None,
                 cached_pr_state: Some("open".to_string()),
+                description: None,
+                ticket: None,
+                owner: None,
+                frozen: false,
             },
             BranchRecord {
                 id: 2,
@@ -265,10 +316,14 @@ mod tests {
                 last_synced_head_sha: None,
                 cached_pr_number: None,
                 cached_pr_state: Some("merged".to_string()),
+                description: None,
+                ticket: None,
+                owner: None,
+                frozen: false,
             },
         ];
 
-        let rendered = render_tree(&branches, false, None, "main", None);
+        let rendered = render_tree(&branches, false, None, &StackBases::new("main"), None);
         assert!(rendered.contains("└── feat/a"));
         assert!(rendered.contains("[PR:open]"));
         assert!(rendered.contains("[SYNC:never]"));
@@ -283,12 +338,50 @@ mod tests {
             last_synced_head_sha: Some("abc".to_string()),
             cached_pr_number: None,
             cached_pr_state: Some("open".to_string()),
+            description: None,
+            ticket: None,
+            owner: None,
+            frozen: false,
         }];
 
-        let rendered = render_tree(&branches, true, None, "main", None);
+        let rendered = render_tree(&branches, true, None, &StackBases::new("main"), None);
         assert!(rendered.contains("\u{1b}["));
     }
 
+    #[test]
+    fn render_tree_shows_annotations_after_badges() {
+        let branches = vec![
+            BranchRecord {
+                id: 1,
+                name: "main".to_string(),
+                parent_branch_id: None,
+                last_synced_head_sha: None,
+                cached_pr_number: None,
+                cached_pr_state: None,
+                description: None,
+                ticket: None,
+                owner: None,
+                frozen: false,
+            },
+            BranchRecord {
+                id: 2,
+                name: "feat/a".to_string(),
+                parent_branch_id: Some(1),
+                last_synced_head_sha: None,
+                cached_pr_number: None,
+                cached_pr_state: None,
+                description: Some("parser rewrite".to_string()),
+                ticket: Some("ABC-123".to_string()),
+                owner: Some("@alice".to_string()),
+                frozen: false,
+            },
+        ];
+
+        let rendered = render_tree(&branches, false, None, &StackBases::new("main"), None);
+        assert!(rendered.contains("└── feat/a [SYNC:never] [ABC-123] @alice — parser rewrite"));
+        assert!(rendered.contains("── main [SYNC:never]\n"));
+    }
+
     #[test]
     fn render_tree_includes_pr_link_when_repo_url_known() {
         let branches = vec![BranchRecord {
@@ -298,13 +391,17 @@ mod tests {
             last_synced_head_sha: Some("abc".to_string()),
             cached_pr_number: Some(42),
             cached_pr_state: Some("open".to_string()),
+            description: None,
+            ticket: None,
+            owner: None,
+            frozen: false,
         }];
 
         let rendered = render_tree(
             &branches,
             false,
             Some("https://github.com/acme/repo"),
-            "main",
+            &StackBases::new("main"),
             None,
         );
         assert!(rendered.contains("https://github.com/acme/repo/pull/42"));
@@ -319,6 +416,10 @@ mod tests {
             last_synced_head_sha: Some("abc".to_string()),
             cached_pr_number: Some(42),
             cached_pr_state: Some("open".to_string()),
+            description: None,
+            ticket: None,
+            owner: None,
+            frozen: false,
         }];
         let mut link_targets = HashMap::new();
         link_targets.insert(
@@ -333,7 +434,7 @@ mod tests {
             &branches,
             false,
             Some("https://github.com/fork/repo"),
-            "main",
+            &StackBases::new("main"),
             Some(&link_targets),
         );
         assert!(rendered.contains("https://github.com/upstream/repo/pull/42"));
@@ -349,13 +450,17 @@ mod tests {
             last_synced_head_sha: None,
             cached_pr_number: Some(123),
             cached_pr_state: Some("open".to_string()),
+            description: None,
+            ticket: None,
+            owner: None,
+            frozen: false,
         }];
 
         let rendered = render_tree(
             &branches,
             true,
             Some("https://github.com/acme/repo"),
-            "main",
+            &StackBases::new("main"),
             None,
         );
         assert!(rendered.contains("\u{1b}]8;;https://github.com/acme/repo/pull/123\u{1b}\\"));
@@ -371,13 +476,17 @@ mod tests {
             last_synced_head_sha: None,
             cached_pr_number: None,
             cached_pr_state: Some("none".to_string()),
+            description: None,
+            ticket: None,
+            owner: None,
+            frozen: false,
         }];
 
         let rendered = render_tree(
             &branches,
             true,
             Some("https://github.com/acme/repo"),
-            "main",
+            &StackBases::new("main"),
             None,
         );
         assert!(rendered.contains(
@@ -395,13 +504,17 @@ mod tests {
             last_synced_head_sha: Some("abc".to_string()),
             cached_pr_number: None,
             cached_pr_state: None,
+            description: None,
+            ticket: None,
+            owner: None,
+            frozen: false,
         }];
 
         let rendered = render_tree(
             &branches,
             false,
             Some("https://github.com/acme/repo"),
-            "main",
+            &StackBases::new("main"),
             None,
         );
         assert!(!rendered.contains("[PR:none]"));
@@ -419,13 +532,17 @@ mod tests {
             last_synced_head_sha: Some("abc".to_string()),
             cached_pr_number: None,
             cached_pr_state: None,
+            description: None,
+            ticket: None,
+            owner: None,
+            frozen: false,
         }];
 
         let rendered = render_tree(
             &branches,
             false,
             Some("https://github.com/acme/repo"),
-            "main",
+            &StackBases::new("main"),
             None,
         );
         assert!(rendered.contains("[no PR (same base/head)]"));
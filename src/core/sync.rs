@@ -1,11 +1,18 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::time::Instant;
 
 use anyhow::{Result, anyhow};
 
-use crate::db::{BranchRecord, Database};
+use crate::core::pr_section::repo_root_from_pr_url;
+use crate::core::{
+    ManagedSectionBuilder, ManagedSectionUpdate, StackBases, draft_policy_changes,
+    managed_section_updates, order_status_updates,
+};
+use crate::db::{BranchRecord, Database, SyncRunOpRecord};
 use crate::git::{Git, StashHandle};
-use crate::provider::{PrState, Provider};
-use crate::util::pr_body::{ManagedBranchRef, managed_pr_section, merge_managed_pr_section};
+use crate::provider::{CommitStatus, PrState, Provider};
+use crate::util::pr_body::ManagedSectionStyle;
+use crate::util::pr_title::PrTitlePrefix;
 use crate::views::{OperationView, SyncPlanView};
 
 #[derive(Debug, Clone)]
@@ -23,6 +30,10 @@ pub enum SyncOp {
         old_base: Option<String>,
         reason: String,
     },
+    FastForward {
+        branch: String,
+        onto: String,
+    },
     UpdateSha {
         branch: String,
         sha: String,
@@ -32,6 +43,84 @@ pub enum SyncOp {
         pr_number: i64,
         body: String,
     },
+    UpdatePrComment {
+        branch: String,
+        pr_number: i64,
+        comment_id: Option<i64>,
+        body: String,
+    },
+    UpdatePrTitle {
+        branch: String,
+        pr_number: i64,
+        title: String,
+    },
+    SetPrDraft {
+        branch: String,
+        pr_number: i64,
+        draft: bool,
+    },
+    SetOrderStatus {
+        branch: String,
+        pr_number: i64,
+        status: CommitStatus,
+    },
+    /// An op left out of the plan, kept so the plan view can explain why.
+    Skip {
+        branch: String,
+        kind: String,
+        reason: String,
+    },
+}
+
+impl SyncOp {
+    /// The branch the op acts on; `None` for fetches.
+    pub fn branch(&self) -> Option<&str> {
+        match self {
+            SyncOp::Fetch { .. } => None,
+            SyncOp::UpdateBaseToMergeCommit { branch, .. }
+            | SyncOp::Restack { branch, .. }
+            | SyncOp::FastForward { branch, .. }
+            | SyncOp::UpdateSha { branch, .. }
+            | SyncOp::UpdatePrBody { branch, .. }
+            | SyncOp::UpdatePrComment { branch, .. }
+            | SyncOp::UpdatePrTitle { branch, .. }
+            | SyncOp::SetPrDraft { branch, .. }
+            | SyncOp::SetOrderStatus { branch, .. }
+            | SyncOp::Skip { branch, .. } => Some(branch),
+        }
+    }
+
+    /// Whether the op edits a PR rather than a branch or the database.
+    pub fn edits_pr(&self) -> bool {
+        matches!(
+            self,
+            SyncOp::UpdatePrBody { .. }
+                | SyncOp::UpdatePrComment { .. }
+                | SyncOp::UpdatePrTitle { .. }
+                | SyncOp::SetPrDraft { .. }
+                | SyncOp::SetOrderStatus { .. }
+        )
+    }
+
+    /// The branch and view kind of ops that rewrite a branch or edit its PR.
+    fn rewrite_target(&self) -> Option<(&str, &'static str)> {
+        match self {
+            SyncOp::Restack { branch, .. } => Some((branch, "restack")),
+            SyncOp::UpdatePrBody { branch, .. } => Some((branch, "update_pr_body")),
+            SyncOp::UpdatePrComment { branch, .. } => Some((branch, "update_pr_comment")),
+            SyncOp::UpdatePrTitle { branch, .. } => Some((branch, "update_pr_title")),
+            SyncOp::SetPrDraft { branch, draft, .. } => Some((
+                branch,
+                if *draft {
+                    "mark_pr_draft"
+                } else {
+                    "mark_pr_ready"
+                },
+            )),
+            SyncOp::SetOrderStatus { branch, .. } => Some((branch, "set_order_status")),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -71,6 +160,12 @@ impl SyncPlan {
                     onto: Some(onto.clone()),
                     details: format!("onto {onto}: {reason}"),
                 }),
+                SyncOp::FastForward { branch, onto } => operations.push(OperationView {
+                    kind: "fast_forward".to_string(),
+                    branch: branch.clone(),
+                    onto: Some(onto.clone()),
+                    details: format!("ff-only to {onto} if it moved (frozen)"),
+                }),
                 SyncOp::UpdateSha { branch, sha } => operations.push(OperationView {
                     kind: "update_sha".to_string(),
                     branch: branch.clone(),
@@ -85,6 +180,73 @@ impl SyncPlan {
                     onto: None,
                     details: format!("pr #{pr_number}"),
                 }),
+                SyncOp::UpdatePrComment {
+                    branch,
+                    pr_number,
+                    comment_id,
+                    ..
+                } => operations.push(OperationView {
+                    kind: "update_pr_comment".to_string(),
+                    branch: branch.clone(),
+                    onto: None,
+                    details: match comment_id {
+                        Some(id) => format!("pr #{pr_number} comment {id}"),
+                        None => format!("pr #{pr_number} new comment"),
+                    },
+                }),
+                SyncOp::UpdatePrTitle {
+                    branch,
+                    pr_number,
+                    title,
+                } => operations.push(OperationView {
+                    kind: "update_pr_title".to_string(),
+                    branch: branch.clone(),
+                    onto: None,
+                    details: format!("pr #{pr_number}: {title}"),
+                }),
+                SyncOp::SetPrDraft {
+                    branch,
+                    pr_number,
+                    draft,
+                } => operations.push(OperationView {
+                    kind: if *draft {
+                        "mark_pr_draft"
+                    } else {
+                        "mark_pr_ready"
+                    }
+                    .to_string(),
+                    branch: branch.clone(),
+                    onto: None,
+                    details: if *draft {
+                        format!("pr #{pr_number}: an open PR below it is not merged yet")
+                    } else {
+                        format!("pr #{pr_number}: parent PR merged")
+                    },
+                }),
+                SyncOp::SetOrderStatus {
+                    branch,
+                    pr_number,
+                    status,
+                } => operations.push(OperationView {
+                    kind: "set_order_status".to_string(),
+                    branch: branch.clone(),
+                    onto: None,
+                    details: format!(
+                        "pr #{pr_number} {}: {}",
+                        status.state.as_str(),
+                        status.description
+                    ),
+                }),
+                SyncOp::Skip {
+                    branch,
+                    kind,
+                    reason,
+                } => operations.push(OperationView {
+                    kind: "skip".to_string(),
+                    branch: branch.clone(),
+                    onto: None,
+                    details: format!("{kind} skipped: {reason}"),
+                }),
             }
         }
         SyncPlanView {
@@ -94,12 +256,23 @@ impl SyncPlan {
     }
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct SyncPlanOptions {
+    pub body_style: ManagedSectionStyle,
+    pub title_prefix: Option<PrTitlePrefix>,
+    pub draft_policy: bool,
+    pub order_status: bool,
+    /// Plans PR edits as skips, since the provider cannot be reached.
+    pub offline: bool,
+}
+
 pub fn build_sync_plan(
     db: &Database,
     git: &Git,
     provider: &dyn Provider,
-    base_branch: &str,
+    bases: &StackBases,
     base_remote: &str,
+    options: &SyncPlanOptions,
 ) -> Result<SyncPlan> {
     #[derive(Clone)]
     struct RestackCandidate {
@@ -116,7 +289,7 @@ pub fn build_sync_plan(
     }
     let metadata_targets: Vec<(&str, Option<i64>)> = tracked
         .iter()
-        .filter(|branch| branch.name != base_branch)
+        .filter(|branch| !bases.contains(&branch.name))
         .filter(|branch| branch_exists.get(&branch.name).copied().unwrap_or(false))
         .map(|branch| (branch.name.as_str(), branch.cached_pr_number))
         .collect();
@@ -128,7 +301,7 @@ pub fn build_sync_plan(
     let mut current_sha_by_branch: HashMap<String, String> = HashMap::new();
     let mut by_id: HashMap<i64, BranchRecord> = HashMap::new();
     let mut children: HashMap<i64, Vec<i64>> = HashMap::new();
-    let mut base_merge_commit_to_apply: Option<String> = None;
+    let mut base_merge_commits_to_apply: BTreeMap<String, String> = BTreeMap::new();
 
     for b in &tracked {
         by_id.insert(b.id, b.clone());
@@ -138,29 +311,58 @@ pub fn build_sync_plan(
     }
 
     let mut queue: VecDeque<RestackCandidate> = VecDeque::new();
+    let mut fetched_remotes = HashSet::from([sync_remote.clone()]);
 
     for branch in &tracked {
         if !branch_exists.get(&branch.name).copied().unwrap_or(false) {
             continue;
         }
-        if branch.name == base_branch {
+        if bases.contains(&branch.name) {
             db.set_pr_cache(&branch.name, None, None)?;
         }
+        let base_branch = bases.base_for(&tracked, &branch.name);
         let current_sha = git.head_sha(&branch.name)?;
         current_sha_by_branch.insert(branch.name.clone(), current_sha.clone());
 
+        // Frozen branches only move by fast-forwarding to their remote-tracking
+        // ref. Whether that ref moved is only known after the plan's fetches,
+        // so the op and its children's restacks re-check when they run.
+        let mut fast_forwarded = false;
+        if branch.frozen
+            && let Some(tracking) = git.remote_tracking_ref(&branch.name)?
+        {
+            if let Some((remote, _)) = tracking.split_once('/')
+                && fetched_remotes.insert(remote.to_string())
+            {
+                ops.push(SyncOp::Fetch {
+                    remote: remote.to_string(),
+                });
+            }
+            ops.push(SyncOp::FastForward {
+                branch: branch.name.clone(),
+                onto: tracking,
+            });
+            for child in children
+                .get(&branch.id)
+                .into_iter()
+                .flatten()
+                .filter_map(|id| by_id.get(id))
+            {
+                queue.push_back(RestackCandidate {
+                    branch: child.name.clone(),
+                    onto: branch.name.clone(),
+                    old_base: Some(current_sha.clone()),
+                });
+            }
+            fast_forwarded = true;
+        }
+
         let mut is_merged_pr = branch
             .cached_pr_state
             .as_deref()
             .is_some_and(|state| state.eq_ignore_ascii_case("merged"));
         if let Some(pr) = pr_by_branch.get(&branch.name).cloned() {
-            let state = match pr.state {
-                PrState::Open => "open",
-                PrState::Merged => "merged",
-                PrState::Closed => "closed",
-                PrState::Unknown => "unknown",
-            };
-            db.set_pr_cache(&branch.name, Some(pr.number), Some(state))?;
+            db.set_pr_cache(&branch.name, Some(pr.number), Some(pr.state.as_str()))?;
             is_merged_pr = matches!(pr.state, PrState::Merged);
 
             if matches!(pr.state, PrState::Merged) {
@@ -174,10 +376,13 @@ pub fn build_sync_plan(
                     .and_then(|parent_id| by_id.get(&parent_id))
                     .is_some_and(|parent| parent.name == base_branch);
                 if is_direct_child_of_base
-                    && base_merge_commit_to_apply.is_none()
+                    && !base_merge_commits_to_apply.contains_key(base_branch)
                     && let Some(merge_commit_oid) = merge_commit_oid.as_deref()
+                    && !(git.ref_exists(merge_commit_oid)?
+                        && git.is_ancestor(merge_commit_oid, base_branch)?)
                 {
-                    base_merge_commit_to_apply = Some(merge_commit_oid.to_string());
+                    base_merge_commits_to_apply
+                        .insert(base_branch.to_string(), merge_commit_oid.to_string());
                 }
 
                 if let Some(children_ids) = children.get(&branch.id) {
@@ -236,8 +441,8 @@ pub fn build_sync_plan(
                             .is_some_and(|state| state.eq_ignore_ascii_case("merged"))
                     });
                 if !parent_is_merged {
-                    let parent_onto = if parent.name == base_branch {
-                        let remote_base_ref = format!("{sync_remote}/{base_branch}");
+                    let parent_onto = if bases.contains(&parent.name) {
+                        let remote_base_ref = format!("{sync_remote}/{}", parent.name);
                         if git.ref_exists(&remote_base_ref)? {
                             remote_base_ref
                         } else {
@@ -270,9 +475,8 @@ pub fn build_sync_plan(
                 }
             }
         }
-        let has_base_merge_update =
-            base_merge_commit_to_apply.is_some() && branch.name == base_branch;
-        if !is_merged_pr && !has_base_merge_update {
+        let has_base_merge_update = base_merge_commits_to_apply.contains_key(&branch.name);
+        if !is_merged_pr && !has_base_merge_update && !fast_forwarded {
             ops.push(SyncOp::UpdateSha {
                 branch: branch.name.clone(),
                 sha: current_sha,
@@ -280,11 +484,11 @@ pub fn build_sync_plan(
         }
     }
 
-    if let Some(merge_commit) = base_merge_commit_to_apply {
+    for (position, (branch, merge_commit)) in base_merge_commits_to_apply.into_iter().enumerate() {
         ops.insert(
-            1,
+            1 + position,
             SyncOp::UpdateBaseToMergeCommit {
-                branch: base_branch.to_string(),
+                branch,
                 merge_commit,
             },
         );
@@ -295,6 +499,17 @@ pub fn build_sync_plan(
         if !seen_restack.insert(item.branch.clone()) {
             continue;
         }
+        if tracked.iter().any(|b| b.name == item.branch && b.frozen) {
+            ops.push(SyncOp::Skip {
+                branch: item.branch.clone(),
+                kind: "restack".to_string(),
+                reason: format!(
+                    "branch is frozen; it stays on its current base instead of {}",
+                    item.onto
+                ),
+            });
+            continue;
+        }
         ops.push(SyncOp::Restack {
             branch: item.branch.clone(),
             onto: item.onto.clone(),
@@ -319,10 +534,12 @@ pub fn build_sync_plan(
     }
 
     let base_url = git
-        .remote_web_url(&sync_remote)?
+        .pr_repo_web_url(&sync_remote)?
         .or_else(|| git.remote_web_url("origin").ok().flatten())
         .or_else(|| git.remote_web_url("upstream").ok().flatten());
     if let Some(base_url) = base_url {
+        let section_builder =
+            ManagedSectionBuilder::new(&tracked, &pr_by_branch, bases, &options.body_style);
         for branch in &tracked {
             let Some(pr) = pr_by_branch.get(&branch.name) else {
                 continue;
@@ -331,55 +548,106 @@ pub fn build_sync_plan(
                 continue;
             }
 
-            let parent_ref = branch
-                .parent_branch_id
-                .and_then(|parent_id| by_id.get(&parent_id))
-                .map(|parent| ManagedBranchRef {
-                    branch: parent.name.clone(),
-                    pr_number: pr_by_branch.get(&parent.name).map(|p| p.number),
-                    pr_url: pr_by_branch.get(&parent.name).and_then(|p| p.url.clone()),
-                });
-            let first_child = children.get(&branch.id).and_then(|ids| {
-                ids.iter()
-                    .filter_map(|id| by_id.get(id))
-                    .map(|child| ManagedBranchRef {
-                        branch: child.name.clone(),
-                        pr_number: pr_by_branch.get(&child.name).map(|p| p.number),
-                        pr_url: pr_by_branch.get(&child.name).and_then(|p| p.url.clone()),
-                    })
-                    .min_by(|a, b| a.branch.cmp(&b.branch))
-            });
             let pr_root = pr
                 .url
                 .as_deref()
                 .and_then(repo_root_from_pr_url)
                 .unwrap_or(base_url.as_str());
             let base_commit_url = git
-                .merge_base(&branch.name, base_branch)
+                .merge_base(&branch.name, bases.base_for(&tracked, &branch.name))
                 .ok()
                 .map(|sha| format!("{}/commit/{sha}", pr_root.trim_end_matches('/')));
-            let managed_section = managed_pr_section(
-                pr_root,
-                base_branch,
-                base_commit_url.as_deref(),
-                parent_ref.as_ref(),
-                first_child.as_ref(),
-            );
-            let merged_body = merge_managed_pr_section(pr.body.as_deref(), &managed_section);
-
-            let should_update = pr.body.as_deref().map(str::trim) != Some(merged_body.trim());
-            if should_update {
-                ops.push(SyncOp::UpdatePrBody {
+            let managed_section =
+                section_builder.section_for(&branch.name, pr_root, base_commit_url.as_deref())?;
+            for update in managed_section_updates(
+                provider,
+                pr,
+                &managed_section,
+                options.body_style.placement,
+                options.offline,
+            ) {
+                ops.push(match update {
+                    ManagedSectionUpdate::Body(body) => SyncOp::UpdatePrBody {
+                        branch: branch.name.clone(),
+                        pr_number: pr.number,
+                        body,
+                    },
+                    ManagedSectionUpdate::Comment { comment_id, body } => SyncOp::UpdatePrComment {
+                        branch: branch.name.clone(),
+                        pr_number: pr.number,
+                        comment_id,
+                        body,
+                    },
+                });
+            }
+        }
+    }
+
+    if let Some(prefix) = &options.title_prefix {
+        let section_builder =
+            ManagedSectionBuilder::new(&tracked, &pr_by_branch, bases, &options.body_style);
+        for branch in &tracked {
+            let Some(pr) = pr_by_branch.get(&branch.name) else {
+                continue;
+            };
+            if !matches!(pr.state, PrState::Open) {
+                continue;
+            }
+            if let Some(title) = section_builder.prefixed_title(&branch.name, pr, prefix) {
+                ops.push(SyncOp::UpdatePrTitle {
                     branch: branch.name.clone(),
                     pr_number: pr.number,
-                    body: merged_body,
+                    title,
                 });
             }
         }
     }
 
+    if options.draft_policy {
+        for change in draft_policy_changes(&tracked, &pr_by_branch, bases) {
+            ops.push(SyncOp::SetPrDraft {
+                branch: change.branch,
+                pr_number: change.pr_number,
+                draft: change.draft,
+            });
+        }
+    }
+
+    if options.order_status {
+        let branches = tracked.iter().map(|b| b.name.as_str()).collect::<Vec<_>>();
+        for update in order_status_updates(provider, &tracked, &pr_by_branch, bases, &branches) {
+            ops.push(SyncOp::SetOrderStatus {
+                branch: update.branch,
+                pr_number: update.pr_number,
+                status: update.status,
+            });
+        }
+    }
+
+    let frozen = tracked
+        .iter()
+        .filter(|b| b.frozen)
+        .map(|b| b.name.as_str())
+        .collect::<HashSet<_>>();
+    let ops = ops
+        .into_iter()
+        .map(|op| match op.rewrite_target() {
+            Some((branch, kind)) if kind != "restack" && frozen.contains(branch) => SyncOp::Skip {
+                branch: branch.to_string(),
+                kind: kind.to_string(),
+                reason: "branch is frozen; its PR is left untouched".to_string(),
+            },
+            Some((branch, kind)) if kind != "restack" && options.offline => SyncOp::Skip {
+                branch: branch.to_string(),
+                kind: kind.to_string(),
+                reason: "offline mode; its PR is left untouched".to_string(),
+            },
+            _ => op,
+        })
+        .collect();
+
     Ok(SyncPlan {
-        base_branch: base_branch.to_string(),
+        base_branch: bases.default_branch().to_string(),
         ops,
     })
 }
@@ -402,58 +670,42 @@ pub fn execute_sync_plan(
     let mut summary = None;
     let replay_supported = git.supports_replay();
 
-    let op_result: Result<()> = (|| {
-        for op in &plan.ops {
-            match op {
-                SyncOp::Fetch { remote } => git.fetch_remote(remote)?,
-                SyncOp::UpdateBaseToMergeCommit {
-                    branch,
-                    merge_commit,
-                } => {
-                    git.fast_forward_branch(branch, merge_commit)?;
-                    let sha = git.head_sha(branch)?;
-                    db.set_sync_sha(branch, &sha)?;
-                }
-                SyncOp::Restack {
-                    branch,
-                    onto,
-                    old_base,
-                    ..
-                } => {
-                    let old_base = if let Some(old_base) = old_base {
-                        old_base.clone()
-                    } else {
-                        git.merge_base(branch, onto)?
-                    };
-                    if git.commit_distance(&old_base, branch)? == 0 {
-                        git.rebase_onto(branch, &old_base, onto)?;
-                        let sha = git.head_sha(branch)?;
-                        db.set_sync_sha(branch, &sha)?;
-                        continue;
-                    }
-                    if replay_supported {
-                        if let Err(err) = git.replay_onto(branch, &old_base, onto) {
-                            let reason = summarize_replay_error(&err);
-                            eprintln!(
-                                "warning: git replay is unavailable for '{branch}' ({reason}); falling back to rebase"
-                            );
-                            git.rebase_onto(branch, &old_base, onto)?;
-                        }
-                    } else {
-                        eprintln!("warning: git replay unavailable; using rebase for {branch}");
-                        git.rebase_onto(branch, &old_base, onto)?;
-                    }
-                    let sha = git.head_sha(branch)?;
-                    db.set_sync_sha(branch, &sha)?;
-                }
-                SyncOp::UpdateSha { branch, sha } => db.set_sync_sha(branch, sha)?,
-                SyncOp::UpdatePrBody {
-                    pr_number, body, ..
-                } => provider.update_pr_body(*pr_number, body)?,
+    let views = plan.to_view().operations;
+    let mut op_result: Result<()> = Ok(());
+    let mut records = Vec::with_capacity(plan.ops.len());
+    for (position, (op, view)) in plan.ops.iter().zip(views).enumerate() {
+        let mut record = SyncRunOpRecord {
+            position: position as i64,
+            kind: view.kind,
+            branch: view.branch,
+            onto: view.onto,
+            details: view.details,
+            status: "not_run".to_string(),
+            duration_ms: None,
+            before_sha: None,
+            after_sha: None,
+            error: None,
+        };
+        if op_result.is_ok() {
+            let rewritten = rewritten_branch(op);
+            record.before_sha = rewritten.and_then(|branch| git.head_sha(branch).ok());
+            let started = Instant::now();
+            let result = apply_sync_op(db, git, provider, op, replay_supported);
+            record.duration_ms = Some(started.elapsed().as_millis() as i64);
+            record.after_sha = rewritten.and_then(|branch| git.head_sha(branch).ok());
+            record.status = match (&result, op) {
+                (Err(_), _) => "failed",
+                (Ok(()), SyncOp::Skip { .. }) => "skipped",
+                (Ok(()), _) => "done",
+            }
+            .to_string();
+            if let Err(err) = result {
+                record.error = Some(err.to_string());
+                op_result = Err(err);
             }
         }
-        Ok(())
-    })();
+        records.push(record);
+    }
 
     let restore_branch_result = restore_starting_branch(git, &starting_branch);
 
@@ -466,6 +718,15 @@ pub fn execute_sync_plan(
         );
     }
 
+    // Written only once the worktree is restored: history is best effort and
+    // must not leave the user on another branch.
+    for record in &records {
+        if let Err(err) = db.record_sync_op(run_id, record) {
+            eprintln!("warning: could not record sync history for run #{run_id}: {err}");
+            break;
+        }
+    }
+
     let result = match (op_result, restore_branch_result) {
         (Err(op_err), Err(restore_err)) => Err(anyhow!(
             "{op_err}; additionally failed to restore prior branch '{}': {restore_err}",
@@ -493,8 +754,108 @@ pub fn execute_sync_plan(
     Ok(())
 }
 
-fn repo_root_from_pr_url(url: &str) -> Option<&str> {
-    url.split_once("/pull/").map(|(root, _)| root)
+/// The branch whose head `op` moves, so its SHAs are recorded in history.
+fn rewritten_branch(op: &SyncOp) -> Option<&str> {
+    match op {
+        SyncOp::UpdateBaseToMergeCommit { branch, .. }
+        | SyncOp::Restack { branch, .. }
+        | SyncOp::FastForward { branch, .. } => Some(branch),
+        _ => None,
+    }
+}
+
+fn apply_sync_op(
+    db: &Database,
+    git: &Git,
+    provider: &dyn Provider,
+    op: &SyncOp,
+    replay_supported: bool,
+) -> Result<()> {
+    match op {
+        SyncOp::Fetch { remote } => git.fetch_remote(remote)?,
+        SyncOp::UpdateBaseToMergeCommit {
+            branch,
+            merge_commit,
+        } => {
+            git.fast_forward_branch(branch, merge_commit)?;
+            let sha = git.head_sha(branch)?;
+            db.set_sync_sha(branch, &sha)?;
+        }
+        SyncOp::Restack {
+            branch,
+            onto,
+            old_base,
+            ..
+        } => {
+            let old_base = if let Some(old_base) = old_base {
+                old_base.clone()
+            } else {
+                git.merge_base(branch, onto)?
+            };
+            // `onto` never moved (e.g. a frozen parent with nothing to
+            // fast-forward) and the branch already sits on it.
+            if git.head_sha(onto)? == git.head_sha(&old_base)? && git.is_ancestor(onto, branch)? {
+                return Ok(());
+            }
+            if git.commit_distance(&old_base, branch)? == 0 {
+                git.rebase_onto(branch, &old_base, onto)?;
+                let sha = git.head_sha(branch)?;
+                db.set_sync_sha(branch, &sha)?;
+                return Ok(());
+            }
+            if replay_supported {
+                if let Err(err) = git.replay_onto(branch, &old_base, onto) {
+                    let reason = summarize_replay_error(&err);
+                    eprintln!(
+                        "warning: git replay is unavailable for '{branch}' ({reason}); falling back to rebase"
+                    );
+                    git.rebase_onto(branch, &old_base, onto)?;
+                }
+            } else {
+                eprintln!("warning: git replay unavailable; using rebase for {branch}");
+                git.rebase_onto(branch, &old_base, onto)?;
+            }
+            let sha = git.head_sha(branch)?;
+            db.set_sync_sha(branch, &sha)?;
+        }
+        SyncOp::FastForward { branch, onto } => {
+            if git.head_sha(onto)? != git.head_sha(branch)? {
+                if git.is_ancestor(branch, onto)? {
+                    git.fast_forward_branch(branch, onto)?;
+                } else {
+                    eprintln!(
+                        "warning: frozen branch '{branch}' has diverged from {onto}; leaving it as is"
+                    );
+                }
+            }
+            let sha = git.head_sha(branch)?;
+            db.set_sync_sha(branch, &sha)?;
+        }
+        SyncOp::UpdateSha { branch, sha } => db.set_sync_sha(branch, sha)?,
+        SyncOp::UpdatePrBody {
+            pr_number, body, ..
+        } => provider.update_pr_body(*pr_number, body)?,
+        SyncOp::UpdatePrComment {
+            pr_number,
+            comment_id,
+            body,
+            ..
+        } => match comment_id {
+            Some(id) => provider.update_pr_comment(*id, body)?,
+            None => provider.create_pr_comment(*pr_number, body)?,
+        },
+        SyncOp::UpdatePrTitle {
+            pr_number, title, ..
+        } => provider.update_pr_title(*pr_number, title)?,
+        SyncOp::SetPrDraft {
+            pr_number, draft, ..
+        } => provider.set_pr_draft(*pr_number, *draft)?,
+        SyncOp::SetOrderStatus {
+            pr_number, status, ..
+        } => provider.set_pr_head_status(*pr_number, status)?,
+        SyncOp::Skip { .. } => {}
+    }
+    Ok(())
 }
 
 fn restore_starting_branch(git: &Git, starting_branch: &str) -> Result<()> {
@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, anyhow};
+use rusqlite::backup::Progress;
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+
+use super::table_exists;
+
+/// Snapshots kept per reason in `stack-backups/`; older ones are removed.
+pub const KEEP_BACKUPS: usize = 10;
+
+/// A snapshot of `stack.db`, stored as `<id>-<unix secs>-<reason>.db` so
+/// the listing needs nothing but the directory.
+#[derive(Debug, Clone)]
+pub struct BackupRecord {
+    pub id: u64,
+    pub created_at: u64,
+    pub reason: String,
+    pub path: PathBuf,
+}
+
+/// A parent link that differs between the live database and a backup.
+/// `None` on one side means the branch is not tracked there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParentLinkChange {
+    pub branch: String,
+    pub current: Option<Option<String>>,
+    pub restored: Option<Option<String>>,
+}
+
+/// `stack-backups/` next to the database.
+pub fn backups_dir(db_path: &Path) -> PathBuf {
+    db_path.with_file_name("stack-backups")
+}
+
+/// Backups of the database at `db_path`, oldest first.
+pub fn list_backups(db_path: &Path) -> Result<Vec<BackupRecord>> {
+    let dir = backups_dir(db_path);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(&dir).with_context(|| format!("failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if let Some(record) = parse_backup_name(&path) {
+            backups.push(record);
+        }
+    }
+    backups.sort_by_key(|b| b.id);
+    Ok(backups)
+}
+
+/// Looks up backup `id`, naming the command that lists backups when absent.
+pub fn find_backup(db_path: &Path, id: u64) -> Result<BackupRecord> {
+    list_backups(db_path)?
+        .into_iter()
+        .find(|b| b.id == id)
+        .ok_or_else(|| anyhow!("no backup #{id}; run `stack db backups` to list them"))
+}
+
+/// Copies the live database into a new snapshot with SQLite's online
+/// backup API, which is consistent even while other connections read.
+pub fn create_backup(conn: &Connection, db_path: &Path, reason: &str) -> Result<BackupRecord> {
+    let dir = backups_dir(db_path);
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let id = list_backups(db_path)?.last().map_or(1, |b| b.id + 1);
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let path = dir.join(format!("{id}-{created_at}-{reason}.db"));
+    conn.backup(DatabaseName::Main, &path, None)
+        .with_context(|| format!("failed to back up stack.db to {}", path.display()))?;
+    // The copy inherits WAL mode; switch it back so each snapshot is one file.
+    Connection::open(&path)?.query_row("PRAGMA journal_mode = DELETE", [], |row| {
+        row.get::<_, String>(0)
+    })?;
+    Ok(BackupRecord {
+        id,
+        created_at,
+        reason: reason.to_string(),
+        path,
+    })
+}
+
+/// Removes all but the newest [`KEEP_BACKUPS`] snapshots of each reason, so
+/// frequent `sync` snapshots never evict a rarer pre-migration one.
+pub fn prune_backups(db_path: &Path) -> Result<()> {
+    let mut by_reason = BTreeMap::<String, Vec<BackupRecord>>::new();
+    for backup in list_backups(db_path)? {
+        by_reason
+            .entry(backup.reason.clone())
+            .or_default()
+            .push(backup);
+    }
+    for backups in by_reason.values() {
+        let excess = backups.len().saturating_sub(KEEP_BACKUPS);
+        for backup in &backups[..excess] {
+            fs::remove_file(&backup.path)
+                .with_context(|| format!("failed to remove {}", backup.path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Overwrites the database at `db_path` with `backup`, page by page through
+/// the backup API so open readers never see a half-copied file.
+pub fn restore_backup(db_path: &Path, backup: &BackupRecord) -> Result<()> {
+    let mut conn = Connection::open(db_path)
+        .with_context(|| format!("failed to open sqlite at {}", db_path.display()))?;
+    conn.restore(DatabaseName::Main, &backup.path, None::<fn(Progress)>)
+        .with_context(|| format!("failed to restore {}", backup.path.display()))?;
+    Ok(())
+}
+
+/// Parent links that restoring `backup` over `db_path` would change.
+pub fn parent_link_changes(db_path: &Path, backup: &BackupRecord) -> Result<Vec<ParentLinkChange>> {
+    let current = if db_path.exists() {
+        parent_links(db_path)?
+    } else {
+        BTreeMap::new()
+    };
+    let restored = parent_links(&backup.path)?;
+    let mut branches = current.keys().chain(restored.keys()).collect::<Vec<_>>();
+    branches.sort();
+    branches.dedup();
+    Ok(branches
+        .into_iter()
+        .filter(|branch| current.get(*branch) != restored.get(*branch))
+        .map(|branch| ParentLinkChange {
+            branch: branch.clone(),
+            current: current.get(branch).cloned(),
+            restored: restored.get(branch).cloned(),
+        })
+        .collect())
+}
+
+fn parent_links(path: &Path) -> Result<BTreeMap<String, Option<String>>> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("failed to open sqlite at {}", path.display()))?;
+    if !table_exists(&conn, "branches")? {
+        return Ok(BTreeMap::new());
+    }
+    let mut stmt = conn.prepare(
+        "SELECT b.name, p.name FROM branches b LEFT JOIN branches p ON b.parent_branch_id = p.id",
+    )?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+fn parse_backup_name(path: &Path) -> Option<BackupRecord> {
+    if path.extension()? != "db" {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?;
+    let mut parts = stem.splitn(3, '-');
+    let id = parts.next()?.parse().ok()?;
+    let created_at = parts.next()?.parse().ok()?;
+    let reason = parts.next()?.to_string();
+    Some(BackupRecord {
+        id,
+        created_at,
+        reason,
+        path: path.to_path_buf(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backups_rotate_and_restore_reports_parent_link_changes() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let db_path = dir.path().join("stack.db");
+        let conn = Connection::open(&db_path).expect("open db");
+        conn.execute_batch(
+            "CREATE TABLE branches (id INTEGER PRIMARY KEY, name TEXT NOT NULL, parent_branch_id INTEGER NULL);
+             INSERT INTO branches VALUES (1, 'main', NULL), (2, 'feat/a', 1), (3, 'feat/b', 2);",
+        )
+        .expect("seed db");
+        let migration = create_backup(&conn, &db_path, "migrate-v7").expect("migration backup");
+        let first = create_backup(&conn, &db_path, "sync").expect("first backup");
+        for _ in 0..KEEP_BACKUPS {
+            create_backup(&conn, &db_path, "sync").expect("backup");
+        }
+        prune_backups(&db_path).expect("prune");
+        let backups = list_backups(&db_path).expect("list");
+        assert_eq!(backups.len(), KEEP_BACKUPS + 1);
+        assert_eq!(backups[0].id, migration.id);
+        assert_eq!(backups[1].id, first.id + 1);
+        assert!(!first.path.exists());
+        let kept = backups[1].clone();
+
+        conn.execute_batch(
+            "UPDATE branches SET parent_branch_id = 1 WHERE name = 'feat/b';
+             DELETE FROM branches WHERE name = 'feat/a';
+             INSERT INTO branches VALUES (4, 'feat/c', 1);",
+        )
+        .expect("edit db");
+        let changes = parent_link_changes(&db_path, &kept).expect("diff");
+        let parent = |name: &str| Some(Some(name.to_string()));
+        assert_eq!(
+            changes,
+            vec![
+                ParentLinkChange {
+                    branch: "feat/a".to_string(),
+                    current: None,
+                    restored: parent("main"),
+                },
+                ParentLinkChange {
+                    branch: "feat/b".to_string(),
+                    current: parent("main"),
+                    restored: parent("feat/a"),
+                },
+                ParentLinkChange {
+                    branch: "feat/c".to_string(),
+                    current: parent("main"),
+                    restored: None,
+                },
+            ]
+        );
+
+        drop(conn);
+        restore_backup(&db_path, &kept).expect("restore");
+        assert!(
+            parent_link_changes(&db_path, &kept)
+                .expect("diff after restore")
+                .is_empty()
+        );
+    }
+}
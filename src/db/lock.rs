@@ -0,0 +1,95 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+
+/// Advisory lock held by commands that rewrite branches or write `stack.db`,
+/// so two `stack` processes never interleave git rewrites and DB writes.
+/// The lock file beside the database records the holder's pid and command
+/// line; the OS releases the lock when the holder exits, even on a crash.
+#[derive(Debug)]
+pub struct StackLock {
+    _file: File,
+}
+
+impl StackLock {
+    /// Takes the lock for `db_path`. When another process holds it, fails
+    /// naming that process, or with `wait` blocks until it is released.
+    pub fn acquire(db_path: &Path, command: &str, wait: bool) -> Result<Self> {
+        let path = lock_path(db_path);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .with_context(|| format!("failed to open lock file {}", path.display()))?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let holder = read_holder(&mut file);
+                if !wait {
+                    return Err(anyhow!(
+                        "another stack command is running ({holder}); rerun with --wait to wait for it"
+                    ));
+                }
+                eprintln!("waiting for another stack command to finish ({holder})");
+                file.lock()
+                    .with_context(|| format!("failed to lock {}", path.display()))?;
+            }
+            Err(TryLockError::Error(err)) => {
+                return Err(err).with_context(|| format!("failed to lock {}", path.display()));
+            }
+        }
+
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        writeln!(file, "{}\n{command}", std::process::id())?;
+        file.flush()?;
+        Ok(Self { _file: file })
+    }
+}
+
+/// `stack.db.lock` next to the database.
+fn lock_path(db_path: &Path) -> PathBuf {
+    let mut name = db_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    db_path.with_file_name(name)
+}
+
+/// `pid 123: stack sync --yes`, or a placeholder while the holder has not
+/// written its details yet.
+fn read_holder(file: &mut File) -> String {
+    let mut contents = String::new();
+    if file.seek(SeekFrom::Start(0)).is_err() || file.read_to_string(&mut contents).is_err() {
+        return "holder unknown".to_string();
+    }
+    match contents.trim().split_once('\n') {
+        Some((pid, command)) => format!("pid {pid}: {}", command.trim()),
+        None if !contents.trim().is_empty() => format!("pid {}", contents.trim()),
+        None => "holder unknown".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn second_lock_fails_naming_the_holder_until_released() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let db_path = dir.path().join("stack.db");
+        let held = StackLock::acquire(&db_path, "stack sync --yes", false).expect("first lock");
+
+        let err = StackLock::acquire(&db_path, "stack push", false).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains(&format!("pid {}", std::process::id())));
+        assert!(message.contains("stack sync --yes"));
+        assert!(message.contains("--wait"));
+
+        drop(held);
+        StackLock::acquire(&db_path, "stack push", false).expect("lock after release");
+    }
+}
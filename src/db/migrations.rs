@@ -0,0 +1,139 @@
+use rusqlite::Connection;
+
+use super::ensure_column;
+
+/// One ordered schema change. Steps are idempotent so databases created
+/// before versioning (which already carry some of these tables and columns)
+/// upgrade cleanly.
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    apply: fn(&Connection) -> rusqlite::Result<()>,
+}
+
+impl Migration {
+    pub fn apply(&self, conn: &Connection) -> rusqlite::Result<()> {
+        (self.apply)(conn)
+    }
+}
+
+/// Every migration in version order; append new ones at the end.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create branches, repo_meta, sync_runs and schema_migrations tables",
+        apply: |conn| {
+            conn.execute_batch(
+                "
+                CREATE TABLE IF NOT EXISTS branches (
+                    id INTEGER PRIMARY KEY,
+                    name TEXT NOT NULL UNIQUE,
+                    parent_branch_id INTEGER NULL,
+                    last_synced_head_sha TEXT NULL,
+                    cached_pr_number INTEGER NULL,
+                    cached_pr_state TEXT NULL,
+                    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY(parent_branch_id) REFERENCES branches(id) ON DELETE SET NULL
+                );
+                CREATE TABLE IF NOT EXISTS repo_meta (
+                    id INTEGER PRIMARY KEY CHECK (id = 1),
+                    base_branch TEXT NOT NULL,
+                    schema_version INTEGER NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS sync_runs (
+                    id INTEGER PRIMARY KEY,
+                    started_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    finished_at TEXT NULL,
+                    status TEXT NOT NULL,
+                    summary_json TEXT NULL
+                );
+                CREATE TABLE IF NOT EXISTS schema_migrations (
+                    version INTEGER PRIMARY KEY,
+                    description TEXT NOT NULL,
+                    applied_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+                );
+                ",
+            )
+        },
+    },
+    Migration {
+        version: 2,
+        description: "add pr_cache table",
+        apply: |conn| {
+            conn.execute_batch(
+                "
+                CREATE TABLE IF NOT EXISTS pr_cache (
+                    branch TEXT PRIMARY KEY,
+                    number INTEGER NULL,
+                    state TEXT NULL,
+                    merge_commit_oid TEXT NULL,
+                    base_ref_name TEXT NULL,
+                    body TEXT NULL,
+                    url TEXT NULL,
+                    fetched_at INTEGER NOT NULL
+                );
+                ",
+            )
+        },
+    },
+    Migration {
+        version: 3,
+        description: "add pr_cache.title",
+        apply: |conn| ensure_column(conn, "pr_cache", "title", "TEXT NULL"),
+    },
+    Migration {
+        version: 4,
+        description: "add pr_cache.is_draft",
+        apply: |conn| ensure_column(conn, "pr_cache", "is_draft", "INTEGER NOT NULL DEFAULT 0"),
+    },
+    Migration {
+        version: 5,
+        description: "add branches.base_branch for per-stack base branches",
+        apply: |conn| ensure_column(conn, "branches", "base_branch", "TEXT NULL"),
+    },
+    Migration {
+        version: 6,
+        description: "add branches.description, ticket and owner annotations",
+        apply: |conn| {
+            ensure_column(conn, "branches", "description", "TEXT NULL")?;
+            ensure_column(conn, "branches", "ticket", "TEXT NULL")?;
+            ensure_column(conn, "branches", "owner", "TEXT NULL")
+        },
+    },
+    Migration {
+        version: 7,
+        description: "add branches.frozen",
+        apply: |conn| ensure_column(conn, "branches", "frozen", "INTEGER NOT NULL DEFAULT 0"),
+    },
+    Migration {
+        version: 8,
+        description: "add sync_run_ops for per-op sync history",
+        apply: |conn| {
+            conn.execute_batch(
+                "
+                CREATE TABLE IF NOT EXISTS sync_run_ops (
+                    run_id INTEGER NOT NULL,
+                    position INTEGER NOT NULL,
+                    kind TEXT NOT NULL,
+                    branch TEXT NOT NULL,
+                    onto TEXT NULL,
+                    details TEXT NOT NULL,
+                    status TEXT NOT NULL,
+                    duration_ms INTEGER NULL,
+                    before_sha TEXT NULL,
+                    after_sha TEXT NULL,
+                    error TEXT NULL,
+                    PRIMARY KEY(run_id, position),
+                    FOREIGN KEY(run_id) REFERENCES sync_runs(id) ON DELETE CASCADE
+                );
+                ",
+            )
+        },
+    },
+];
+
+/// Version of the newest known migration.
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
@@ -1,7 +1,16 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use anyhow::{Context, Result, anyhow};
-use rusqlite::{Connection, OptionalExtension, params};
+use rusqlite::{Connection, OpenFlags, OptionalExtension, params};
+
+pub mod backup;
+mod lock;
+mod migrations;
+
+pub use lock::StackLock;
+
+use migrations::{MIGRATIONS, latest_version};
 
 #[derive(Debug, Clone)]
 pub struct BranchRecord {
@@ -11,6 +20,49 @@ pub struct BranchRecord {
     pub last_synced_head_sha: Option<String>,
     pub cached_pr_number: Option<i64>,
     pub cached_pr_state: Option<String>,
+    pub description: Option<String>,
+    pub ticket: Option<String>,
+    pub owner: Option<String>,
+    /// Frozen branches are never restacked, pushed or PR-edited; sync only
+    /// fast-forwards them from their remote-tracking ref.
+    pub frozen: bool,
+}
+
+#[cfg(test)]
+impl BranchRecord {
+    /// Record with only an id, name and parent, for unit tests.
+    pub fn test(id: i64, name: &str, parent: Option<i64>) -> Self {
+        Self {
+            id,
+            name: name.to_string(),
+            parent_branch_id: parent,
+            last_synced_head_sha: None,
+            cached_pr_number: None,
+            cached_pr_state: None,
+            description: None,
+            ticket: None,
+            owner: None,
+            frozen: false,
+        }
+    }
+}
+
+/// Free-text branch annotations set with `stack annotate`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BranchAnnotations {
+    pub description: Option<String>,
+    pub ticket: Option<String>,
+    pub owner: Option<String>,
+}
+
+impl BranchRecord {
+    pub fn annotations(&self) -> BranchAnnotations {
+        BranchAnnotations {
+            description: self.description.clone(),
+            ticket: self.ticket.clone(),
+            owner: self.owner.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -18,68 +70,180 @@ pub struct RepoMeta {
     pub base_branch: String,
 }
 
+#[derive(Debug, Clone)]
+pub struct PrCacheEntry {
+    pub branch: String,
+    pub number: Option<i64>,
+    pub state: Option<String>,
+    pub merge_commit_oid: Option<String>,
+    pub base_ref_name: Option<String>,
+    pub body: Option<String>,
+    pub url: Option<String>,
+    pub title: Option<String>,
+    pub is_draft: bool,
+    pub fetched_at: i64,
+}
+
 #[derive(Debug, Clone)]
 pub struct ParentUpdate {
     pub child_name: String,
     pub parent_name: Option<String>,
 }
 
+/// One recorded `stack sync` (or post-merge restack) run.
+#[derive(Debug, Clone)]
+pub struct SyncRunRecord {
+    pub id: i64,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub status: String,
+    pub summary_json: Option<String>,
+}
+
+/// One op of a recorded sync run. `status` is `done`, `failed`, `skipped`
+/// (left out of the plan) or `not_run` (after an earlier op failed).
+#[derive(Debug, Clone)]
+pub struct SyncRunOpRecord {
+    pub position: i64,
+    pub kind: String,
+    pub branch: String,
+    pub onto: Option<String>,
+    pub details: String,
+    pub status: String,
+    pub duration_ms: Option<i64>,
+    pub before_sha: Option<String>,
+    pub after_sha: Option<String>,
+    pub error: Option<String>,
+}
+
+/// A migration that has been applied (`applied_at` set) or is pending.
+#[derive(Debug, Clone)]
+pub struct MigrationRecord {
+    pub version: i64,
+    pub description: String,
+    pub applied_at: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SchemaInfo {
+    pub version: i64,
+    pub latest_version: i64,
+    pub applied: Vec<MigrationRecord>,
+    pub pending: Vec<MigrationRecord>,
+}
+
 pub struct Database {
     conn: Connection,
+    path: PathBuf,
 }
 
 impl Database {
     pub fn open(path: &Path) -> Result<Self> {
         let conn = Connection::open(path)
             .with_context(|| format!("failed to open sqlite at {}", path.display()))?;
-        let db = Self { conn };
+        // WAL lets read-only commands (and the TUI) read while another
+        // process writes; writers are serialized by `StackLock`.
+        conn.query_row("PRAGMA journal_mode = WAL", [], |row| {
+            row.get::<_, String>(0)
+        })?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        let db = Self {
+            conn,
+            path: path.to_path_buf(),
+        };
         db.migrate()?;
         Ok(db)
     }
 
+    /// Applies pending migrations in one transaction. Databases written by a
+    /// newer `stack` are refused rather than modified.
     fn migrate(&self) -> Result<()> {
-        self.conn.execute_batch(
-            "
-            PRAGMA foreign_keys = ON;
-            CREATE TABLE IF NOT EXISTS branches (
-                id INTEGER PRIMARY KEY,
-                name TEXT NOT NULL UNIQUE,
-                parent_branch_id INTEGER NULL,
-                last_synced_head_sha TEXT NULL,
-                cached_pr_number INTEGER NULL,
-                cached_pr_state TEXT NULL,
-                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-                updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-                FOREIGN KEY(parent_branch_id) REFERENCES branches(id) ON DELETE SET NULL
-            );
-            CREATE TABLE IF NOT EXISTS repo_meta (
-                id INTEGER PRIMARY KEY CHECK (id = 1),
-                base_branch TEXT NOT NULL,
-                schema_version INTEGER NOT NULL
-            );
-            CREATE TABLE IF NOT EXISTS sync_runs (
-                id INTEGER PRIMARY KEY,
-                started_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-                finished_at TEXT NULL,
-                status TEXT NOT NULL,
-                summary_json TEXT NULL
-            );
-            ",
-        )?;
+        self.conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        let current = schema_version(&self.conn)?;
+        let latest = latest_version();
+        if current > latest {
+            return Err(anyhow!(
+                "stack.db schema version {current} is newer than this stack supports ({latest}); upgrade stack to use this repository"
+            ));
+        }
+        if current == latest {
+            return Ok(());
+        }
+
+        if table_exists(&self.conn, "repo_meta")? {
+            self.snapshot(&format!("migrate-v{current}"));
+        }
+        let tx = self.conn.unchecked_transaction()?;
+        for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+            migration.apply(&tx).with_context(|| {
+                format!(
+                    "failed to apply stack.db migration {} ({})",
+                    migration.version, migration.description
+                )
+            })?;
+            tx.execute(
+                "INSERT INTO schema_migrations(version, description) VALUES (?1, ?2)",
+                params![migration.version, migration.description],
+            )?;
+        }
+        tx.execute("UPDATE repo_meta SET schema_version = ?1", params![latest])?;
+        tx.commit()?;
         Ok(())
     }
 
+    /// Keeps a rotating snapshot of the database before a bulk rewrite. A
+    /// failed backup only warns, so it never blocks the command itself.
+    pub fn snapshot(&self, reason: &str) {
+        let result = backup::create_backup(&self.conn, &self.path, reason)
+            .and_then(|_| backup::prune_backups(&self.path));
+        if let Err(err) = result {
+            eprintln!("warning: could not back up stack.db: {err:#}");
+        }
+    }
+
+    /// Schema state of the database at `path` without migrating it.
+    pub fn inspect(path: &Path) -> Result<SchemaInfo> {
+        let (version, applied) = if path.exists() {
+            let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+                .with_context(|| format!("failed to open sqlite at {}", path.display()))?;
+            (schema_version(&conn)?, applied_migrations(&conn)?)
+        } else {
+            (0, Vec::new())
+        };
+        let pending = MIGRATIONS
+            .iter()
+            .filter(|m| m.version > version)
+            .map(|m| MigrationRecord {
+                version: m.version,
+                description: m.description.to_string(),
+                applied_at: None,
+            })
+            .collect();
+        Ok(SchemaInfo {
+            version,
+            latest_version: latest_version(),
+            applied,
+            pending,
+        })
+    }
+
     pub fn set_base_branch_if_missing(&self, base_branch: &str) -> Result<()> {
         self.conn.execute(
             "INSERT INTO repo_meta(id, base_branch, schema_version)
-             VALUES (1, ?1, 1)
+             VALUES (1, ?1, ?2)
              ON CONFLICT(id) DO NOTHING",
-            params![base_branch],
+            params![base_branch, latest_version()],
         )?;
         Ok(())
     }
 
     pub fn repo_meta(&self) -> Result<RepoMeta> {
+        self.try_repo_meta()?
+            .ok_or_else(|| anyhow!("repo metadata missing"))
+    }
+
+    /// Repository metadata, or `None` before the base branch is chosen.
+    pub fn try_repo_meta(&self) -> Result<Option<RepoMeta>> {
         self.conn
             .query_row(
                 "SELECT base_branch FROM repo_meta WHERE id = 1",
@@ -90,8 +254,19 @@ impl Database {
                     })
                 },
             )
-            .optional()?
-            .ok_or_else(|| anyhow!("repo metadata missing"))
+            .optional()
+            .map_err(Into::into)
+    }
+
+    pub fn set_base_branch(&self, base_branch: &str) -> Result<()> {
+        let updated = self.conn.execute(
+            "UPDATE repo_meta SET base_branch = ?1 WHERE id = 1",
+            params![base_branch],
+        )?;
+        if updated == 0 {
+            return Err(anyhow!("repo metadata missing"));
+        }
+        Ok(())
     }
 
     pub fn upsert_branch(&self, name: &str) -> Result<i64> {
@@ -108,7 +283,8 @@ impl Database {
     pub fn branch_by_name(&self, name: &str) -> Result<Option<BranchRecord>> {
         self.conn
             .query_row(
-                "SELECT id, name, parent_branch_id, last_synced_head_sha, cached_pr_number, cached_pr_state
+                "SELECT id, name, parent_branch_id, last_synced_head_sha, cached_pr_number, cached_pr_state,
+                    description, ticket, owner, frozen
                  FROM branches WHERE name = ?1",
                 params![name],
                 |row| {
@@ -119,6 +295,10 @@ impl Database {
                         last_synced_head_sha: row.get(3)?,
                         cached_pr_number: row.get(4)?,
                         cached_pr_state: row.get(5)?,
+                        description: row.get(6)?,
+                        ticket: row.get(7)?,
+                        owner: row.get(8)?,
+                        frozen: row.get(9)?,
                     })
                 },
             )
@@ -128,7 +308,8 @@ impl Database {
 
     pub fn list_branches(&self) -> Result<Vec<BranchRecord>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, name, parent_branch_id, last_synced_head_sha, cached_pr_number, cached_pr_state
+            "SELECT id, name, parent_branch_id, last_synced_head_sha, cached_pr_number, cached_pr_state,
+                    description, ticket, owner, frozen
              FROM branches ORDER BY name",
         )?;
         let mut rows = stmt.query([])?;
@@ -141,6 +322,10 @@ impl Database {
                 last_synced_head_sha: row.get(3)?,
                 cached_pr_number: row.get(4)?,
                 cached_pr_state: row.get(5)?,
+                description: row.get(6)?,
+                ticket: row.get(7)?,
+                owner: row.get(8)?,
+                frozen: row.get(9)?,
             });
         }
         Ok(out)
@@ -167,7 +352,68 @@ impl Database {
         if updates.is_empty() {
             return Ok(());
         }
+        self.check_parents_batch(updates)?;
+
+        self.transaction(|| self.write_parents(updates))
+    }
+
+    fn write_parents(&self, updates: &[ParentUpdate]) -> Result<()> {
+        let tx = &self.conn;
+        for update in updates {
+            tx.execute(
+                "INSERT INTO branches(name) VALUES (?1)
+                 ON CONFLICT(name) DO UPDATE SET updated_at = CURRENT_TIMESTAMP",
+                params![update.child_name],
+            )?;
+            if let Some(parent) = &update.parent_name {
+                tx.execute(
+                    "INSERT INTO branches(name) VALUES (?1)
+                     ON CONFLICT(name) DO UPDATE SET updated_at = CURRENT_TIMESTAMP",
+                    params![parent],
+                )?;
+            }
+        }
 
+        for update in updates {
+            if let Some(parent) = &update.parent_name {
+                tx.execute(
+                    "UPDATE branches
+                     SET parent_branch_id = (SELECT id FROM branches WHERE name = ?1),
+                         updated_at = CURRENT_TIMESTAMP
+                     WHERE name = ?2",
+                    params![parent, update.child_name],
+                )?;
+            } else {
+                tx.execute(
+                    "UPDATE branches SET parent_branch_id = NULL, updated_at = CURRENT_TIMESTAMP WHERE name = ?1",
+                    params![update.child_name],
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs `f` inside a savepoint: its writes are committed together, or
+    /// rolled back together when it fails. Savepoints nest, so callers may
+    /// wrap helpers that open their own.
+    pub fn transaction<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        self.conn.execute_batch("SAVEPOINT stack_tx")?;
+        match f() {
+            Ok(value) => {
+                self.conn.execute_batch("RELEASE stack_tx")?;
+                Ok(value)
+            }
+            Err(err) => {
+                self.conn
+                    .execute_batch("ROLLBACK TO stack_tx; RELEASE stack_tx")?;
+                Err(err)
+            }
+        }
+    }
+
+    /// Fails when applying `updates` would link branches into a cycle,
+    /// without changing anything.
+    pub fn check_parents_batch(&self, updates: &[ParentUpdate]) -> Result<()> {
         let existing = self.list_branches()?;
         let mut id_by_name: std::collections::HashMap<String, i64> =
             existing.iter().map(|b| (b.name.clone(), b.id)).collect();
@@ -201,40 +447,6 @@ impl Database {
                 cursor = parent_by_id.get(&current).copied().flatten();
             }
         }
-
-        let tx = self.conn.unchecked_transaction()?;
-        for update in updates {
-            tx.execute(
-                "INSERT INTO branches(name) VALUES (?1)
-                 ON CONFLICT(name) DO UPDATE SET updated_at = CURRENT_TIMESTAMP",
-                params![update.child_name],
-            )?;
-            if let Some(parent) = &update.parent_name {
-                tx.execute(
-                    "INSERT INTO branches(name) VALUES (?1)
-                     ON CONFLICT(name) DO UPDATE SET updated_at = CURRENT_TIMESTAMP",
-                    params![parent],
-                )?;
-            }
-        }
-
-        for update in updates {
-            if let Some(parent) = &update.parent_name {
-                tx.execute(
-                    "UPDATE branches
-                     SET parent_branch_id = (SELECT id FROM branches WHERE name = ?1),
-                         updated_at = CURRENT_TIMESTAMP
-                     WHERE name = ?2",
-                    params![parent, update.child_name],
-                )?;
-            } else {
-                tx.execute(
-                    "UPDATE branches SET parent_branch_id = NULL, updated_at = CURRENT_TIMESTAMP WHERE name = ?1",
-                    params![update.child_name],
-                )?;
-            }
-        }
-        tx.commit()?;
         Ok(())
     }
 
@@ -282,6 +494,95 @@ impl Database {
         Ok(())
     }
 
+    pub fn upsert_pr_cache_entry(&self, entry: &PrCacheEntry) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO pr_cache(branch, number, state, merge_commit_oid, base_ref_name, body, url, title, is_draft, fetched_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+             ON CONFLICT(branch) DO UPDATE SET
+                number = excluded.number,
+                state = excluded.state,
+                merge_commit_oid = excluded.merge_commit_oid,
+                base_ref_name = excluded.base_ref_name,
+                body = excluded.body,
+                url = excluded.url,
+                title = excluded.title,
+                is_draft = excluded.is_draft,
+                fetched_at = excluded.fetched_at",
+            params![
+                entry.branch,
+                entry.number,
+                entry.state,
+                entry.merge_commit_oid,
+                entry.base_ref_name,
+                entry.body,
+                entry.url,
+                entry.title,
+                entry.is_draft,
+                entry.fetched_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn pr_cache_entry(&self, branch: &str) -> Result<Option<PrCacheEntry>> {
+        self.conn
+            .query_row(
+                "SELECT branch, number, state, merge_commit_oid, base_ref_name, body, url, title, is_draft, fetched_at
+                 FROM pr_cache WHERE branch = ?1",
+                params![branch],
+                |row| {
+                    Ok(PrCacheEntry {
+                        branch: row.get(0)?,
+                        number: row.get(1)?,
+                        state: row.get(2)?,
+                        merge_commit_oid: row.get(3)?,
+                        base_ref_name: row.get(4)?,
+                        body: row.get(5)?,
+                        url: row.get(6)?,
+                        title: row.get(7)?,
+                        is_draft: row.get(8)?,
+                        fetched_at: row.get(9)?,
+                    })
+                },
+            )
+            .optional()
+            .map_err(Into::into)
+    }
+
+    pub fn list_pr_cache_entries(&self) -> Result<Vec<PrCacheEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT branch, number, state, merge_commit_oid, base_ref_name, body, url, title, is_draft, fetched_at
+             FROM pr_cache ORDER BY branch",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(PrCacheEntry {
+                branch: row.get(0)?,
+                number: row.get(1)?,
+                state: row.get(2)?,
+                merge_commit_oid: row.get(3)?,
+                base_ref_name: row.get(4)?,
+                body: row.get(5)?,
+                url: row.get(6)?,
+                title: row.get(7)?,
+                is_draft: row.get(8)?,
+                fetched_at: row.get(9)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    pub fn delete_pr_cache_entry(&self, branch: &str) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM pr_cache WHERE branch = ?1", params![branch])?;
+        Ok(())
+    }
+
+    pub fn invalidate_pr_cache_number(&self, number: i64) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM pr_cache WHERE number = ?1", params![number])?;
+        Ok(())
+    }
+
     pub fn clear_parent(&self, branch_name: &str) -> Result<()> {
         self.conn.execute(
             "UPDATE branches SET parent_branch_id = NULL, updated_at = CURRENT_TIMESTAMP WHERE name = ?1",
@@ -294,19 +595,81 @@ impl Database {
         self.conn.execute("UPDATE branches SET parent_branch_id = NULL WHERE parent_branch_id = (SELECT id FROM branches WHERE name = ?1)", params![branch_name])?;
         self.conn
             .execute("DELETE FROM branches WHERE name = ?1", params![branch_name])?;
+        self.delete_pr_cache_entry(branch_name)?;
         Ok(())
     }
 
+    /// Declares (or with `None` clears) the base branch of the stack rooted
+    /// at `root`.
+    pub fn set_stack_base(&self, root: &str, base: Option<&str>) -> Result<()> {
+        let root_id = self.upsert_branch(root)?;
+        self.conn.execute(
+            "UPDATE branches SET base_branch = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+            params![base, root_id],
+        )?;
+        Ok(())
+    }
+
+    /// Replaces the free-text annotations of a tracked branch; `None` clears
+    /// a field.
+    pub fn set_annotations(
+        &self,
+        branch_name: &str,
+        annotations: &BranchAnnotations,
+    ) -> Result<()> {
+        let updated = self.conn.execute(
+            "UPDATE branches
+             SET description = ?1, ticket = ?2, owner = ?3, updated_at = CURRENT_TIMESTAMP
+             WHERE name = ?4",
+            params![
+                annotations.description,
+                annotations.ticket,
+                annotations.owner,
+                branch_name
+            ],
+        )?;
+        if updated == 0 {
+            return Err(anyhow!("branch '{branch_name}' is not tracked"));
+        }
+        Ok(())
+    }
+
+    pub fn set_frozen(&self, branch_name: &str, frozen: bool) -> Result<()> {
+        let updated = self.conn.execute(
+            "UPDATE branches SET frozen = ?1, updated_at = CURRENT_TIMESTAMP WHERE name = ?2",
+            params![frozen, branch_name],
+        )?;
+        if updated == 0 {
+            return Err(anyhow!("branch '{branch_name}' is not tracked"));
+        }
+        Ok(())
+    }
+
+    /// `(root, base)` pairs for stack roots with a declared base branch.
+    pub fn stack_base_declarations(&self) -> Result<Vec<(String, String)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name, base_branch FROM branches WHERE base_branch IS NOT NULL ORDER BY name",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(Into::into)
+    }
+
     pub fn splice_out_branch(&self, branch_name: &str) -> Result<()> {
         let branch = self
             .branch_by_name(branch_name)?
             .ok_or_else(|| anyhow!("branch '{branch_name}' is not tracked"))?;
+        // Children of a stack root inherit its declared base.
         self.conn.execute(
-            "UPDATE branches SET parent_branch_id = ?1 WHERE parent_branch_id = ?2",
+            "UPDATE branches
+             SET parent_branch_id = ?1,
+                 base_branch = COALESCE(base_branch, (SELECT base_branch FROM branches WHERE id = ?2))
+             WHERE parent_branch_id = ?2",
             params![branch.parent_branch_id, branch.id],
         )?;
         self.conn
             .execute("DELETE FROM branches WHERE id = ?1", params![branch.id])?;
+        self.delete_pr_cache_entry(branch_name)?;
         Ok(())
     }
 
@@ -328,6 +691,81 @@ impl Database {
         )?;
         Ok(())
     }
+
+    pub fn record_sync_op(&self, run_id: i64, op: &SyncRunOpRecord) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO sync_run_ops(run_id, position, kind, branch, onto, details, status, duration_ms, before_sha, after_sha, error)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                run_id,
+                op.position,
+                op.kind,
+                op.branch,
+                op.onto,
+                op.details,
+                op.status,
+                op.duration_ms,
+                op.before_sha,
+                op.after_sha,
+                op.error
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// The most recent `limit` sync runs, newest first.
+    pub fn sync_runs(&self, limit: usize) -> Result<Vec<SyncRunRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, started_at, finished_at, status, summary_json
+             FROM sync_runs ORDER BY id DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit as i64], sync_run_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    pub fn sync_run(&self, id: i64) -> Result<Option<SyncRunRecord>> {
+        self.conn
+            .query_row(
+                "SELECT id, started_at, finished_at, status, summary_json
+                 FROM sync_runs WHERE id = ?1",
+                params![id],
+                sync_run_from_row,
+            )
+            .optional()
+            .map_err(Into::into)
+    }
+
+    pub fn sync_run_ops(&self, run_id: i64) -> Result<Vec<SyncRunOpRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT position, kind, branch, onto, details, status, duration_ms, before_sha, after_sha, error
+             FROM sync_run_ops WHERE run_id = ?1 ORDER BY position",
+        )?;
+        let rows = stmt.query_map(params![run_id], |row| {
+            Ok(SyncRunOpRecord {
+                position: row.get(0)?,
+                kind: row.get(1)?,
+                branch: row.get(2)?,
+                onto: row.get(3)?,
+                details: row.get(4)?,
+                status: row.get(5)?,
+                duration_ms: row.get(6)?,
+                before_sha: row.get(7)?,
+                after_sha: row.get(8)?,
+                error: row.get(9)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+}
+
+fn sync_run_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SyncRunRecord> {
+    Ok(SyncRunRecord {
+        id: row.get(0)?,
+        started_at: row.get(1)?,
+        finished_at: row.get(2)?,
+        status: row.get(3)?,
+        summary_json: row.get(4)?,
+    })
 }
 
 fn ensure_temp_id(
@@ -347,6 +785,54 @@ fn ensure_temp_id(
     }
 }
 
+/// Highest applied migration; 0 for new and pre-versioning databases.
+fn schema_version(conn: &Connection) -> Result<i64> {
+    if !table_exists(conn, "schema_migrations")? {
+        return Ok(0);
+    }
+    Ok(conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+        [],
+        |row| row.get(0),
+    )?)
+}
+
+fn applied_migrations(conn: &Connection) -> Result<Vec<MigrationRecord>> {
+    if !table_exists(conn, "schema_migrations")? {
+        return Ok(Vec::new());
+    }
+    let mut stmt = conn.prepare(
+        "SELECT version, description, applied_at FROM schema_migrations ORDER BY version",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(MigrationRecord {
+            version: row.get(0)?,
+            description: row.get(1)?,
+            applied_at: row.get(2)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+fn table_exists(conn: &Connection, table: &str) -> Result<bool> {
+    let mut stmt =
+        conn.prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1")?;
+    Ok(stmt.exists(params![table])?)
+}
+
+fn ensure_column(conn: &Connection, table: &str, column: &str, decl: &str) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT 1 FROM pragma_table_info('{table}') WHERE name = ?1"
+    ))?;
+    if !stmt.exists(params![column])? {
+        conn.execute(
+            &format!("ALTER TABLE {table} ADD COLUMN {column} {decl}"),
+            [],
+        )?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -360,6 +846,33 @@ mod tests {
         assert!(err.to_string().contains("cycle"));
     }
 
+    #[test]
+    fn transaction_rolls_back_every_write_when_it_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::open(&dir.path().join("stack.db")).unwrap();
+        db.set_base_branch_if_missing("main").unwrap();
+        db.set_parent("a", Some("main")).unwrap();
+
+        let err = db
+            .transaction(|| {
+                db.set_base_branch("develop")?;
+                db.delete_branch("main")?;
+                db.set_parents_batch(&[ParentUpdate {
+                    child_name: "a".to_string(),
+                    parent_name: Some("develop".to_string()),
+                }])?;
+                Err::<(), _>(anyhow!("boom"))
+            })
+            .unwrap_err();
+        assert_eq!(err.to_string(), "boom");
+
+        assert_eq!(db.repo_meta().unwrap().base_branch, "main");
+        let a = db.branch_by_name("a").unwrap().unwrap();
+        let main = db.branch_by_name("main").unwrap().unwrap();
+        assert_eq!(a.parent_branch_id, Some(main.id));
+        assert!(db.branch_by_name("develop").unwrap().is_none());
+    }
+
     #[test]
     fn splice_out_branch_relinks_children_to_parent() {
         let dir = tempfile::tempdir().unwrap();
@@ -374,6 +887,33 @@ mod tests {
         assert!(db.branch_by_name("a").unwrap().is_none());
     }
 
+    #[test]
+    fn pr_cache_entry_round_trips_and_is_dropped_with_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::open(&dir.path().join("stack.db")).unwrap();
+        db.set_parent("a", Some("main")).unwrap();
+        db.upsert_pr_cache_entry(&PrCacheEntry {
+            branch: "a".to_string(),
+            number: Some(7),
+            state: Some("open".to_string()),
+            merge_commit_oid: None,
+            base_ref_name: Some("main".to_string()),
+            body: None,
+            url: None,
+            title: None,
+            is_draft: false,
+            fetched_at: 100,
+        })
+        .unwrap();
+
+        let entry = db.pr_cache_entry("a").unwrap().unwrap();
+        assert_eq!(entry.number, Some(7));
+        assert_eq!(entry.fetched_at, 100);
+
+        db.splice_out_branch("a").unwrap();
+        assert!(db.pr_cache_entry("a").unwrap().is_none());
+    }
+
     #[test]
     fn set_parents_batch_rejects_cycles() {
         let dir = tempfile::tempdir().unwrap();
@@ -395,4 +935,42 @@ mod tests {
             .unwrap_err();
         assert!(err.to_string().contains("cycle"));
     }
+
+    #[test]
+    fn migrate_upgrades_unversioned_databases_and_refuses_newer_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stack.db");
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE repo_meta (id INTEGER PRIMARY KEY CHECK (id = 1), base_branch TEXT NOT NULL, schema_version INTEGER NOT NULL);
+                 INSERT INTO repo_meta VALUES (1, 'main', 1);
+                 CREATE TABLE pr_cache (branch TEXT PRIMARY KEY, number INTEGER NULL, state TEXT NULL, merge_commit_oid TEXT NULL, base_ref_name TEXT NULL, body TEXT NULL, url TEXT NULL, title TEXT NULL, fetched_at INTEGER NOT NULL);",
+            )
+            .unwrap();
+        assert_eq!(Database::inspect(&path).unwrap().version, 0);
+
+        let db = Database::open(&path).unwrap();
+        assert_eq!(db.repo_meta().unwrap().base_branch, "main");
+        drop(db);
+        let info = Database::inspect(&path).unwrap();
+        assert_eq!(info.version, latest_version());
+        assert_eq!(info.applied.len(), MIGRATIONS.len());
+        assert!(info.pending.is_empty());
+        let conn = Connection::open(&path).unwrap();
+        let schema_version: i64 = conn
+            .query_row("SELECT schema_version FROM repo_meta", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(schema_version, latest_version());
+
+        conn.execute(
+            "INSERT INTO schema_migrations(version, description) VALUES (?1, 'future')",
+            params![latest_version() + 1],
+        )
+        .unwrap();
+        let err = Database::open(&path)
+            .err()
+            .expect("newer schema is refused");
+        assert!(err.to_string().contains("newer than this stack supports"));
+    }
 }
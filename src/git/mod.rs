@@ -1,12 +1,31 @@
 use std::io::Write;
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
+use std::sync::OnceLock;
 
 use anyhow::{Context, Result, anyhow};
 
+use crate::util::ssh_config::SshConfig;
+use crate::util::url::{GITHUB_HOST, HostMap, web_url_host};
+
 #[derive(Debug, Clone)]
 pub struct Git {
     root: PathBuf,
+    host_map: HostMap,
+    ssh_config: OnceLock<SshConfig>,
+    remotes: RemoteSettings,
+}
+
+/// Explicit remote and PR repository settings that take precedence over the
+/// `upstream`/`origin` heuristics.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemoteSettings {
+    /// `stack.pushRemote`: where stacked branches are pushed.
+    pub push_remote: Option<String>,
+    /// `stack.baseRemote`: where the base branch is fetched from.
+    pub base_remote: Option<String>,
+    /// `stack.prRepo`: the `owner/name` repository PRs are opened against.
+    pub pr_repo: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -14,6 +33,12 @@ pub struct StashHandle {
     pub reference: String,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitMessage {
+    pub subject: String,
+    pub body: String,
+}
+
 impl Git {
     pub fn discover() -> Result<Self> {
         let output = Command::new("git")
@@ -26,9 +51,27 @@ impl Git {
         let root = String::from_utf8(output.stdout)?.trim().to_string();
         Ok(Self {
             root: PathBuf::from(root),
+            host_map: HostMap::default(),
+            ssh_config: OnceLock::new(),
+            remotes: RemoteSettings::default(),
         })
     }
 
+    /// Applies `host_map` to every web URL derived from a remote.
+    pub fn with_host_map(mut self, host_map: HostMap) -> Self {
+        self.host_map = host_map;
+        self
+    }
+
+    pub fn with_remote_settings(mut self, remotes: RemoteSettings) -> Self {
+        self.remotes = remotes;
+        self
+    }
+
+    pub fn remote_settings(&self) -> &RemoteSettings {
+        &self.remotes
+    }
+
     pub fn root(&self) -> &PathBuf {
         &self.root
     }
@@ -145,6 +188,9 @@ impl Git {
     }
 
     pub fn preferred_sync_remote(&self, base_remote: &str) -> Result<String> {
+        if let Some(remote) = &self.remotes.base_remote {
+            return Ok(remote.clone());
+        }
         if self.has_remote("upstream")? {
             return Ok("upstream".to_string());
         }
@@ -167,23 +213,60 @@ impl Git {
         Ok("main".to_string())
     }
 
+    /// Web URL of `remote`, which may be a remote name or a URL (as allowed
+    /// in `branch.<name>.remote`). `git remote get-url` already expands
+    /// `url.<base>.insteadOf` for named remotes; URLs are rewritten here.
+    /// SSH host aliases resolve through the OpenSSH client config before
+    /// `stack.hostMapping` applies.
     pub fn remote_web_url(&self, remote: &str) -> Result<Option<String>> {
         let output = Command::new("git")
             .current_dir(&self.root)
             .args(["remote", "get-url", remote])
             .output()
             .with_context(|| format!("failed to read {remote} remote URL"))?;
-        if !output.status.success() {
+        let raw = if output.status.success() {
+            String::from_utf8(output.stdout)?.trim().to_string()
+        } else if remote.contains(':') {
+            rewrite_url(remote, &self.url_rewrites()?)
+        } else {
             return Ok(None);
-        }
-        let raw = String::from_utf8(output.stdout)?.trim().to_string();
+        };
         if raw.is_empty() {
             return Ok(None);
         }
-        Ok(parse_remote_to_web_url(&raw))
+        let ssh_config = self.ssh_config.get_or_init(SshConfig::load);
+        Ok(parse_remote_to_web_url(&raw, ssh_config).map(|url| self.host_map.apply(&url)))
+    }
+
+    /// `(base, prefix)` pairs from `url.<base>.insteadOf = <prefix>`.
+    fn url_rewrites(&self) -> Result<Vec<(String, String)>> {
+        let output = Command::new("git")
+            .current_dir(&self.root)
+            .args(["config", "--get-regexp", r"^url\..*\.insteadof$"])
+            .output()
+            .context("failed to read url.<base>.insteadOf")?;
+        if !output.status.success() {
+            return Ok(Vec::new());
+        }
+        Ok(String::from_utf8(output.stdout)?
+            .lines()
+            .filter_map(|line| {
+                let (key, prefix) = line.split_once(' ')?;
+                let base = key.strip_prefix("url.")?.strip_suffix(".insteadof")?;
+                Some((base.to_string(), prefix.to_string()))
+            })
+            .collect())
     }
 
     pub fn remote_for_branch(&self, branch: &str) -> Result<Option<String>> {
+        Ok(Some(
+            self.tracked_remote(branch)?
+                .unwrap_or_else(|| "origin".to_string()),
+        ))
+    }
+
+    /// Remote from `branch.<name>.remote` or the upstream ref, if either is set.
+    fn tracked_remote(&self, branch: &str) -> Result<Option<String>> {
         let config_key = format!("branch.{branch}.remote");
         let output = Command::new("git")
             .current_dir(&self.root)
@@ -212,15 +295,97 @@ impl Git {
             return Ok(Some(remote.to_string()));
         }
 
-        Ok(Some("origin".to_string()))
+        Ok(None)
+    }
+
+    pub fn config_value(&self, key: &str) -> Result<Option<String>> {
+        let output = Command::new("git")
+            .current_dir(&self.root)
+            .args(["config", "--get", key])
+            .output()
+            .with_context(|| format!("failed to read {key}"))?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let value = String::from_utf8(output.stdout)?.trim().to_string();
+        if value.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(value))
+    }
+
+    pub fn config_values(&self, key: &str) -> Result<Vec<String>> {
+        let output = Command::new("git")
+            .current_dir(&self.root)
+            .args(["config", "--get-all", key])
+            .output()
+            .with_context(|| format!("failed to read {key}"))?;
+        if !output.status.success() {
+            return Ok(Vec::new());
+        }
+        Ok(String::from_utf8(output.stdout)?
+            .lines()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+            .collect())
     }
 
     pub fn base_remote_for_stack(&self, base_branch: &str) -> Result<String> {
+        if let Some(remote) = &self.remotes.base_remote {
+            return Ok(remote.clone());
+        }
         Ok(self
             .remote_for_branch(base_branch)?
             .unwrap_or_else(|| "origin".to_string()))
     }
 
+    /// Remote `branch` is known to push to: `stack.pushRemote` when set,
+    /// otherwise the branch's own remote.
+    pub fn configured_push_remote(&self, branch: &str) -> Result<Option<String>> {
+        if let Some(remote) = &self.remotes.push_remote {
+            return Ok(Some(remote.clone()));
+        }
+        self.tracked_remote(branch)
+    }
+
+    /// Remote for branches with no remote of their own: `origin` (the fork,
+    /// when `stack.baseRemote` names the canonical repository), otherwise
+    /// `stack.baseRemote`.
+    pub fn default_push_remote(&self) -> Result<Option<String>> {
+        if self.has_remote("origin")? {
+            return Ok(Some("origin".to_string()));
+        }
+        Ok(self.remotes.base_remote.clone())
+    }
+
+    /// Remote `branch` is pushed to, falling back to [`Self::default_push_remote`]
+    /// and then to the remote of the stack's base branch (e.g. `upstream` in a
+    /// clone with no `origin`).
+    pub fn push_remote_for_branch(&self, branch: &str, base_branch: &str) -> Result<String> {
+        if let Some(remote) = self.configured_push_remote(branch)? {
+            return Ok(remote);
+        }
+        match self.default_push_remote()? {
+            Some(remote) => Ok(remote),
+            None => self.base_remote_for_stack(base_branch),
+        }
+    }
+
+    /// Web URL of the repository PRs target: `stack.prRepo` on `remote`'s
+    /// host when configured, otherwise `remote`'s own web URL.
+    pub fn pr_repo_web_url(&self, remote: &str) -> Result<Option<String>> {
+        let remote_url = self.remote_web_url(remote)?;
+        let Some(repo) = &self.remotes.pr_repo else {
+            return Ok(remote_url);
+        };
+        let host = remote_url
+            .as_deref()
+            .and_then(web_url_host)
+            .unwrap_or(GITHUB_HOST);
+        Ok(Some(format!("https://{host}/{repo}")))
+    }
+
     pub fn supports_replay(&self) -> bool {
         Command::new("git")
             .current_dir(&self.root)
@@ -244,12 +409,33 @@ impl Git {
         Ok(output.status.success())
     }
 
+    /// Remote-tracking ref `branch` follows: its configured upstream, or
+    /// `<remote>/<branch>` on its remote when that ref exists.
+    pub fn remote_tracking_ref(&self, branch: &str) -> Result<Option<String>> {
+        let upstream = self
+            .capture([
+                "for-each-ref",
+                "--format=%(upstream:short)",
+                &format!("refs/heads/{branch}"),
+            ])
+            .unwrap_or_default();
+        let upstream = upstream.trim();
+        if !upstream.is_empty() && self.ref_exists(upstream)? {
+            return Ok(Some(upstream.to_string()));
+        }
+        let remote = self
+            .remote_for_branch(branch)?
+            .unwrap_or_else(|| "origin".to_string());
+        let candidate = format!("{remote}/{branch}");
+        Ok(self.ref_exists(&candidate)?.then_some(candidate))
+    }
+
     pub fn fast_forward_branch(&self, branch: &str, onto: &str) -> Result<()> {
         self.run(["checkout", branch])?;
         self.run(["merge", "--ff-only", onto])
     }
 
-    fn has_remote(&self, name: &str) -> Result<bool> {
+    pub fn has_remote(&self, name: &str) -> Result<bool> {
         let output = Command::new("git")
             .current_dir(&self.root)
             .args(["remote"])
@@ -312,6 +498,10 @@ impl Git {
         self.run(["rebase", "--onto", new_base, old_base, branch])
     }
 
+    pub fn abort_rebase(&self) -> Result<()> {
+        self.run(["rebase", "--abort"])
+    }
+
     pub fn merge_base(&self, branch: &str, onto: &str) -> Result<String> {
         self.capture(["merge-base", branch, onto])
             .map(|s| s.trim().to_string())
@@ -335,6 +525,126 @@ impl Git {
         Ok(count)
     }
 
+    /// Messages of the commits in `base..head`, oldest first.
+    pub fn commit_messages(&self, base: &str, head: &str) -> Result<Vec<CommitMessage>> {
+        let out = self.capture([
+            "log",
+            "--reverse",
+            "--format=%s%x1f%b%x1e",
+            &format!("{base}..{head}"),
+        ])?;
+        Ok(out
+            .split('\x1e')
+            .filter_map(|raw| {
+                let (subject, body) = raw.trim_start_matches('\n').split_once('\x1f')?;
+                Some(CommitMessage {
+                    subject: subject.trim().to_string(),
+                    body: body.trim().to_string(),
+                })
+            })
+            .collect())
+    }
+
+    /// Paths that differ between `base` and `head`.
+    pub fn changed_files(&self, base: &str, head: &str) -> Result<Vec<String>> {
+        let out = self.capture(["diff", "--name-only", "-z", base, head])?;
+        Ok(out
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// The editor git would use (`GIT_EDITOR`, `core.editor`, `$VISUAL`,
+    /// `$EDITOR`, then `vi`).
+    pub fn editor(&self) -> Result<String> {
+        self.capture(["var", "GIT_EDITOR"])
+            .map(|s| s.trim().to_string())
+    }
+
+    /// Commit of `rev`, or `None` when it does not resolve.
+    pub fn resolve_commit(&self, rev: &str) -> Result<Option<String>> {
+        let output = Command::new("git")
+            .current_dir(&self.root)
+            .args([
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("{rev}^{{commit}}"),
+            ])
+            .output()
+            .with_context(|| format!("failed to resolve {rev}"))?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        Ok(Some(String::from_utf8(output.stdout)?.trim().to_string()))
+    }
+
+    /// Contents of `path` in the tree of `rev`.
+    pub fn file_at(&self, rev: &str, path: &str) -> Result<String> {
+        self.capture(["show", &format!("{rev}:{path}")])
+    }
+
+    /// Writes a commit whose tree holds only `path` with `contents`, without
+    /// touching the index or working tree.
+    pub fn commit_file(
+        &self,
+        path: &str,
+        contents: &str,
+        parents: &[&str],
+        message: &str,
+    ) -> Result<String> {
+        let blob = self.capture_with_stdin(&["hash-object", "-w", "--stdin"], contents)?;
+        let tree = self.capture_with_stdin(
+            &["mktree"],
+            &format!("100644 blob {}\t{path}\n", blob.trim()),
+        )?;
+        let mut args = vec!["commit-tree", tree.trim(), "-m", message];
+        for parent in parents {
+            args.extend(["-p", parent]);
+        }
+        Ok(self.capture_with_stdin(&args, "")?.trim().to_string())
+    }
+
+    pub fn update_ref(&self, name: &str, commit: &str) -> Result<()> {
+        self.run(["update-ref", name, commit])
+    }
+
+    /// Whether `remote` advertises `refname`.
+    pub fn remote_has_ref(&self, remote: &str, refname: &str) -> Result<bool> {
+        Ok(!self
+            .capture(["ls-remote", remote, refname])?
+            .trim()
+            .is_empty())
+    }
+
+    fn capture_with_stdin(&self, args: &[&str], input: &str) -> Result<String> {
+        let mut child = Command::new("git")
+            .current_dir(&self.root)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to run git {:?}", args))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(input.as_bytes())
+                .with_context(|| format!("failed to write input to git {:?}", args))?;
+        }
+        let output = child
+            .wait_with_output()
+            .with_context(|| format!("failed to run git {:?}", args))?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "git command failed {:?}: {}",
+                args,
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(String::from_utf8(output.stdout)?)
+    }
+
     pub fn capture<const N: usize>(&self, args: [&str; N]) -> Result<String> {
         let output = Command::new("git")
             .current_dir(&self.root)
@@ -368,36 +678,64 @@ impl Git {
     }
 }
 
-fn parse_remote_to_web_url(raw: &str) -> Option<String> {
-    if let Some(rest) = raw.strip_prefix("git@")
-        && let Some((host, repo)) = rest.split_once(':')
-    {
+/// Applies the longest matching `insteadOf` prefix, as git does.
+fn rewrite_url(url: &str, rewrites: &[(String, String)]) -> String {
+    rewrites
+        .iter()
+        .filter(|(_, prefix)| url.starts_with(prefix.as_str()))
+        .max_by_key(|(_, prefix)| prefix.len())
+        .map_or_else(
+            || url.to_string(),
+            |(base, prefix)| format!("{base}{}", &url[prefix.len()..]),
+        )
+}
+
+/// Derives the web URL of a remote from its `https://`, `ssh://` or
+/// scp-style (`[user@]host:path`) URL. SSH users and ports are dropped, as is
+/// any userinfo on HTTP URLs, and SSH hosts go through `ssh_config` aliases.
+fn parse_remote_to_web_url(raw: &str, ssh_config: &SshConfig) -> Option<String> {
+    if let Some((scheme @ ("https" | "http"), rest)) = raw.split_once("://") {
+        let rest = strip_userinfo(rest);
         return Some(sanitize_terminal_text(&format!(
-            "https://{}/{}",
-            host.trim_end_matches('/'),
-            repo.trim_end_matches(".git")
+            "{scheme}://{}",
+            rest.trim_end_matches(".git").trim_end_matches('/').trim()
         )));
     }
 
-    if let Some(rest) = raw.strip_prefix("ssh://git@")
-        && let Some((host, repo)) = rest.split_once('/')
-    {
-        return Some(sanitize_terminal_text(&format!(
-            "https://{}/{}",
-            host.trim_end_matches('/'),
-            repo.trim_end_matches(".git")
-        )));
+    if let Some(rest) = raw.strip_prefix("ssh://") {
+        let (authority, repo) = strip_userinfo(rest).split_once('/')?;
+        let host = authority.split(':').next().unwrap_or(authority);
+        return ssh_web_url(&ssh_config.hostname(host), repo);
     }
 
-    if raw.starts_with("https://") || raw.starts_with("http://") {
-        return Some(sanitize_terminal_text(
-            raw.trim_end_matches(".git").trim_end_matches('/').trim(),
-        ));
+    if !raw.contains("://")
+        && let Some((authority, repo)) = raw.split_once(':')
+    {
+        let host = authority.rsplit('@').next().unwrap_or(authority);
+        return ssh_web_url(&ssh_config.hostname(host), repo);
     }
 
     None
 }
 
+fn strip_userinfo(rest: &str) -> &str {
+    match rest.split_once('@') {
+        Some((userinfo, host_and_path)) if !userinfo.contains('/') => host_and_path,
+        _ => rest,
+    }
+}
+
+fn ssh_web_url(host: &str, repo: &str) -> Option<String> {
+    let repo = repo.trim_start_matches('/');
+    if host.is_empty() || host.contains('/') || repo.is_empty() {
+        return None;
+    }
+    Some(sanitize_terminal_text(&format!(
+        "https://{host}/{}",
+        repo.trim_end_matches(".git").trim_end_matches('/')
+    )))
+}
+
 fn sanitize_terminal_text(value: &str) -> String {
     value.chars().filter(|c| !c.is_control()).collect()
 }
@@ -408,15 +746,82 @@ mod tests {
 
     #[test]
     fn parse_remote_to_web_url_strips_control_characters() {
-        let parsed = parse_remote_to_web_url("https://github.com/acme/repo\u{1b}[31m")
-            .expect("url should parse");
+        let parsed = parse_remote_to_web_url(
+            "https://github.com/acme/repo\u{1b}[31m",
+            &SshConfig::default(),
+        )
+        .expect("url should parse");
         assert_eq!(parsed, "https://github.com/acme/repo[31m");
     }
 
     #[test]
     fn parse_remote_to_web_url_normalizes_git_ssh_remote() {
-        let parsed =
-            parse_remote_to_web_url("git@github.com:acme/repo.git").expect("url should parse");
+        let parsed = parse_remote_to_web_url("git@github.com:acme/repo.git", &SshConfig::default())
+            .expect("url should parse");
         assert_eq!(parsed, "https://github.com/acme/repo");
     }
+
+    #[test]
+    fn parse_remote_to_web_url_drops_ssh_users_ports_and_userinfo() {
+        for (raw, expected) in [
+            (
+                "ssh://git@git.corp.example:7999/acme/repo.git",
+                "https://git.corp.example/acme/repo",
+            ),
+            (
+                "ssh://git.corp.example/acme/repo",
+                "https://git.corp.example/acme/repo",
+            ),
+            (
+                "deploy@git.corp.example:acme/repo.git",
+                "https://git.corp.example/acme/repo",
+            ),
+            (
+                "https://token@github.corp.example/acme/repo.git",
+                "https://github.corp.example/acme/repo",
+            ),
+        ] {
+            assert_eq!(
+                parse_remote_to_web_url(raw, &SshConfig::default()).as_deref(),
+                Some(expected)
+            );
+        }
+        assert_eq!(
+            parse_remote_to_web_url("/srv/git/repo.git", &SshConfig::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_remote_to_web_url_resolves_ssh_host_aliases() {
+        let ssh_config = SshConfig::parse(
+            "Host github-work\n  HostName github.com\n",
+            std::path::Path::new("/nonexistent"),
+        );
+        assert_eq!(
+            parse_remote_to_web_url("git@github-work:acme/repo.git", &ssh_config).as_deref(),
+            Some("https://github.com/acme/repo")
+        );
+        assert_eq!(
+            parse_remote_to_web_url("ssh://git@github-work:22/acme/repo", &ssh_config).as_deref(),
+            Some("https://github.com/acme/repo")
+        );
+    }
+
+    #[test]
+    fn rewrite_url_applies_longest_instead_of_prefix() {
+        let rewrites = vec![
+            ("git@github.com:".to_string(), "gh:".to_string()),
+            ("git@github-work:".to_string(), "gh:acme/".to_string()),
+        ];
+        assert_eq!(
+            rewrite_url("gh:acme/repo", &rewrites),
+            "git@github-work:repo"
+        );
+        assert_eq!(
+            rewrite_url("gh:other/repo", &rewrites),
+            "git@github.com:other/repo"
+        );
+        assert_eq!(rewrite_url("https://x/y", &rewrites), "https://x/y");
+    }
 }
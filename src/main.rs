@@ -1,6 +1,7 @@
 mod app;
 mod args;
 mod commands;
+mod config;
 mod core;
 mod db;
 mod git;
@@ -0,0 +1,373 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Result, anyhow};
+
+use super::{
+    CommitStatus, MergeMethod, NewPr, PrComment, PrInfo, PrMergeStatus, PrMetadata, PrState,
+    Provider, ProviderUnavailable,
+};
+use crate::db::{Database, PrCacheEntry};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrDataFreshness {
+    Live,
+    Cached { age_secs: i64 },
+    Stale { age_secs: Option<i64> },
+}
+
+impl PrDataFreshness {
+    pub fn describe(self) -> String {
+        match self {
+            PrDataFreshness::Live => "fresh (fetched live)".to_string(),
+            PrDataFreshness::Cached { age_secs } => {
+                format!("fresh (cached {} ago)", format_age(age_secs))
+            }
+            PrDataFreshness::Stale {
+                age_secs: Some(age_secs),
+            } => format!("stale (cached {} ago)", format_age(age_secs)),
+            PrDataFreshness::Stale { age_secs: None } => "stale (nothing cached)".to_string(),
+        }
+    }
+
+    fn combine(self, other: PrDataFreshness) -> PrDataFreshness {
+        use PrDataFreshness::*;
+        match (self, other) {
+            (Stale { age_secs: a }, Stale { age_secs: b }) => Stale {
+                age_secs: a.zip(b).map(|(a, b)| a.max(b)),
+            },
+            (stale @ Stale { .. }, _) | (_, stale @ Stale { .. }) => stale,
+            (Cached { age_secs: a }, Cached { age_secs: b }) => Cached { age_secs: a.max(b) },
+            (cached @ Cached { .. }, Live) | (Live, cached @ Cached { .. }) => cached,
+            (Live, Live) => Live,
+        }
+    }
+}
+
+/// Wraps a live provider with the `pr_cache` table so PR lookups can be
+/// served from recent data or, when offline, from whatever was last fetched.
+pub struct CachedProvider<'a> {
+    inner: &'a dyn Provider,
+    db: &'a Database,
+    ttl_secs: i64,
+    offline: bool,
+    freshness: Cell<Option<PrDataFreshness>>,
+    mutated: RefCell<HashSet<i64>>,
+}
+
+impl<'a> CachedProvider<'a> {
+    pub fn new(inner: &'a dyn Provider, db: &'a Database, ttl_secs: u64, offline: bool) -> Self {
+        Self {
+            inner,
+            db,
+            ttl_secs: i64::try_from(ttl_secs).unwrap_or(i64::MAX),
+            offline,
+            freshness: Cell::new(None),
+            mutated: RefCell::new(HashSet::new()),
+        }
+    }
+
+    /// Freshness of the PR data served so far, or `None` if nothing was read.
+    pub fn freshness(&self) -> Option<PrDataFreshness> {
+        self.freshness.get()
+    }
+
+    /// Re-fetches PR metadata for `branches` from the live provider,
+    /// ignoring the TTL, and replaces the cached entries.
+    pub fn refresh(&self, branches: &[(&str, Option<i64>)]) -> Result<HashMap<String, PrInfo>> {
+        if self.offline {
+            return Err(anyhow!("cannot refresh PR metadata in offline mode"));
+        }
+        let prs = self.inner.resolve_prs_by_head(branches)?;
+        self.store(branches, &prs, now_secs())?;
+        self.note(PrDataFreshness::Live);
+        Ok(prs)
+    }
+
+    fn resolve_with(
+        &self,
+        branches: &[(&str, Option<i64>)],
+        fetch: impl FnOnce(&[(&str, Option<i64>)]) -> Result<HashMap<String, PrInfo>>,
+    ) -> Result<HashMap<String, PrInfo>> {
+        let now = now_secs();
+        if self.offline {
+            return self.serve_stale(branches, now);
+        }
+
+        let mut out = HashMap::new();
+        let mut pending = Vec::new();
+        for &(branch, cached_number) in branches {
+            match self.cached_entry(branch)? {
+                Some(entry) if self.ttl_secs > 0 && now - entry.fetched_at < self.ttl_secs => {
+                    self.note(PrDataFreshness::Cached {
+                        age_secs: now - entry.fetched_at,
+                    });
+                    if let Some(pr) = entry_to_pr(&entry) {
+                        out.insert(branch.to_string(), pr);
+                    }
+                }
+                _ => pending.push((branch, cached_number)),
+            }
+        }
+        if pending.is_empty() {
+            return Ok(out);
+        }
+
+        match fetch(&pending) {
+            Ok(prs) => {
+                self.store(&pending, &prs, now)?;
+                self.note(PrDataFreshness::Live);
+                out.extend(prs);
+            }
+            Err(err) if err.is::<ProviderUnavailable>() => {
+                out.extend(self.serve_stale(&pending, now)?);
+            }
+            Err(err) => return Err(err),
+        }
+        Ok(out)
+    }
+
+    fn serve_stale(
+        &self,
+        branches: &[(&str, Option<i64>)],
+        now: i64,
+    ) -> Result<HashMap<String, PrInfo>> {
+        let mut out = HashMap::new();
+        for &(branch, _) in branches {
+            let entry = self.cached_entry(branch)?;
+            self.note(PrDataFreshness::Stale {
+                age_secs: entry.as_ref().map(|e| now - e.fetched_at),
+            });
+            if let Some(pr) = entry.as_ref().and_then(entry_to_pr) {
+                out.insert(branch.to_string(), pr);
+            }
+        }
+        Ok(out)
+    }
+
+    fn cached_entry(&self, branch: &str) -> Result<Option<PrCacheEntry>> {
+        let entry = self.db.pr_cache_entry(branch)?;
+        let mutated = self.mutated.borrow();
+        Ok(entry.filter(|e| e.number.is_none_or(|n| !mutated.contains(&n))))
+    }
+
+    fn store(
+        &self,
+        branches: &[(&str, Option<i64>)],
+        prs: &HashMap<String, PrInfo>,
+        now: i64,
+    ) -> Result<()> {
+        for &(branch, _) in branches {
+            let pr = prs.get(branch);
+            self.db.upsert_pr_cache_entry(&PrCacheEntry {
+                branch: branch.to_string(),
+                number: pr.map(|p| p.number),
+                state: pr.map(|p| p.state.as_str().to_string()),
+                merge_commit_oid: pr.and_then(|p| p.merge_commit_oid.clone()),
+                base_ref_name: pr.and_then(|p| p.base_ref_name.clone()),
+                body: pr.and_then(|p| p.body.clone()),
+                url: pr.and_then(|p| p.url.clone()),
+                title: pr.and_then(|p| p.title.clone()),
+                is_draft: pr.is_some_and(|p| p.is_draft),
+                fetched_at: now,
+            })?;
+        }
+        Ok(())
+    }
+
+    fn note(&self, observed: PrDataFreshness) {
+        let next = match self.freshness.get() {
+            Some(current) => current.combine(observed),
+            None => observed,
+        };
+        self.freshness.set(Some(next));
+    }
+
+    fn ensure_online(&self, action: &str) -> Result<()> {
+        if self.offline {
+            return Err(anyhow!("offline mode: cannot {action}"));
+        }
+        Ok(())
+    }
+
+    fn invalidate(&self, pr_number: i64) -> Result<()> {
+        self.mutated.borrow_mut().insert(pr_number);
+        self.db.invalidate_pr_cache_number(pr_number)
+    }
+}
+
+impl Provider for CachedProvider<'_> {
+    fn resolve_pr_by_head(
+        &self,
+        branch: &str,
+        cached_number: Option<i64>,
+    ) -> Result<Option<PrInfo>> {
+        let mut prs = self.resolve_with(&[(branch, cached_number)], |pending| {
+            let mut out = HashMap::new();
+            if let Some(pr) = self.inner.resolve_pr_by_head(pending[0].0, pending[0].1)? {
+                out.insert(pending[0].0.to_string(), pr);
+            }
+            Ok(out)
+        })?;
+        Ok(prs.remove(branch))
+    }
+
+    fn resolve_prs_by_head(
+        &self,
+        branches: &[(&str, Option<i64>)],
+    ) -> Result<HashMap<String, PrInfo>> {
+        self.resolve_with(branches, |pending| self.inner.resolve_prs_by_head(pending))
+    }
+
+    fn update_pr_body(&self, pr_number: i64, body: &str) -> Result<()> {
+        self.ensure_online(&format!("update body of PR #{pr_number}"))?;
+        self.inner.update_pr_body(pr_number, body)?;
+        self.invalidate(pr_number)
+    }
+
+    fn update_pr_title(&self, pr_number: i64, title: &str) -> Result<()> {
+        self.ensure_online(&format!("update title of PR #{pr_number}"))?;
+        self.inner.update_pr_title(pr_number, title)?;
+        self.invalidate(pr_number)
+    }
+
+    fn update_pr_base(&self, pr_number: i64, base: &str) -> Result<()> {
+        self.ensure_online(&format!("retarget PR #{pr_number}"))?;
+        self.inner.update_pr_base(pr_number, base)?;
+        self.invalidate(pr_number)
+    }
+
+    fn pr_merge_status(&self, pr_number: i64) -> Result<PrMergeStatus> {
+        self.ensure_online(&format!("check merge status of PR #{pr_number}"))?;
+        self.inner.pr_merge_status(pr_number)
+    }
+
+    fn merge_pr(&self, pr_number: i64, method: MergeMethod) -> Result<()> {
+        self.ensure_online(&format!("merge PR #{pr_number}"))?;
+        self.inner.merge_pr(pr_number, method)?;
+        self.invalidate(pr_number)
+    }
+
+    fn delete_pr(&self, pr_number: i64) -> Result<()> {
+        self.ensure_online(&format!("close PR #{pr_number}"))?;
+        self.inner.delete_pr(pr_number)?;
+        self.invalidate(pr_number)
+    }
+
+    fn set_pr_draft(&self, pr_number: i64, draft: bool) -> Result<()> {
+        let action = if draft { "convert" } else { "mark" };
+        self.ensure_online(&format!("{action} PR #{pr_number}"))?;
+        self.inner.set_pr_draft(pr_number, draft)?;
+        self.invalidate(pr_number)
+    }
+
+    fn close_pr(&self, pr_number: i64) -> Result<()> {
+        self.ensure_online(&format!("close PR #{pr_number}"))?;
+        self.inner.close_pr(pr_number)?;
+        self.invalidate(pr_number)
+    }
+
+    fn reopen_pr(&self, pr_number: i64) -> Result<()> {
+        self.ensure_online(&format!("reopen PR #{pr_number}"))?;
+        self.inner.reopen_pr(pr_number)?;
+        self.invalidate(pr_number)
+    }
+
+    fn create_pr(&self, pr: &NewPr<'_>) -> Result<PrInfo> {
+        self.ensure_online(&format!("create a PR for '{}'", pr.branch))?;
+        let created = self.inner.create_pr(pr)?;
+        let prs = HashMap::from([(pr.branch.to_string(), created.clone())]);
+        self.store(&[(pr.branch, Some(created.number))], &prs, now_secs())?;
+        Ok(created)
+    }
+
+    fn pr_metadata(&self, pr_number: i64) -> Result<PrMetadata> {
+        self.ensure_online(&format!("read metadata of PR #{pr_number}"))?;
+        self.inner.pr_metadata(pr_number)
+    }
+
+    fn list_pr_comments(&self, pr_number: i64) -> Result<Vec<PrComment>> {
+        self.ensure_online(&format!("list comments on PR #{pr_number}"))?;
+        self.inner.list_pr_comments(pr_number)
+    }
+
+    fn create_pr_comment(&self, pr_number: i64, body: &str) -> Result<()> {
+        self.ensure_online(&format!("comment on PR #{pr_number}"))?;
+        self.inner.create_pr_comment(pr_number, body)
+    }
+
+    fn update_pr_comment(&self, comment_id: i64, body: &str) -> Result<()> {
+        self.ensure_online(&format!("update PR comment {comment_id}"))?;
+        self.inner.update_pr_comment(comment_id, body)
+    }
+
+    fn pr_head_statuses(&self, pr_number: i64) -> Result<Vec<CommitStatus>> {
+        self.ensure_online(&format!("read commit statuses of PR #{pr_number}"))?;
+        self.inner.pr_head_statuses(pr_number)
+    }
+
+    fn set_pr_head_status(&self, pr_number: i64, status: &CommitStatus) -> Result<()> {
+        self.ensure_online(&format!("set {} status on PR #{pr_number}", status.context))?;
+        self.inner.set_pr_head_status(pr_number, status)
+    }
+}
+
+fn entry_to_pr(entry: &PrCacheEntry) -> Option<PrInfo> {
+    Some(PrInfo {
+        number: entry.number?,
+        state: entry
+            .state
+            .as_deref()
+            .map(PrState::parse)
+            .unwrap_or(PrState::Unknown),
+        merge_commit_oid: entry.merge_commit_oid.clone(),
+        base_ref_name: entry.base_ref_name.clone(),
+        body: entry.body.clone(),
+        url: entry.url.clone(),
+        title: entry.title.clone(),
+        is_draft: entry.is_draft,
+    })
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+fn format_age(secs: i64) -> String {
+    let secs = secs.max(0);
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h {}m", secs / 3600, (secs % 3600) / 60),
+        _ => format!("{}d {}h", secs / 86400, (secs % 86400) / 3600),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_age_picks_coarsest_units() {
+        assert_eq!(format_age(42), "42s");
+        assert_eq!(format_age(125), "2m");
+        assert_eq!(format_age(3 * 3600 + 5 * 60), "3h 5m");
+        assert_eq!(format_age(2 * 86400 + 3600), "2d 1h");
+    }
+
+    #[test]
+    fn stale_data_dominates_combined_freshness() {
+        let combined = PrDataFreshness::Live
+            .combine(PrDataFreshness::Cached { age_secs: 30 })
+            .combine(PrDataFreshness::Stale { age_secs: Some(90) });
+        assert_eq!(combined, PrDataFreshness::Stale { age_secs: Some(90) });
+        assert_eq!(
+            PrDataFreshness::Cached { age_secs: 5 }.combine(PrDataFreshness::Live),
+            PrDataFreshness::Cached { age_secs: 5 }
+        );
+    }
+}
@@ -0,0 +1,468 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::git::Git;
+
+use super::{
+    ChecksState, CommitStatus, MergeMethod, NewPr, PrComment, PrInfo, PrMergeStatus, PrMetadata,
+    PrState, Provider, ReviewState,
+};
+
+/// Provider backed by a local JSON document instead of a hosting service.
+///
+/// The file is re-read on every call and rewritten after every mutation, so
+/// scripts can edit it between commands to simulate merges or closed PRs.
+#[derive(Debug, Clone)]
+pub struct FileProvider {
+    path: PathBuf,
+    git: Option<Git>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FileProviderState {
+    #[serde(default)]
+    prs: Vec<FilePr>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FilePr {
+    number: i64,
+    head: String,
+    base: String,
+    #[serde(default)]
+    state: FilePrState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    merge_commit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    merge_method: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    checks: Option<FileChecks>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    review: Option<FileReview>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    comments: Vec<PrComment>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    reviewers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    labels: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    assignees: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    milestone: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    draft: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    statuses: Vec<CommitStatus>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum FilePrState {
+    #[default]
+    Open,
+    Merged,
+    Closed,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum FileChecks {
+    Passing,
+    Pending,
+    Failing,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum FileReview {
+    Approved,
+    ChangesRequested,
+    ReviewRequired,
+}
+
+impl FileProvider {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, git: None }
+    }
+
+    /// Resolves branch heads in `git`, so merges record a merge commit.
+    pub fn with_git(mut self, git: Git) -> Self {
+        self.git = Some(git);
+        self
+    }
+
+    fn load(&self) -> Result<FileProviderState> {
+        if !self.path.exists() {
+            return Ok(FileProviderState::default());
+        }
+        let raw = fs::read_to_string(&self.path)
+            .with_context(|| format!("failed to read provider file {}", self.path.display()))?;
+        if raw.trim().is_empty() {
+            return Ok(FileProviderState::default());
+        }
+        serde_json::from_str(&raw)
+            .with_context(|| format!("failed to parse provider file {}", self.path.display()))
+    }
+
+    fn save(&self, state: &FileProviderState) -> Result<()> {
+        let raw = serde_json::to_string_pretty(state)?;
+        fs::write(&self.path, format!("{raw}\n"))
+            .with_context(|| format!("failed to write provider file {}", self.path.display()))
+    }
+
+    fn update(&self, pr_number: i64, apply: impl FnOnce(&mut FilePr) -> Result<()>) -> Result<()> {
+        let mut state = self.load()?;
+        let pr = state
+            .prs
+            .iter_mut()
+            .find(|pr| pr.number == pr_number)
+            .ok_or_else(|| missing_pr(&self.path, pr_number))?;
+        apply(pr)?;
+        self.save(&state)
+    }
+}
+
+impl Provider for FileProvider {
+    fn resolve_pr_by_head(
+        &self,
+        branch: &str,
+        _cached_number: Option<i64>,
+    ) -> Result<Option<PrInfo>> {
+        let state = self.load()?;
+        let candidates = state.prs.iter().filter(|pr| pr.head == branch);
+        let best_open = candidates
+            .clone()
+            .filter(|pr| pr.state == FilePrState::Open)
+            .max_by_key(|pr| pr.number);
+        Ok(best_open
+            .or_else(|| candidates.max_by_key(|pr| pr.number))
+            .map(convert_file_pr))
+    }
+
+    fn update_pr_body(&self, pr_number: i64, body: &str) -> Result<()> {
+        self.update(pr_number, |pr| {
+            pr.body = Some(body.to_string());
+            Ok(())
+        })
+    }
+
+    fn update_pr_title(&self, pr_number: i64, title: &str) -> Result<()> {
+        self.update(pr_number, |pr| {
+            pr.title = Some(title.to_string());
+            Ok(())
+        })
+    }
+
+    fn update_pr_base(&self, pr_number: i64, base: &str) -> Result<()> {
+        self.update(pr_number, |pr| {
+            pr.base = base.to_string();
+            Ok(())
+        })
+    }
+
+    fn pr_merge_status(&self, pr_number: i64) -> Result<PrMergeStatus> {
+        let state = self.load()?;
+        let pr = state
+            .prs
+            .iter()
+            .find(|pr| pr.number == pr_number)
+            .ok_or_else(|| missing_pr(&self.path, pr_number))?;
+        Ok(PrMergeStatus {
+            checks: match pr.checks {
+                Some(FileChecks::Passing) => ChecksState::Passing,
+                Some(FileChecks::Pending) => ChecksState::Pending,
+                Some(FileChecks::Failing) => ChecksState::Failing,
+                None => ChecksState::None,
+            },
+            review: match pr.review {
+                Some(FileReview::Approved) => ReviewState::Approved,
+                Some(FileReview::ChangesRequested) => ReviewState::ChangesRequested,
+                Some(FileReview::ReviewRequired) => ReviewState::ReviewRequired,
+                None => ReviewState::NotRequired,
+            },
+        })
+    }
+
+    fn merge_pr(&self, pr_number: i64, method: MergeMethod) -> Result<()> {
+        self.update(pr_number, |pr| {
+            if pr.state != FilePrState::Open {
+                return Err(anyhow!("PR #{pr_number} is not open"));
+            }
+            if pr.draft {
+                return Err(anyhow!("PR #{pr_number} is still a draft"));
+            }
+            pr.state = FilePrState::Merged;
+            pr.merge_method = Some(method.as_str().to_string());
+            // Merge as a fast-forward: the base's new head is the PR head.
+            if let Some(git) = &self.git {
+                pr.merge_commit = Some(git.head_sha(&pr.head)?);
+            }
+            Ok(())
+        })
+    }
+
+    fn delete_pr(&self, pr_number: i64) -> Result<()> {
+        self.update(pr_number, |pr| {
+            pr.state = FilePrState::Closed;
+            Ok(())
+        })
+    }
+
+    fn set_pr_draft(&self, pr_number: i64, draft: bool) -> Result<()> {
+        self.update(pr_number, |pr| {
+            if pr.state != FilePrState::Open {
+                return Err(anyhow!("PR #{pr_number} is not open"));
+            }
+            pr.draft = draft;
+            Ok(())
+        })
+    }
+
+    fn close_pr(&self, pr_number: i64) -> Result<()> {
+        self.update(pr_number, |pr| {
+            if pr.state != FilePrState::Open {
+                return Err(anyhow!("PR #{pr_number} is not open"));
+            }
+            pr.state = FilePrState::Closed;
+            Ok(())
+        })
+    }
+
+    fn reopen_pr(&self, pr_number: i64) -> Result<()> {
+        self.update(pr_number, |pr| {
+            if pr.state != FilePrState::Closed {
+                return Err(anyhow!("PR #{pr_number} is not closed"));
+            }
+            pr.state = FilePrState::Open;
+            Ok(())
+        })
+    }
+
+    fn create_pr(&self, new: &NewPr<'_>) -> Result<PrInfo> {
+        let mut state = self.load()?;
+        let head = new
+            .head_ref
+            .split_once(':')
+            .map_or(new.head_ref, |(_, branch)| branch);
+        let pr = FilePr {
+            number: state
+                .prs
+                .iter()
+                .map(|pr| pr.number)
+                .max()
+                .unwrap_or_default()
+                + 1,
+            head: head.to_string(),
+            base: new.base.to_string(),
+            state: FilePrState::Open,
+            title: Some(new.title.to_string()),
+            body: Some(new.body.to_string()),
+            merge_commit: None,
+            merge_method: None,
+            url: None,
+            checks: None,
+            review: None,
+            comments: Vec::new(),
+            reviewers: new.metadata.reviewers.clone(),
+            labels: new.metadata.labels.clone(),
+            assignees: new.metadata.assignees.clone(),
+            milestone: new.metadata.milestone.clone(),
+            draft: new.draft,
+            statuses: Vec::new(),
+        };
+        let info = convert_file_pr(&pr);
+        state.prs.push(pr);
+        self.save(&state)?;
+        Ok(info)
+    }
+
+    fn pr_metadata(&self, pr_number: i64) -> Result<PrMetadata> {
+        let state = self.load()?;
+        let pr = state
+            .prs
+            .into_iter()
+            .find(|pr| pr.number == pr_number)
+            .ok_or_else(|| missing_pr(&self.path, pr_number))?;
+        Ok(PrMetadata {
+            reviewers: pr.reviewers,
+            labels: pr.labels,
+            assignees: pr.assignees,
+            milestone: pr.milestone,
+        })
+    }
+
+    fn list_pr_comments(&self, pr_number: i64) -> Result<Vec<PrComment>> {
+        let state = self.load()?;
+        state
+            .prs
+            .into_iter()
+            .find(|pr| pr.number == pr_number)
+            .map(|pr| pr.comments)
+            .ok_or_else(|| missing_pr(&self.path, pr_number))
+    }
+
+    fn create_pr_comment(&self, pr_number: i64, body: &str) -> Result<()> {
+        let next_id = self
+            .load()?
+            .prs
+            .iter()
+            .flat_map(|pr| pr.comments.iter().map(|c| c.id))
+            .max()
+            .unwrap_or_default()
+            + 1;
+        self.update(pr_number, |pr| {
+            pr.comments.push(PrComment {
+                id: next_id,
+                body: body.to_string(),
+            });
+            Ok(())
+        })
+    }
+
+    fn update_pr_comment(&self, comment_id: i64, body: &str) -> Result<()> {
+        let mut state = self.load()?;
+        let comment = state
+            .prs
+            .iter_mut()
+            .flat_map(|pr| pr.comments.iter_mut())
+            .find(|c| c.id == comment_id)
+            .ok_or_else(|| {
+                anyhow!(
+                    "comment {comment_id} not found in provider file {}",
+                    self.path.display()
+                )
+            })?;
+        comment.body = body.to_string();
+        self.save(&state)
+    }
+
+    fn pr_head_statuses(&self, pr_number: i64) -> Result<Vec<CommitStatus>> {
+        let state = self.load()?;
+        state
+            .prs
+            .into_iter()
+            .find(|pr| pr.number == pr_number)
+            .map(|pr| pr.statuses)
+            .ok_or_else(|| missing_pr(&self.path, pr_number))
+    }
+
+    fn set_pr_head_status(&self, pr_number: i64, status: &CommitStatus) -> Result<()> {
+        self.update(pr_number, |pr| {
+            pr.statuses.retain(|s| s.context != status.context);
+            pr.statuses.push(status.clone());
+            Ok(())
+        })
+    }
+}
+
+fn convert_file_pr(pr: &FilePr) -> PrInfo {
+    PrInfo {
+        number: pr.number,
+        state: match pr.state {
+            FilePrState::Open => PrState::Open,
+            FilePrState::Merged => PrState::Merged,
+            FilePrState::Closed => PrState::Closed,
+        },
+        merge_commit_oid: pr.merge_commit.clone(),
+        base_ref_name: Some(pr.base.clone()),
+        body: pr.body.clone(),
+        url: pr.url.clone(),
+        title: pr.title.clone(),
+        is_draft: pr.draft,
+    }
+}
+
+fn missing_pr(path: &Path, pr_number: i64) -> anyhow::Error {
+    anyhow!(
+        "PR #{pr_number} not found in provider file {}",
+        path.display()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider_with(raw: &str) -> (tempfile::TempDir, FileProvider) {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("provider.json");
+        fs::write(&path, raw).expect("write provider file");
+        (dir, FileProvider::new(path))
+    }
+
+    #[test]
+    fn resolve_prefers_open_pr_over_newer_closed_pr() {
+        let (_dir, provider) = provider_with(
+            r#"{"prs":[
+                {"number":3,"head":"feat/a","base":"main","state":"open"},
+                {"number":9,"head":"feat/a","base":"main","state":"closed"},
+                {"number":4,"head":"feat/b","base":"feat/a","state":"merged","merge_commit":"abc"}
+            ]}"#,
+        );
+        let pr = provider
+            .resolve_pr_by_head("feat/a", None)
+            .unwrap()
+            .expect("pr");
+        assert_eq!(pr.number, 3);
+        let merged = provider
+            .resolve_pr_by_head("feat/b", None)
+            .unwrap()
+            .expect("pr");
+        assert!(matches!(merged.state, PrState::Merged));
+        assert_eq!(merged.merge_commit_oid.as_deref(), Some("abc"));
+        assert!(
+            provider
+                .resolve_pr_by_head("feat/c", None)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn mutations_are_written_back_to_the_file() {
+        let (_dir, provider) = provider_with(
+            r#"{"prs":[{"number":1,"head":"feat/a","base":"feat/x","checks":"passing"}]}"#,
+        );
+        provider.update_pr_base(1, "main").unwrap();
+        provider.update_pr_body(1, "hello").unwrap();
+        provider.merge_pr(1, MergeMethod::Squash).unwrap();
+
+        let reloaded = FileProvider::new(provider.path.clone());
+        let pr = reloaded
+            .resolve_pr_by_head("feat/a", None)
+            .unwrap()
+            .expect("pr");
+        assert!(matches!(pr.state, PrState::Merged));
+        assert_eq!(pr.base_ref_name.as_deref(), Some("main"));
+        assert_eq!(pr.body.as_deref(), Some("hello"));
+        assert!(provider.merge_pr(1, MergeMethod::Merge).is_err());
+    }
+
+    #[test]
+    fn comments_are_created_and_updated_by_id() {
+        let (_dir, provider) = provider_with(
+            r#"{"prs":[{"number":1,"head":"feat/a","base":"main"},{"number":2,"head":"feat/b","base":"feat/a","comments":[{"id":5,"body":"hi"}]}]}"#,
+        );
+        provider.create_pr_comment(1, "first").unwrap();
+        provider.update_pr_comment(5, "edited").unwrap();
+
+        assert_eq!(
+            provider.list_pr_comments(1).unwrap(),
+            vec![PrComment {
+                id: 6,
+                body: "first".to_string()
+            }]
+        );
+        assert_eq!(provider.list_pr_comments(2).unwrap()[0].body, "edited");
+        assert!(provider.update_pr_comment(99, "x").is_err());
+    }
+}
@@ -1,12 +1,26 @@
 use std::collections::{HashMap, HashSet};
 use std::process::Command;
+use std::sync::OnceLock;
 
 use anyhow::{Context, Result};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use crate::git::Git;
 use crate::util::url::{github_owner_from_web_url, github_repo_slug_from_web_url};
 
+mod cache;
+mod file;
+
+pub use cache::CachedProvider;
+pub use file::FileProvider;
+
+pub const PROVIDER_ENV: &str = "STACK_PROVIDER";
+
+#[derive(Debug, Error)]
+#[error("PR metadata provider is unreachable")]
+pub struct ProviderUnavailable;
+
 #[derive(Debug, Clone)]
 pub enum PrState {
     Open,
@@ -15,6 +29,74 @@ pub enum PrState {
     Unknown,
 }
 
+impl PrState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PrState::Open => "open",
+            PrState::Merged => "merged",
+            PrState::Closed => "closed",
+            PrState::Unknown => "unknown",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Self {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "open" => PrState::Open,
+            "merged" => PrState::Merged,
+            "closed" => PrState::Closed,
+            _ => PrState::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeMethod {
+    Merge,
+    Squash,
+    Rebase,
+}
+
+impl MergeMethod {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "merge" => Some(MergeMethod::Merge),
+            "squash" => Some(MergeMethod::Squash),
+            "rebase" => Some(MergeMethod::Rebase),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MergeMethod::Merge => "merge",
+            MergeMethod::Squash => "squash",
+            MergeMethod::Rebase => "rebase",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksState {
+    Passing,
+    Pending,
+    Failing,
+    None,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewState {
+    Approved,
+    ChangesRequested,
+    ReviewRequired,
+    NotRequired,
+}
+
+#[derive(Debug, Clone)]
+pub struct PrMergeStatus {
+    pub checks: ChecksState,
+    pub review: ReviewState,
+}
+
 #[derive(Debug, Clone)]
 pub struct PrInfo {
     pub number: i64,
@@ -23,6 +105,86 @@ pub struct PrInfo {
     pub base_ref_name: Option<String>,
     pub body: Option<String>,
     pub url: Option<String>,
+    pub title: Option<String>,
+    pub is_draft: bool,
+}
+
+/// Reviewers, labels, assignees and milestone of a PR.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PrMetadata {
+    pub reviewers: Vec<String>,
+    pub labels: Vec<String>,
+    pub assignees: Vec<String>,
+    pub milestone: Option<String>,
+}
+
+impl PrMetadata {
+    /// Adds entries from `other` that are not present yet; a milestone in
+    /// `other` replaces this one.
+    pub fn merge(&mut self, other: PrMetadata) {
+        for (into, from) in [
+            (&mut self.reviewers, other.reviewers),
+            (&mut self.labels, other.labels),
+            (&mut self.assignees, other.assignees),
+        ] {
+            for value in from {
+                if !into.contains(&value) {
+                    into.push(value);
+                }
+            }
+        }
+        if other.milestone.is_some() {
+            self.milestone = other.milestone;
+        }
+    }
+}
+
+/// A PR to open through the provider API.
+#[derive(Debug, Clone)]
+pub struct NewPr<'a> {
+    /// Local branch name, used for caching.
+    pub branch: &'a str,
+    /// Head ref as the provider expects it (`owner:branch` for forks).
+    pub head_ref: &'a str,
+    pub base: &'a str,
+    pub title: &'a str,
+    pub body: &'a str,
+    pub draft: bool,
+    pub metadata: &'a PrMetadata,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrComment {
+    pub id: i64,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommitStatusState {
+    Pending,
+    Success,
+    Failure,
+    Error,
+}
+
+impl CommitStatusState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CommitStatusState::Pending => "pending",
+            CommitStatusState::Success => "success",
+            CommitStatusState::Failure => "failure",
+            CommitStatusState::Error => "error",
+        }
+    }
+}
+
+/// A commit status on a PR's head commit, identified by its context.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitStatus {
+    pub context: String,
+    pub state: CommitStatusState,
+    pub description: String,
 }
 
 pub trait Provider {
@@ -44,26 +206,127 @@ pub trait Provider {
         Ok(out)
     }
     fn update_pr_body(&self, pr_number: i64, body: &str) -> Result<()>;
+    fn update_pr_title(&self, pr_number: i64, title: &str) -> Result<()>;
+    fn update_pr_base(&self, pr_number: i64, base: &str) -> Result<()>;
+    fn pr_merge_status(&self, pr_number: i64) -> Result<PrMergeStatus>;
+    fn merge_pr(&self, pr_number: i64, method: MergeMethod) -> Result<()>;
     fn delete_pr(&self, pr_number: i64) -> Result<()>;
+    /// Converts an open PR to a draft, or marks it ready for review.
+    fn set_pr_draft(&self, pr_number: i64, draft: bool) -> Result<()>;
+    /// Closes a PR, keeping its head branch.
+    fn close_pr(&self, pr_number: i64) -> Result<()>;
+    fn reopen_pr(&self, pr_number: i64) -> Result<()>;
+    fn create_pr(&self, pr: &NewPr<'_>) -> Result<PrInfo>;
+    fn pr_metadata(&self, pr_number: i64) -> Result<PrMetadata>;
+    fn list_pr_comments(&self, pr_number: i64) -> Result<Vec<PrComment>>;
+    fn create_pr_comment(&self, pr_number: i64, body: &str) -> Result<()>;
+    fn update_pr_comment(&self, comment_id: i64, body: &str) -> Result<()>;
+    /// Latest status per context on the PR's current head commit.
+    fn pr_head_statuses(&self, pr_number: i64) -> Result<Vec<CommitStatus>>;
+    fn set_pr_head_status(&self, pr_number: i64, status: &CommitStatus) -> Result<()>;
+}
+
+/// Selects the provider named by `STACK_PROVIDER`: unset or `github` uses
+/// the `gh` CLI, `file:<path>` uses a JSON file relative to the repo root.
+pub fn provider_from_env(
+    git: &Git,
+    debug: bool,
+    hostname: Option<String>,
+) -> Result<Box<dyn Provider>> {
+    let selector = std::env::var(PROVIDER_ENV).unwrap_or_default();
+    let selector = selector.trim();
+    if selector.is_empty() || selector == "github" {
+        return Ok(Box::new(GithubProvider::new(git.clone(), debug, hostname)));
+    }
+    if let Some(path) = selector.strip_prefix("file:")
+        && !path.trim().is_empty()
+    {
+        return Ok(Box::new(
+            FileProvider::new(git.root().join(path.trim())).with_git(git.clone()),
+        ));
+    }
+    Err(anyhow::anyhow!(
+        "unsupported {PROVIDER_ENV} '{selector}'; expected 'github' or 'file:<path>'"
+    ))
 }
 
 #[derive(Debug, Clone)]
 pub struct GithubProvider {
     git: Git,
     debug: bool,
+    /// GitHub host passed to `gh`; `None` lets `gh` infer it from remotes.
+    hostname: Option<String>,
+    default_repo: OnceLock<Option<String>>,
 }
 
 impl GithubProvider {
-    pub fn new(git: Git, debug: bool) -> Self {
-        Self { git, debug }
+    pub fn new(git: Git, debug: bool, hostname: Option<String>) -> Self {
+        Self {
+            git,
+            debug,
+            hostname,
+            default_repo: OnceLock::new(),
+        }
     }
 
-    fn run_gh_required(&self, args: &[&str]) -> Result<String> {
-        let output = Command::new("gh")
+    /// A `gh` invocation aimed at the configured host: `GH_HOST`/`GH_REPO`
+    /// cover commands that would otherwise infer the repo from remotes, and
+    /// `gh api` also gets `--hostname`.
+    fn gh_command(&self, args: &[&str]) -> Result<Command> {
+        let mut command = Command::new("gh");
+        command
             .current_dir(self.git.root())
             .env("NO_COLOR", "1")
-            .env("CLICOLOR", "0")
-            .args(args)
+            .env("CLICOLOR", "0");
+        if (self.hostname.is_some() || self.git.remote_settings().pr_repo.is_some())
+            && let Some(repo) = self.default_repo()?
+        {
+            command.env("GH_REPO", repo);
+        }
+        match &self.hostname {
+            Some(host) => {
+                command.env("GH_HOST", host);
+                match args.split_first() {
+                    Some((&"api", rest)) => command.args(["api", "--hostname", host]).args(rest),
+                    _ => command.args(args),
+                };
+            }
+            None => {
+                command.args(args);
+            }
+        }
+        Ok(command)
+    }
+
+    /// Remote that hosts `branch`, resolved like pushes resolve it so lookups
+    /// search where `stack push` put the branch.
+    fn head_remote(&self, branch: &str) -> Result<String> {
+        if let Some(remote) = self.git.configured_push_remote(branch)? {
+            return Ok(remote);
+        }
+        Ok(self
+            .git
+            .default_push_remote()?
+            .unwrap_or_else(|| "origin".to_string()))
+    }
+
+    fn default_repo(&self) -> Result<Option<&String>> {
+        if self.default_repo.get().is_none() {
+            let repo = match self.configured_repo_slug()? {
+                Some(slug) => Some(slug),
+                None => match self.repo_slug_for_remote("upstream")? {
+                    Some(slug) => Some(slug),
+                    None => self.repo_slug_for_remote("origin")?,
+                },
+            };
+            let _ = self.default_repo.set(repo);
+        }
+        Ok(self.default_repo.get().and_then(Option::as_ref))
+    }
+
+    fn run_gh_required(&self, args: &[&str]) -> Result<String> {
+        let output = self
+            .gh_command(args)?
             .output()
             .with_context(|| format!("failed to run gh {args:?}"))?;
         if !output.status.success() {
@@ -77,11 +340,8 @@ impl GithubProvider {
     }
 
     fn run_gh_optional(&self, args: &[&str]) -> Result<Option<String>> {
-        let output = Command::new("gh")
-            .current_dir(self.git.root())
-            .env("NO_COLOR", "1")
-            .env("CLICOLOR", "0")
-            .args(args)
+        let output = self
+            .gh_command(args)?
             .output()
             .with_context(|| format!("failed to run gh {args:?}"))?;
         if !output.status.success() {
@@ -95,19 +355,44 @@ impl GithubProvider {
         Ok(Some(String::from_utf8(output.stdout)?))
     }
 
+    /// `owner/repo` for `remote`, qualified as `host/owner/repo` when a
+    /// hostname is configured so `--repo` and `GH_REPO` reach that host.
     fn repo_slug_for_remote(&self, remote: &str) -> Result<Option<String>> {
-        Ok(self
+        let slug = self
             .git
             .remote_web_url(remote)?
-            .and_then(|url| github_repo_slug_from_web_url(&url)))
+            .and_then(|url| github_repo_slug_from_web_url(&url));
+        Ok(match &self.hostname {
+            Some(host) => slug.map(|slug| format!("{host}/{slug}")),
+            None => slug,
+        })
+    }
+
+    /// `stack.prRepo`, or the `stack.baseRemote` repository, host-qualified
+    /// like `repo_slug_for_remote`.
+    fn configured_repo_slug(&self) -> Result<Option<String>> {
+        let settings = self.git.remote_settings();
+        if let Some(repo) = &settings.pr_repo {
+            return Ok(Some(match &self.hostname {
+                Some(host) => format!("{host}/{repo}"),
+                None => repo.clone(),
+            }));
+        }
+        match &settings.base_remote {
+            Some(remote) => self.repo_slug_for_remote(remote),
+            None => Ok(None),
+        }
     }
 
     fn repo_scope_candidates_for_branch(&self, branch: &str) -> Result<Vec<String>> {
         let mut out = Vec::new();
         let mut seen = HashSet::new();
 
-        if let Some(remote) = self.git.remote_for_branch(branch)?
-            && let Some(slug) = self.repo_slug_for_remote(&remote)?
+        if let Some(slug) = self.configured_repo_slug()? {
+            seen.insert(slug.clone());
+            out.push(slug);
+        }
+        if let Some(slug) = self.repo_slug_for_remote(&self.head_remote(branch)?)?
             && seen.insert(slug.clone())
         {
             out.push(slug);
@@ -130,6 +415,10 @@ impl GithubProvider {
         let mut out = Vec::new();
         let mut seen = HashSet::new();
 
+        if let Some(slug) = self.configured_repo_slug()? {
+            seen.insert(slug.clone());
+            out.push(slug);
+        }
         for remote in ["upstream", "origin"] {
             if let Some(slug) = self.repo_slug_for_remote(remote)?
                 && seen.insert(slug.clone())
@@ -139,8 +428,7 @@ impl GithubProvider {
         }
 
         for (branch, _) in branches {
-            if let Some(remote) = self.git.remote_for_branch(branch)?
-                && let Some(slug) = self.repo_slug_for_remote(&remote)?
+            if let Some(slug) = self.repo_slug_for_remote(&self.head_remote(branch)?)?
                 && seen.insert(slug.clone())
             {
                 out.push(slug);
@@ -150,6 +438,27 @@ impl GithubProvider {
         Ok(out)
     }
 
+    fn pr_head_oid(&self, pr_number: i64) -> Result<String> {
+        let num = pr_number.to_string();
+        let args = [
+            "pr",
+            "view",
+            &num,
+            "--json",
+            "headRefOid",
+            "--jq",
+            ".headRefOid",
+        ];
+        let raw = self.run_gh_required(&args)?;
+        let sha = clean_gh_json_output(&raw).trim().to_string();
+        if sha.is_empty() {
+            return Err(anyhow::anyhow!(
+                "gh did not report a head commit for PR #{pr_number}"
+            ));
+        }
+        Ok(sha)
+    }
+
     fn parse_gh_pr_list(&self, raw: &str, context: &str) -> Result<Vec<GhPr>> {
         let cleaned = clean_gh_json_output(raw);
         serde_json::from_str::<Vec<GhPr>>(&cleaned).map_err(|err| {
@@ -193,10 +502,63 @@ struct GhPr {
     head_repository_owner: Option<GhOwner>,
     body: Option<String>,
     url: Option<String>,
+    title: Option<String>,
+    #[serde(rename = "isDraft", default)]
+    is_draft: bool,
     #[serde(rename = "mergeCommit")]
     merge_commit: Option<GhMergeCommit>,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+struct GhMergeStatus {
+    #[serde(rename = "reviewDecision")]
+    review_decision: Option<String>,
+    #[serde(rename = "statusCheckRollup", default)]
+    status_check_rollup: Option<Vec<GhCheck>>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+struct GhCheck {
+    status: Option<String>,
+    conclusion: Option<String>,
+    state: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+struct GhPrMetadata {
+    #[serde(rename = "reviewRequests", default)]
+    review_requests: Vec<GhReviewRequest>,
+    #[serde(rename = "latestReviews", default)]
+    latest_reviews: Vec<GhReview>,
+    #[serde(default)]
+    labels: Vec<GhLabel>,
+    #[serde(default)]
+    assignees: Vec<GhOwner>,
+    milestone: Option<GhMilestone>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+struct GhReviewRequest {
+    login: Option<String>,
+    slug: Option<String>,
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+struct GhReview {
+    author: Option<GhOwner>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+struct GhLabel {
+    name: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+struct GhMilestone {
+    title: String,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 struct GhOwner {
     login: String,
@@ -222,6 +584,7 @@ impl Provider for GithubProvider {
         for scope in self.repo_scope_candidates_for_branches(branches)? {
             repo_scopes.push(Some(scope));
         }
+        let mut any_reachable = false;
         for scope in repo_scopes {
             let mut args = vec![
                 "pr".to_string(),
@@ -231,7 +594,7 @@ impl Provider for GithubProvider {
                 "--limit".to_string(),
                 "200".to_string(),
                 "--json".to_string(),
-                "number,state,mergeCommit,baseRefName,headRefName,headRepositoryOwner,url,body"
+                "number,state,isDraft,mergeCommit,baseRefName,headRefName,headRepositoryOwner,url,body,title"
                     .to_string(),
             ];
             if let Some(scope) = scope.as_deref() {
@@ -242,6 +605,7 @@ impl Provider for GithubProvider {
             let Some(raw) = self.run_gh_optional(&arg_refs)? else {
                 continue;
             };
+            any_reachable = true;
             if raw.trim().is_empty() {
                 continue;
             }
@@ -255,12 +619,14 @@ impl Provider for GithubProvider {
                 }
             }
         }
+        if !any_reachable {
+            return Err(ProviderUnavailable.into());
+        }
 
         for (branch, cached_number) in branches {
             let preferred_owner = self
                 .git
-                .remote_for_branch(branch)?
-                .and_then(|remote| self.git.remote_web_url(&remote).ok().flatten())
+                .remote_web_url(&self.head_remote(branch)?)?
                 .and_then(|url| github_owner_from_web_url(&url));
 
             if let Some(candidates) = by_head.get(*branch) {
@@ -308,6 +674,7 @@ impl Provider for GithubProvider {
         branch: &str,
         cached_number: Option<i64>,
     ) -> Result<Option<PrInfo>> {
+        let mut any_reachable = false;
         if let Some(num) = cached_number {
             let mut scopes: Vec<Option<String>> = vec![None];
             for scope in self.repo_scope_candidates_for_branch(branch)? {
@@ -319,7 +686,7 @@ impl Provider for GithubProvider {
                     "view".to_string(),
                     num.to_string(),
                     "--json".to_string(),
-                    "number,state,mergeCommit,baseRefName,url,body".to_string(),
+                    "number,state,isDraft,mergeCommit,baseRefName,url,body,title".to_string(),
                 ];
                 if let Some(scope) = scope.as_deref() {
                     args.push("--repo".to_string());
@@ -329,6 +696,7 @@ impl Provider for GithubProvider {
                 let Some(out) = self.run_gh_optional(&arg_refs)? else {
                     continue;
                 };
+                any_reachable = true;
                 if out.trim().is_empty() {
                     continue;
                 }
@@ -344,12 +712,12 @@ impl Provider for GithubProvider {
                 let pr = self.parse_gh_pr_view(&out, context)?;
                 return Ok(Some(convert_pr(pr)));
             }
-            return Ok(None);
+            // The cached number may be stale (PR deleted, repo moved or
+            // renamed); look the branch up by head before giving up.
         }
 
         let mut head_filters = vec![branch.to_string()];
-        if let Some(remote) = self.git.remote_for_branch(branch)?
-            && let Some(url) = self.git.remote_web_url(&remote)?
+        if let Some(url) = self.git.remote_web_url(&self.head_remote(branch)?)?
             && let Some(owner) = github_owner_from_web_url(&url)
         {
             let qualified = format!("{owner}:{branch}");
@@ -372,7 +740,7 @@ impl Provider for GithubProvider {
                     "--state".to_string(),
                     "all".to_string(),
                     "--json".to_string(),
-                    "number,state,mergeCommit,baseRefName,url,body".to_string(),
+                    "number,state,isDraft,mergeCommit,baseRefName,url,body,title".to_string(),
                 ];
                 if let Some(scope) = scope.as_deref() {
                     args.push("--repo".to_string());
@@ -382,6 +750,7 @@ impl Provider for GithubProvider {
                 let Some(out) = self.run_gh_optional(&arg_refs)? else {
                     continue;
                 };
+                any_reachable = true;
                 if out.trim().is_empty() {
                     continue;
                 }
@@ -399,6 +768,9 @@ impl Provider for GithubProvider {
                 }
             }
         }
+        if !any_reachable {
+            return Err(ProviderUnavailable.into());
+        }
         Ok(None)
     }
 
@@ -409,12 +781,303 @@ impl Provider for GithubProvider {
         Ok(())
     }
 
+    fn set_pr_draft(&self, pr_number: i64, draft: bool) -> Result<()> {
+        let num = pr_number.to_string();
+        let mut args = vec!["pr", "ready", &num];
+        if draft {
+            args.push("--undo");
+        }
+        let _ = self.run_gh_required(&args)?;
+        Ok(())
+    }
+
+    fn close_pr(&self, pr_number: i64) -> Result<()> {
+        let num = pr_number.to_string();
+        let args = ["pr", "close", &num];
+        let _ = self.run_gh_required(&args)?;
+        Ok(())
+    }
+
+    fn reopen_pr(&self, pr_number: i64) -> Result<()> {
+        let num = pr_number.to_string();
+        let args = ["pr", "reopen", &num];
+        let _ = self.run_gh_required(&args)?;
+        Ok(())
+    }
+
     fn update_pr_body(&self, pr_number: i64, body: &str) -> Result<()> {
         let num = pr_number.to_string();
         let args = ["pr", "edit", &num, "--body", body];
         let _ = self.run_gh_required(&args)?;
         Ok(())
     }
+
+    fn update_pr_title(&self, pr_number: i64, title: &str) -> Result<()> {
+        let num = pr_number.to_string();
+        let args = ["pr", "edit", &num, "--title", title];
+        let _ = self.run_gh_required(&args)?;
+        Ok(())
+    }
+
+    fn update_pr_base(&self, pr_number: i64, base: &str) -> Result<()> {
+        let num = pr_number.to_string();
+        let args = ["pr", "edit", &num, "--base", base];
+        let _ = self.run_gh_required(&args)?;
+        Ok(())
+    }
+
+    fn pr_merge_status(&self, pr_number: i64) -> Result<PrMergeStatus> {
+        let num = pr_number.to_string();
+        let args = [
+            "pr",
+            "view",
+            &num,
+            "--json",
+            "reviewDecision,statusCheckRollup",
+        ];
+        let raw = self.run_gh_required(&args)?;
+        let cleaned = clean_gh_json_output(&raw);
+        let status: GhMergeStatus = serde_json::from_str(&cleaned).map_err(|err| {
+            if self.debug {
+                anyhow::anyhow!(
+                    "failed to parse gh merge status JSON for #{pr_number}: {err}; gh output: {}",
+                    raw.trim()
+                )
+            } else {
+                err.into()
+            }
+        })?;
+        Ok(convert_merge_status(status))
+    }
+
+    fn merge_pr(&self, pr_number: i64, method: MergeMethod) -> Result<()> {
+        let num = pr_number.to_string();
+        let flag = format!("--{}", method.as_str());
+        let args = ["pr", "merge", &num, &flag];
+        let _ = self.run_gh_required(&args)?;
+        Ok(())
+    }
+
+    fn create_pr(&self, pr: &NewPr<'_>) -> Result<PrInfo> {
+        let mut args = vec![
+            "pr",
+            "create",
+            "--head",
+            pr.head_ref,
+            "--base",
+            pr.base,
+            "--title",
+            pr.title,
+            "--body",
+            pr.body,
+        ];
+        if pr.draft {
+            args.push("--draft");
+        }
+        let metadata = pr.metadata;
+        for (flag, values) in [
+            ("--reviewer", &metadata.reviewers),
+            ("--label", &metadata.labels),
+            ("--assignee", &metadata.assignees),
+        ] {
+            for value in values {
+                args.extend([flag, value.as_str()]);
+            }
+        }
+        if let Some(milestone) = &metadata.milestone {
+            args.extend(["--milestone", milestone.as_str()]);
+        }
+        let raw = self.run_gh_required(&args)?;
+        let url = clean_gh_json_output(&raw)
+            .lines()
+            .map(str::trim)
+            .rfind(|line| line.contains("/pull/"))
+            .map(str::to_string)
+            .ok_or_else(|| {
+                anyhow::anyhow!("gh pr create did not print a PR URL: {}", raw.trim())
+            })?;
+        let number = url
+            .rsplit('/')
+            .next()
+            .and_then(|n| n.parse::<i64>().ok())
+            .ok_or_else(|| anyhow::anyhow!("could not read PR number from '{url}'"))?;
+        Ok(PrInfo {
+            number,
+            state: PrState::Open,
+            merge_commit_oid: None,
+            base_ref_name: Some(pr.base.to_string()),
+            body: Some(pr.body.to_string()),
+            url: Some(url),
+            title: Some(pr.title.to_string()),
+            is_draft: pr.draft,
+        })
+    }
+
+    fn pr_metadata(&self, pr_number: i64) -> Result<PrMetadata> {
+        let num = pr_number.to_string();
+        let args = [
+            "pr",
+            "view",
+            &num,
+            "--json",
+            "reviewRequests,latestReviews,labels,assignees,milestone",
+        ];
+        let raw = self.run_gh_required(&args)?;
+        let cleaned = clean_gh_json_output(&raw);
+        let parsed: GhPrMetadata = serde_json::from_str(&cleaned).map_err(|err| {
+            if self.debug {
+                anyhow::anyhow!(
+                    "failed to parse gh PR metadata JSON for #{pr_number}: {err}; gh output: {}",
+                    raw.trim()
+                )
+            } else {
+                err.into()
+            }
+        })?;
+        let mut metadata = PrMetadata {
+            reviewers: Vec::new(),
+            labels: parsed.labels.into_iter().map(|l| l.name).collect(),
+            assignees: parsed.assignees.into_iter().map(|a| a.login).collect(),
+            milestone: parsed.milestone.map(|m| m.title),
+        };
+        metadata.merge(PrMetadata {
+            reviewers: parsed
+                .review_requests
+                .into_iter()
+                .filter_map(|r| r.login.or(r.slug).or(r.name))
+                .chain(
+                    parsed
+                        .latest_reviews
+                        .into_iter()
+                        .filter_map(|r| r.author.map(|a| a.login)),
+                )
+                .collect(),
+            ..Default::default()
+        });
+        Ok(metadata)
+    }
+
+    fn list_pr_comments(&self, pr_number: i64) -> Result<Vec<PrComment>> {
+        let endpoint = format!("repos/{{owner}}/{{repo}}/issues/{pr_number}/comments");
+        let args = ["api", "--paginate", &endpoint, "--jq", ".[] | {id, body}"];
+        let raw = self.run_gh_required(&args)?;
+        clean_gh_json_output(&raw)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line).map_err(|err| {
+                    if self.debug {
+                        anyhow::anyhow!(
+                            "failed to parse gh comment JSON for #{pr_number}: {err}; gh output: {}",
+                            line.trim()
+                        )
+                    } else {
+                        err.into()
+                    }
+                })
+            })
+            .collect()
+    }
+
+    fn create_pr_comment(&self, pr_number: i64, body: &str) -> Result<()> {
+        let endpoint = format!("repos/{{owner}}/{{repo}}/issues/{pr_number}/comments");
+        let field = format!("body={body}");
+        let args = ["api", "-X", "POST", &endpoint, "-f", &field];
+        let _ = self.run_gh_required(&args)?;
+        Ok(())
+    }
+
+    fn update_pr_comment(&self, comment_id: i64, body: &str) -> Result<()> {
+        let endpoint = format!("repos/{{owner}}/{{repo}}/issues/comments/{comment_id}");
+        let field = format!("body={body}");
+        let args = ["api", "-X", "PATCH", &endpoint, "-f", &field];
+        let _ = self.run_gh_required(&args)?;
+        Ok(())
+    }
+
+    fn pr_head_statuses(&self, pr_number: i64) -> Result<Vec<CommitStatus>> {
+        let sha = self.pr_head_oid(pr_number)?;
+        let endpoint = format!("repos/{{owner}}/{{repo}}/commits/{sha}/status");
+        let args = [
+            "api",
+            &endpoint,
+            "--jq",
+            ".statuses[] | {context, state, description: (.description // \"\")}",
+        ];
+        let raw = self.run_gh_required(&args)?;
+        clean_gh_json_output(&raw)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line).map_err(|err| {
+                    if self.debug {
+                        anyhow::anyhow!(
+                            "failed to parse gh commit status JSON for #{pr_number}: {err}; gh output: {}",
+                            line.trim()
+                        )
+                    } else {
+                        err.into()
+                    }
+                })
+            })
+            .collect()
+    }
+
+    fn set_pr_head_status(&self, pr_number: i64, status: &CommitStatus) -> Result<()> {
+        let sha = self.pr_head_oid(pr_number)?;
+        let endpoint = format!("repos/{{owner}}/{{repo}}/statuses/{sha}");
+        let state = format!("state={}", status.state.as_str());
+        let context = format!("context={}", status.context);
+        let description = format!("description={}", status.description);
+        let args = [
+            "api",
+            "-X",
+            "POST",
+            &endpoint,
+            "-f",
+            &state,
+            "-f",
+            &context,
+            "-f",
+            &description,
+        ];
+        let _ = self.run_gh_required(&args)?;
+        Ok(())
+    }
+}
+
+fn convert_merge_status(status: GhMergeStatus) -> PrMergeStatus {
+    let mut checks = ChecksState::None;
+    for check in status.status_check_rollup.unwrap_or_default() {
+        let outcome = if let Some(state) = check.state.as_deref() {
+            match state {
+                "SUCCESS" => ChecksState::Passing,
+                "PENDING" | "EXPECTED" => ChecksState::Pending,
+                _ => ChecksState::Failing,
+            }
+        } else if check.status.as_deref() != Some("COMPLETED") {
+            ChecksState::Pending
+        } else {
+            match check.conclusion.as_deref() {
+                Some("SUCCESS" | "NEUTRAL" | "SKIPPED") => ChecksState::Passing,
+                _ => ChecksState::Failing,
+            }
+        };
+        checks = match (checks, outcome) {
+            (ChecksState::Failing, _) | (_, ChecksState::Failing) => ChecksState::Failing,
+            (ChecksState::Pending, _) | (_, ChecksState::Pending) => ChecksState::Pending,
+            _ => ChecksState::Passing,
+        };
+    }
+
+    let review = match status.review_decision.as_deref() {
+        Some("APPROVED") => ReviewState::Approved,
+        Some("CHANGES_REQUESTED") => ReviewState::ChangesRequested,
+        Some("REVIEW_REQUIRED") => ReviewState::ReviewRequired,
+        _ => ReviewState::NotRequired,
+    };
+
+    PrMergeStatus { checks, review }
 }
 
 fn convert_pr(pr: GhPr) -> PrInfo {
@@ -431,6 +1094,8 @@ fn convert_pr(pr: GhPr) -> PrInfo {
         base_ref_name: pr.base_ref_name,
         body: pr.body,
         url: pr.url,
+        title: pr.title,
+        is_draft: pr.is_draft,
     }
 }
 
@@ -448,6 +1113,8 @@ fn select_preferred_pr(prs: Vec<GhPr>) -> Option<GhPr> {
                 head_repository_owner: pr.head_repository_owner.clone(),
                 body: pr.body.clone(),
                 url: pr.url.clone(),
+                title: pr.title.clone(),
+                is_draft: pr.is_draft,
                 merge_commit: pr
                     .merge_commit
                     .as_ref()
@@ -509,6 +1176,8 @@ mod tests {
                 head_repository_owner: None,
                 body: None,
                 url: None,
+                title: None,
+                is_draft: false,
                 merge_commit: None,
             },
             GhPr {
@@ -519,6 +1188,8 @@ mod tests {
                 head_repository_owner: None,
                 body: None,
                 url: None,
+                title: None,
+                is_draft: false,
                 merge_commit: None,
             },
         ];
@@ -526,4 +1197,24 @@ mod tests {
         assert_eq!(picked.number, 6693);
         assert_eq!(picked.state, "OPEN");
     }
+
+    #[test]
+    fn convert_merge_status_failing_check_wins_over_pending() {
+        let raw = r#"{"reviewDecision":"APPROVED","statusCheckRollup":[
+            {"__typename":"CheckRun","status":"IN_PROGRESS","conclusion":""},
+            {"__typename":"CheckRun","status":"COMPLETED","conclusion":"FAILURE"},
+            {"__typename":"StatusContext","state":"SUCCESS"}
+        ]}"#;
+        let status = convert_merge_status(serde_json::from_str(raw).expect("valid json"));
+        assert_eq!(status.checks, ChecksState::Failing);
+        assert_eq!(status.review, ReviewState::Approved);
+    }
+
+    #[test]
+    fn convert_merge_status_without_checks_or_review_policy() {
+        let raw = r#"{"reviewDecision":"","statusCheckRollup":[]}"#;
+        let status = convert_merge_status(serde_json::from_str(raw).expect("valid json"));
+        assert_eq!(status.checks, ChecksState::None);
+        assert_eq!(status.review, ReviewState::NotRequired);
+    }
 }
@@ -59,6 +59,10 @@ mod tests {
             last_synced_head_sha: None,
             cached_pr_number: Some(10),
             cached_pr_state: Some("open".to_string()),
+            description: None,
+            ticket: None,
+            owner: None,
+            frozen: false,
         }];
         let ordered = vec![
             "main".to_string(),
@@ -81,6 +85,10 @@ mod tests {
                 last_synced_head_sha: None,
                 cached_pr_number: Some(10),
                 cached_pr_state: Some("open".to_string()),
+                description: None,
+                ticket: None,
+                owner: None,
+                frozen: false,
             },
             BranchRecord {
                 id: 2,
@@ -89,6 +97,10 @@ mod tests {
                 last_synced_head_sha: None,
                 cached_pr_number: None,
                 cached_pr_state: None,
+                description: None,
+                ticket: None,
+                owner: None,
+                frozen: false,
             },
         ];
         let names = vec!["feat/a".to_string(), "feat/b".to_string()];
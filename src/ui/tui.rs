@@ -62,9 +62,24 @@ pub fn run_stack_tui(branches: &[BranchView]) -> Result<()> {
                     .last_synced_head_sha
                     .as_deref()
                     .unwrap_or("unknown");
+                let annotations: String = [
+                    ("Ticket", &branch.ticket),
+                    ("Owner", &branch.owner),
+                    ("Description", &branch.description),
+                ]
+                .into_iter()
+                .filter_map(|(label, value)| value.as_ref().map(|v| format!("{label}: {v}\n")))
+                .collect();
                 format!(
-                    "Branch: {}\nParent: {}\nPR: #{} ({})\nLast synced SHA: {}\nExists in git: {}\n\nKeys: j/k or arrows to move, q or Ctrl-C to quit",
-                    branch.name, parent, pr_num, pr_state, synced, branch.exists_in_git
+                    "Branch: {}\nParent: {}\nPR: #{} ({})\nLast synced SHA: {}\nExists in git: {}\nFrozen: {}\n{}\nKeys: j/k or arrows to move, q or Ctrl-C to quit",
+                    branch.name,
+                    parent,
+                    pr_num,
+                    pr_state,
+                    synced,
+                    branch.exists_in_git,
+                    branch.frozen,
+                    annotations
                 )
             } else {
                 "No branch selected\n\nKeys: q or Ctrl-C to quit".to_string()
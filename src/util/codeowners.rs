@@ -0,0 +1,178 @@
+use anyhow::Result;
+
+use crate::git::Git;
+
+/// Locations GitHub reads CODEOWNERS from, in priority order.
+const CODEOWNERS_PATHS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+#[derive(Debug, Clone)]
+struct OwnerRule {
+    pattern: String,
+    owners: Vec<String>,
+}
+
+/// Parsed CODEOWNERS rules; as on GitHub, the last matching rule wins.
+#[derive(Debug, Clone, Default)]
+pub struct CodeOwners {
+    rules: Vec<OwnerRule>,
+}
+
+impl CodeOwners {
+    /// Reads the first CODEOWNERS file found in the tree of `rev`.
+    pub fn load_at(git: &Git, rev: &str) -> Option<Self> {
+        CODEOWNERS_PATHS
+            .iter()
+            .find_map(|path| git.file_at(rev, path).ok())
+            .map(|raw| Self::parse(&raw))
+    }
+
+    pub fn parse(raw: &str) -> Self {
+        let rules = raw
+            .lines()
+            .filter_map(|line| {
+                let line = line.split_once('#').map_or(line, |(rule, _)| rule);
+                let mut tokens = line.split_whitespace();
+                let pattern = tokens.next()?.to_string();
+                Some(OwnerRule {
+                    pattern,
+                    owners: tokens.map(str::to_string).collect(),
+                })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Owners of `path`, empty when no rule (or an owner-less rule) matches.
+    pub fn owners_for(&self, path: &str) -> &[String] {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| pattern_matches(&rule.pattern, path))
+            .map(|rule| rule.owners.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Sorted owners of the files `branch` changes since it forked from
+    /// `parent`, by the CODEOWNERS file of that fork point rather than any
+    /// edits the branch makes to it.
+    pub fn owners_for_branch(git: &Git, branch: &str, parent: &str) -> Result<Vec<String>> {
+        let fork_point = git.merge_base(branch, parent)?;
+        let Some(rules) = Self::load_at(git, &fork_point) else {
+            return Ok(Vec::new());
+        };
+        let mut owners = git
+            .changed_files(&fork_point, branch)?
+            .iter()
+            .flat_map(|path| rules.owners_for(path).iter().cloned())
+            .collect::<Vec<_>>();
+        owners.sort();
+        owners.dedup();
+        Ok(owners)
+    }
+}
+
+/// Turns CODEOWNERS owners into reviewer logins, dropping email owners.
+pub fn reviewer_logins(owners: &[String]) -> Vec<String> {
+    owners
+        .iter()
+        .map(|owner| owner.trim_start_matches('@'))
+        .filter(|owner| !owner.is_empty() && !owner.contains('@'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// gitignore-style matching as used by CODEOWNERS: patterns containing a
+/// non-trailing `/` are anchored to the repo root, others match at any depth,
+/// and a pattern matching a directory owns everything beneath it. A `*` never
+/// crosses `/`, so `docs/*` owns `docs/a.md` but not `docs/a/b.md`.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let dir_only = pattern.ends_with('/');
+    let trimmed = pattern.trim_end_matches('/');
+    let anchored = trimmed.starts_with('/') || trimmed.trim_start_matches('/').contains('/');
+    let trimmed = trimmed.trim_start_matches('/');
+    if trimmed.is_empty() {
+        return false;
+    }
+    let pattern_segments = trimmed.split('/').collect::<Vec<_>>();
+    let path_segments = path.split('/').collect::<Vec<_>>();
+    let max_len = if dir_only {
+        path_segments.len() - 1
+    } else {
+        path_segments.len()
+    };
+
+    let wildcard_leaf = pattern_segments
+        .last()
+        .is_some_and(|leaf| leaf.contains('*') && *leaf != "**");
+    if anchored && wildcard_leaf && !dir_only {
+        segments_match(&pattern_segments, &path_segments)
+    } else if anchored {
+        (1..=max_len).any(|len| segments_match(&pattern_segments, &path_segments[..len]))
+    } else {
+        path_segments[..max_len]
+            .iter()
+            .any(|segment| glob_match(trimmed, segment))
+    }
+}
+
+fn segments_match(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| segments_match(rest, &path[skip..])),
+        Some((first, rest)) => path.split_first().is_some_and(|(segment, tail)| {
+            glob_match(first, segment) && segments_match(rest, tail)
+        }),
+    }
+}
+
+fn glob_match(pattern: &str, text: &str) -> bool {
+    fn go(pattern: &[char], text: &[char]) -> bool {
+        match pattern.split_first() {
+            None => text.is_empty(),
+            Some(('*', rest)) => (0..=text.len()).any(|skip| go(rest, &text[skip..])),
+            Some(('?', rest)) => !text.is_empty() && go(rest, &text[1..]),
+            Some((c, rest)) => text.first() == Some(c) && go(rest, &text[1..]),
+        }
+    }
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    go(&pattern, &text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_matching_rule_wins_with_gitignore_style_patterns() {
+        let owners = CodeOwners::parse(
+            "# default owners\n* @acme/all\n*.rs @acme/rust # inline comment\n/docs/ @acme/docs\napps/ @acme/apps\nsrc/generated/**/*.rs\n/build/*.log @acme/build\n",
+        );
+        assert_eq!(owners.owners_for("README.md"), ["@acme/all"]);
+        assert_eq!(owners.owners_for("src/lib.rs"), ["@acme/rust"]);
+        assert_eq!(owners.owners_for("docs/guide/intro.md"), ["@acme/docs"]);
+        assert_eq!(owners.owners_for("nested/docs/intro.md"), ["@acme/all"]);
+        assert_eq!(owners.owners_for("web/apps/main.ts"), ["@acme/apps"]);
+        assert!(owners.owners_for("src/generated/a/b/types.rs").is_empty());
+        assert_eq!(owners.owners_for("build/out.log"), ["@acme/build"]);
+        assert_eq!(owners.owners_for("build/nested/out.log"), ["@acme/all"]);
+    }
+
+    #[test]
+    fn single_star_does_not_cross_directories() {
+        assert!(pattern_matches("docs/*", "docs/a.md"));
+        assert!(!pattern_matches("docs/*", "docs/a/b.md"));
+        assert!(pattern_matches("docs/**", "docs/a/b.md"));
+        assert!(pattern_matches("docs/*/", "docs/a/b.md"));
+    }
+
+    #[test]
+    fn reviewer_logins_strip_at_and_drop_emails() {
+        let logins = reviewer_logins(&[
+            "@alice".to_string(),
+            "@acme/core".to_string(),
+            "dev@example.com".to_string(),
+        ]);
+        assert_eq!(logins, ["alice", "acme/core"]);
+    }
+}
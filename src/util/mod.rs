@@ -1,4 +1,8 @@
+pub mod codeowners;
 pub mod pr_body;
 pub mod pr_links;
+pub mod pr_title;
+pub mod ssh_config;
+pub mod template;
 pub mod terminal;
 pub mod url;
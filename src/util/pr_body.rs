@@ -1,4 +1,4 @@
-use crate::util::url::{escape_markdown_link_label, url_encode_branch_path};
+use crate::util::url::{escape_markdown_link_label, url_encode_branch_path, url_encode_component};
 
 #[derive(Debug, Clone)]
 pub struct ManagedBranchRef {
@@ -7,6 +7,69 @@ pub struct ManagedBranchRef {
     pub pr_url: Option<String>,
 }
 
+#[derive(Debug, Clone)]
+pub struct StackSectionEntry {
+    pub branch: ManagedBranchRef,
+    pub depth: usize,
+    pub title: Option<String>,
+    pub state: Option<String>,
+    pub current: bool,
+}
+
+/// How the stack-managed PR body section is rendered. A template, when set,
+/// replaces the built-in layout entirely.
+#[derive(Debug, Clone, Default)]
+pub struct ManagedSectionStyle {
+    pub format: ManagedSectionFormat,
+    pub template: Option<String>,
+    pub placement: ManagedSectionPlacement,
+    /// Link pattern for branch tickets, with `{ticket}` as the placeholder.
+    pub ticket_url: Option<String>,
+}
+
+/// Where the stack-managed section is published on each PR.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ManagedSectionPlacement {
+    /// Merged into the PR description between the managed markers.
+    #[default]
+    Body,
+    /// Posted as a single comment, found again via the managed markers.
+    Comment,
+}
+
+impl ManagedSectionPlacement {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "body" => Some(ManagedSectionPlacement::Body),
+            "comment" => Some(ManagedSectionPlacement::Comment),
+            _ => None,
+        }
+    }
+}
+
+/// Layout of the stack-managed PR body section.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ManagedSectionFormat {
+    /// `… → parent → (this PR) → first child → …`
+    #[default]
+    Chain,
+    /// Nested markdown list of the whole stack.
+    List,
+    /// Markdown table of the whole stack.
+    Table,
+}
+
+impl ManagedSectionFormat {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "chain" => Some(ManagedSectionFormat::Chain),
+            "list" => Some(ManagedSectionFormat::List),
+            "table" => Some(ManagedSectionFormat::Table),
+            _ => None,
+        }
+    }
+}
+
 pub const MANAGED_BODY_MARKER_START: &str = "<!-- stack:managed:start -->";
 pub const MANAGED_BODY_MARKER_END: &str = "<!-- stack:managed:end -->";
 
@@ -16,6 +79,21 @@ pub fn managed_pr_section(
     base_commit_url: Option<&str>,
     parent: Option<&ManagedBranchRef>,
     first_child: Option<&ManagedBranchRef>,
+    notes: Option<&str>,
+) -> String {
+    finish_managed_section(
+        &managed_chain_line(base_url, base_branch, base_commit_url, parent, first_child),
+        notes,
+    )
+}
+
+/// The `… → parent → (this PR) → first child → …` line without markers.
+pub fn managed_chain_line(
+    base_url: &str,
+    base_branch: &str,
+    base_commit_url: Option<&str>,
+    parent: Option<&ManagedBranchRef>,
+    first_child: Option<&ManagedBranchRef>,
 ) -> String {
     let root = base_url.trim_end_matches('/');
     let base_label = escape_markdown_link_label(base_branch);
@@ -40,27 +118,146 @@ pub fn managed_pr_section(
     } else {
         String::new()
     };
-    let managed_line = if let Some(child) = first_child {
+    if let Some(child) = first_child {
         format!(
             "{prefix}{parent_chain} → (this PR) → {} → …",
             format_pr_chain_node(root, child)
         )
     } else {
         format!("{prefix}{parent_chain} → (this PR)")
-    };
-    format!("{MANAGED_BODY_MARKER_START}\n{managed_line}\n<hr />\n{MANAGED_BODY_MARKER_END}")
+    }
+}
+
+pub fn wrap_managed_section(content: &str) -> String {
+    format!(
+        "{MANAGED_BODY_MARKER_START}\n{}\n{MANAGED_BODY_MARKER_END}",
+        content.trim()
+    )
 }
 
-pub fn compose_branch_pr_body(
+/// Wraps a built-in layout, with the branch notes below it, in the markers.
+fn finish_managed_section(content: &str, notes: Option<&str>) -> String {
+    match notes {
+        Some(notes) => wrap_managed_section(&format!("{content}\n\n{notes}\n<hr />")),
+        None => wrap_managed_section(&format!("{content}\n<hr />")),
+    }
+}
+
+/// `ticket` linked through a `stack.ticketUrl` pattern, or the bare key when
+/// no pattern is configured.
+pub fn ticket_link(ticket: &str, ticket_url: Option<&str>) -> String {
+    match ticket_url {
+        Some(pattern) => format!(
+            "[{}]({})",
+            escape_markdown_link_label(ticket),
+            pattern.replace("{ticket}", &url_encode_component(ticket))
+        ),
+        None => ticket.to_string(),
+    }
+}
+
+/// The annotation block of the built-in layouts: the description, then the
+/// ticket and owner on one line. `None` when the branch has no annotations.
+pub fn managed_notes(
+    description: Option<&str>,
+    ticket_link: Option<&str>,
+    owner: Option<&str>,
+) -> Option<String> {
+    let facts = [("Ticket", ticket_link), ("Owner", owner)]
+        .into_iter()
+        .filter_map(|(label, value)| value.map(|v| format!("**{label}:** {v}")))
+        .collect::<Vec<_>>()
+        .join(" · ");
+    let parts = [description.unwrap_or_default(), facts.as_str()]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    (!parts.is_empty()).then(|| parts.join("\n\n"))
+}
+
+/// Renders the whole stack (root to leaves) as a list or table, marking the
+/// entry for the PR that carries the section.
+pub fn managed_stack_section(
     base_url: &str,
     base_branch: &str,
     base_commit_url: Option<&str>,
-    parent: Option<&ManagedBranchRef>,
-    first_child: Option<&ManagedBranchRef>,
-    user_body: Option<&str>,
+    entries: &[StackSectionEntry],
+    format: ManagedSectionFormat,
+    notes: Option<&str>,
+) -> String {
+    finish_managed_section(
+        &managed_stack_lines(base_url, base_branch, base_commit_url, entries, format),
+        notes,
+    )
+}
+
+/// The stack heading and list/table rows without markers.
+pub fn managed_stack_lines(
+    base_url: &str,
+    base_branch: &str,
+    base_commit_url: Option<&str>,
+    entries: &[StackSectionEntry],
+    format: ManagedSectionFormat,
 ) -> String {
-    let managed_section =
-        managed_pr_section(base_url, base_branch, base_commit_url, parent, first_child);
+    let root = base_url.trim_end_matches('/');
+    let base_label = escape_markdown_link_label(base_branch);
+    let base_link = base_commit_url
+        .map(|url| format!("[{base_label}]({url})"))
+        .unwrap_or_else(|| {
+            format!(
+                "[{base_label}]({root}/tree/{})",
+                url_encode_branch_path(base_branch)
+            )
+        });
+
+    let mut lines = vec![format!("Stack into {base_link}:"), String::new()];
+    if format == ManagedSectionFormat::Table {
+        lines.push("| | PR | Branch | Title | State |".to_string());
+        lines.push("|---|---|---|---|---|".to_string());
+    }
+    for entry in entries {
+        let pr = entry
+            .branch
+            .pr_number
+            .map(|_| format_pr_chain_node(root, &entry.branch));
+        let title = entry.title.as_deref().map(str::trim).unwrap_or_default();
+        let state = entry.state.as_deref().unwrap_or_default();
+        if format == ManagedSectionFormat::Table {
+            let indent = if entry.depth == 0 {
+                String::new()
+            } else {
+                format!("{}↳ ", "&nbsp;&nbsp;".repeat(entry.depth - 1))
+            };
+            lines.push(format!(
+                "| {} | {} | {indent}`{}` | {} | {state} |",
+                if entry.current { "→" } else { "" },
+                pr.unwrap_or_else(|| "—".to_string()),
+                entry.branch.branch,
+                title.replace('|', "\\|"),
+            ));
+        } else {
+            let mut line = format!(
+                "{}- {}",
+                "  ".repeat(entry.depth),
+                pr.map(|pr| format!("{pr} `{}`", entry.branch.branch))
+                    .unwrap_or_else(|| format_pr_chain_node(root, &entry.branch))
+            );
+            if !title.is_empty() {
+                line.push_str(&format!(" {title}"));
+            }
+            if !state.is_empty() {
+                line.push_str(&format!(" ({state})"));
+            }
+            if entry.current {
+                line.push_str(" ← this PR");
+            }
+            lines.push(line);
+        }
+    }
+    lines.join("\n")
+}
+
+pub fn compose_branch_pr_body(managed_section: &str, user_body: Option<&str>) -> String {
     let user = user_body.and_then(|body| {
         let trimmed = body.trim();
         if trimmed.is_empty() {
@@ -72,7 +269,7 @@ pub fn compose_branch_pr_body(
     if let Some(user) = user {
         format!("{managed_section}\n\n{user}")
     } else {
-        managed_section
+        managed_section.to_string()
     }
 }
 
@@ -104,6 +301,24 @@ pub fn merge_managed_pr_section(existing_body: Option<&str>, managed_section: &s
     format!("{managed_section}\n\n{existing}")
 }
 
+/// Removes the managed section from a PR body, returning `None` when the body
+/// has no managed section.
+pub fn strip_managed_pr_section(existing_body: &str) -> Option<String> {
+    let (start, end) = managed_section_bounds(existing_body)?;
+    let prefix = existing_body[..start].trim_end();
+    let suffix = existing_body[end..].trim_start();
+    Some(match (prefix.is_empty(), suffix.is_empty()) {
+        (_, true) => prefix.to_string(),
+        (true, false) => suffix.to_string(),
+        (false, false) => format!("{prefix}\n\n{suffix}"),
+    })
+}
+
+/// Whether a PR comment is the stack-managed comment.
+pub fn is_managed_comment(body: &str) -> bool {
+    managed_section_bounds(body).is_some()
+}
+
 fn managed_section_bounds(body: &str) -> Option<(usize, usize)> {
     let start = body.find(MANAGED_BODY_MARKER_START)?;
     let end_start = body[start..].find(MANAGED_BODY_MARKER_END)? + start;
@@ -111,7 +326,7 @@ fn managed_section_bounds(body: &str) -> Option<(usize, usize)> {
     Some((start, end))
 }
 
-fn format_pr_chain_node(root: &str, node: &ManagedBranchRef) -> String {
+pub fn format_pr_chain_node(root: &str, node: &ManagedBranchRef) -> String {
     if let Some(number) = node.pr_number {
         if let Some(url) = node.pr_url.as_deref() {
             format!("[#{number}]({url})")
@@ -147,6 +362,7 @@ mod tests {
             None,
             Some(&parent),
             Some(&child),
+            None,
         );
         assert!(body.contains(MANAGED_BODY_MARKER_START));
         assert!(body.contains(MANAGED_BODY_MARKER_END));
@@ -158,7 +374,14 @@ mod tests {
 
     #[test]
     fn managed_pr_section_base_parent_has_no_leading_ellipsis() {
-        let body = managed_pr_section("https://github.com/acme/repo", "main", None, None, None);
+        let body = managed_pr_section(
+            "https://github.com/acme/repo",
+            "main",
+            None,
+            None,
+            None,
+            None,
+        );
         assert!(body.contains("[main](https://github.com/acme/repo/tree/main) → (this PR)"));
         assert!(!body.contains("… [main]"));
     }
@@ -181,6 +404,7 @@ mod tests {
             None,
             Some(&base_parent),
             Some(&child),
+            None,
         );
         assert!(
             body.contains(
@@ -203,6 +427,7 @@ mod tests {
             None,
             Some(&parent),
             None,
+            None,
         );
         assert!(body.contains("… → [#12](https://github.com/acme/repo/pull/12) → (this PR)"));
         assert!(!body.contains("(this PR) …"));
@@ -210,14 +435,15 @@ mod tests {
 
     #[test]
     fn compose_branch_pr_body_appends_user_text_after_managed_block() {
-        let body = compose_branch_pr_body(
+        let managed = managed_pr_section(
             "https://github.com/acme/repo",
             "main",
             None,
             None,
             None,
-            Some("details"),
+            None,
         );
+        let body = compose_branch_pr_body(&managed, Some("details"));
         assert!(body.starts_with(MANAGED_BODY_MARKER_START));
         assert!(body.contains(MANAGED_BODY_MARKER_END));
         assert!(body.ends_with("details"));
@@ -239,6 +465,20 @@ mod tests {
         assert_eq!(merged, format!("{new_section}\n\nuser text"));
     }
 
+    #[test]
+    fn strip_managed_section_keeps_user_text_only() {
+        let body = format!(
+            "intro\n\n{MANAGED_BODY_MARKER_START}\nold\n{MANAGED_BODY_MARKER_END}\n\nuser text"
+        );
+        assert_eq!(
+            strip_managed_pr_section(&body).as_deref(),
+            Some("intro\n\nuser text")
+        );
+        assert_eq!(strip_managed_pr_section("user text"), None);
+        assert!(is_managed_comment(&body));
+        assert!(!is_managed_comment("user text"));
+    }
+
     #[test]
     fn managed_pr_section_uses_base_commit_link_when_provided() {
         let body = managed_pr_section(
@@ -247,11 +487,89 @@ mod tests {
             Some("https://github.com/acme/repo/commit/abc123"),
             None,
             None,
+            None,
         );
         assert!(body.contains("[main](https://github.com/acme/repo/commit/abc123)"));
         assert!(!body.contains("/tree/main"));
     }
 
+    fn stack_entries() -> Vec<StackSectionEntry> {
+        let entry = |branch: &str, number: Option<i64>, depth, title: Option<&str>, current| {
+            StackSectionEntry {
+                branch: ManagedBranchRef {
+                    branch: branch.to_string(),
+                    pr_number: number,
+                    pr_url: None,
+                },
+                depth,
+                title: title.map(str::to_string),
+                state: number.map(|_| "open".to_string()),
+                current,
+            }
+        };
+        vec![
+            entry("feat/a", Some(12), 0, Some("Add a"), false),
+            entry("feat/b", Some(13), 1, Some("Fix b | c"), true),
+            entry("feat/c", None, 1, None, false),
+        ]
+    }
+
+    #[test]
+    fn managed_stack_section_renders_nested_list_with_current_marker() {
+        let body = managed_stack_section(
+            "https://github.com/acme/repo",
+            "main",
+            None,
+            &stack_entries(),
+            ManagedSectionFormat::List,
+            None,
+        );
+        assert!(body.starts_with(MANAGED_BODY_MARKER_START));
+        assert!(body.contains("Stack into [main](https://github.com/acme/repo/tree/main):"));
+        assert!(
+            body.contains(
+                "\n- [#12](https://github.com/acme/repo/pull/12) `feat/a` Add a (open)\n"
+            )
+        );
+        assert!(body.contains(
+            "\n  - [#13](https://github.com/acme/repo/pull/13) `feat/b` Fix b | c (open) ← this PR\n"
+        ));
+        assert!(body.contains("\n  - [feat/c](https://github.com/acme/repo/tree/feat/c)\n"));
+    }
+
+    #[test]
+    fn managed_stack_section_renders_table_rows_and_escapes_pipes() {
+        let body = managed_stack_section(
+            "https://github.com/acme/repo",
+            "main",
+            None,
+            &stack_entries(),
+            ManagedSectionFormat::Table,
+            None,
+        );
+        assert!(body.contains("| | PR | Branch | Title | State |"));
+        assert!(body.contains(
+            "|  | [#12](https://github.com/acme/repo/pull/12) | `feat/a` | Add a | open |"
+        ));
+        assert!(body.contains(
+            "| → | [#13](https://github.com/acme/repo/pull/13) | ↳ `feat/b` | Fix b \\| c | open |"
+        ));
+        assert!(body.contains("|  | — | ↳ `feat/c` |  |  |"));
+    }
+
+    #[test]
+    fn managed_section_format_parses_known_values() {
+        assert_eq!(
+            ManagedSectionFormat::parse("Table"),
+            Some(ManagedSectionFormat::Table)
+        );
+        assert_eq!(
+            ManagedSectionFormat::parse("list"),
+            Some(ManagedSectionFormat::List)
+        );
+        assert_eq!(ManagedSectionFormat::parse("tree"), None);
+    }
+
     #[test]
     fn managed_pr_section_escapes_labels_and_encodes_branch_paths() {
         let parent = ManagedBranchRef {
@@ -270,6 +588,7 @@ mod tests {
             None,
             Some(&parent),
             Some(&child),
+            None,
         );
         assert!(
             body.contains("[feat/paren\\]t](https://github.com/acme/repo/tree/feat/paren%5Dt)")
@@ -9,6 +9,8 @@ pub struct PrLinkTarget {
     pub head_ref: String,
 }
 
+/// Where a PR for `head` is opened: `stack.prRepo`/`stack.baseRemote` when
+/// configured, otherwise `upstream` when the head remote is a fork of it.
 pub fn determine_pr_link_target(git: &Git, base: &str, head: &str) -> Result<PrLinkTarget> {
     if base == head {
         return Err(anyhow!(
@@ -17,28 +19,35 @@ pub fn determine_pr_link_target(git: &Git, base: &str, head: &str) -> Result<PrL
         ));
     }
 
-    let head_remote = git
-        .remote_for_branch(head)?
-        .unwrap_or_else(|| "origin".to_string());
+    let head_remote = git.push_remote_for_branch(head, base)?;
     let head_url = git.remote_web_url(&head_remote)?;
 
-    let mut base_remote = git
-        .remote_for_branch(head)?
-        .or_else(|| git.remote_for_branch(base).ok().flatten())
-        .unwrap_or_else(|| "origin".to_string());
-
-    if let (Some(head_url), Some(upstream_url)) = (
-        head_url.as_deref(),
-        git.remote_web_url("upstream")?.as_deref(),
-    ) && let (Some(head_owner), Some(upstream_owner)) = (
-        github_owner_from_web_url(head_url),
-        github_owner_from_web_url(upstream_url),
-    ) && head_owner != upstream_owner
-    {
-        base_remote = "upstream".to_string();
-    }
+    let settings = git.remote_settings();
+    let base_remote = match &settings.base_remote {
+        Some(remote) => remote.clone(),
+        None => {
+            let mut base_remote = git
+                .remote_for_branch(head)?
+                .or_else(|| git.remote_for_branch(base).ok().flatten())
+                .unwrap_or_else(|| "origin".to_string());
+            if settings.pr_repo.is_none()
+                && let (Some(head_url), Some(upstream_url)) = (
+                    head_url.as_deref(),
+                    git.remote_web_url("upstream")?.as_deref(),
+                )
+                && let (Some(head_owner), Some(upstream_owner)) = (
+                    github_owner_from_web_url(head_url),
+                    github_owner_from_web_url(upstream_url),
+                )
+                && head_owner != upstream_owner
+            {
+                base_remote = "upstream".to_string();
+            }
+            base_remote
+        }
+    };
 
-    let Some(base_url) = git.remote_web_url(&base_remote)? else {
+    let Some(base_url) = git.pr_repo_web_url(&base_remote)? else {
         return Err(anyhow!(
             "unable to derive PR URL from remote '{}'; configure a GitHub-style remote URL",
             base_remote
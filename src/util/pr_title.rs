@@ -0,0 +1,146 @@
+use anyhow::{Result, anyhow};
+
+/// Position prefix kept on stacked PR titles, rendered from a pattern such as
+/// `[{index}/{total}]` and separated from the title by a single space.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrTitlePrefix {
+    pattern: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PrefixToken<'a> {
+    Literal(&'a str),
+    Number,
+}
+
+impl PrTitlePrefix {
+    pub const DEFAULT_PATTERN: &'static str = "[{index}/{total}]";
+
+    /// Parses `stack.prTitlePrefix`: boolean values toggle the default
+    /// pattern, anything else is a pattern that must contain `{index}`.
+    pub fn parse(raw: &str) -> Result<Option<Self>> {
+        let raw = raw.trim();
+        match raw.to_ascii_lowercase().as_str() {
+            "" | "false" | "off" | "no" => return Ok(None),
+            "true" | "on" | "yes" => {
+                return Ok(Some(Self {
+                    pattern: Self::DEFAULT_PATTERN.to_string(),
+                }));
+            }
+            _ => {}
+        }
+        let prefix = Self {
+            pattern: raw.to_string(),
+        };
+        let tokens = prefix.tokens()?;
+        if !raw.contains("{index}") {
+            return Err(anyhow!("title prefix '{raw}' must contain {{index}}"));
+        }
+        if tokens
+            .windows(2)
+            .any(|pair| pair == [PrefixToken::Number, PrefixToken::Number])
+        {
+            return Err(anyhow!(
+                "title prefix '{raw}' needs a separator between placeholders"
+            ));
+        }
+        Ok(Some(prefix))
+    }
+
+    pub fn render(&self, index: usize, total: usize) -> String {
+        self.pattern
+            .replace("{index}", &index.to_string())
+            .replace("{total}", &total.to_string())
+    }
+
+    /// Removes a prefix rendered from this pattern, with any numbers.
+    pub fn strip<'a>(&self, title: &'a str) -> &'a str {
+        let Ok(tokens) = self.tokens() else {
+            return title;
+        };
+        let mut rest = title.trim_start();
+        for token in tokens {
+            rest = match token {
+                PrefixToken::Literal(literal) => match rest.strip_prefix(literal) {
+                    Some(rest) => rest,
+                    None => return title,
+                },
+                PrefixToken::Number => {
+                    let digits =
+                        rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+                    if digits == 0 {
+                        return title;
+                    }
+                    &rest[digits..]
+                }
+            };
+        }
+        rest.trim_start()
+    }
+
+    /// Replaces any existing prefix on `title` with the one for this position.
+    pub fn apply(&self, title: &str, index: usize, total: usize) -> String {
+        let stripped = self.strip(title);
+        let prefix = self.render(index, total);
+        if stripped.is_empty() {
+            prefix
+        } else {
+            format!("{prefix} {stripped}")
+        }
+    }
+
+    fn tokens(&self) -> Result<Vec<PrefixToken<'_>>> {
+        let mut tokens = Vec::new();
+        let mut rest = self.pattern.as_str();
+        while let Some(open) = rest.find('{') {
+            if open > 0 {
+                tokens.push(PrefixToken::Literal(&rest[..open]));
+            }
+            let after = &rest[open..];
+            if let Some(tail) = after.strip_prefix("{index}") {
+                rest = tail;
+            } else if let Some(tail) = after.strip_prefix("{total}") {
+                rest = tail;
+            } else {
+                return Err(anyhow!(
+                    "unknown placeholder in title prefix '{}'; expected {{index}} or {{total}}",
+                    self.pattern
+                ));
+            }
+            tokens.push(PrefixToken::Number);
+        }
+        if !rest.is_empty() {
+            tokens.push(PrefixToken::Literal(rest));
+        }
+        Ok(tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_replaces_existing_prefix_idempotently() {
+        let prefix = PrTitlePrefix::parse("true").unwrap().expect("enabled");
+        assert_eq!(prefix.apply("Add parser", 2, 5), "[2/5] Add parser");
+        assert_eq!(prefix.apply("[3/4] Add parser", 2, 5), "[2/5] Add parser");
+        assert_eq!(prefix.apply("[2/5] Add parser", 2, 5), "[2/5] Add parser");
+        assert_eq!(
+            prefix.apply("[WIP] Add parser", 1, 1),
+            "[1/1] [WIP] Add parser"
+        );
+    }
+
+    #[test]
+    fn parse_accepts_custom_patterns_and_rejects_invalid_ones() {
+        let prefix = PrTitlePrefix::parse("({index} of {total}):")
+            .unwrap()
+            .expect("enabled");
+        assert_eq!(prefix.apply("(1 of 3): Fix", 2, 3), "(2 of 3): Fix");
+        assert!(PrTitlePrefix::parse("off").unwrap().is_none());
+        assert!(PrTitlePrefix::parse("[{total}]").is_err());
+        assert!(PrTitlePrefix::parse("[{index}{total}]").is_err());
+        assert!(PrTitlePrefix::parse("[{idx}]").is_err());
+    }
+}
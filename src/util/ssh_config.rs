@@ -0,0 +1,280 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Nesting limit for `Include`, matching OpenSSH.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// `HostName` settings from the user's and the system OpenSSH client config,
+/// used to resolve `Host` aliases in SSH remotes to the real host.
+///
+/// Only `Host`, `HostName` and `Include` are understood; `Match` blocks never
+/// apply.
+#[derive(Debug, Clone, Default)]
+pub struct SshConfig {
+    entries: Vec<SshHostEntry>,
+}
+
+#[derive(Debug, Clone)]
+struct SshHostEntry {
+    /// Every `Host` pattern list that must match, outermost first. An
+    /// `Include` inside a `Host` block adds the enclosing list.
+    conditions: Vec<Vec<String>>,
+    hostname: String,
+}
+
+impl SshConfig {
+    /// Reads `~/.ssh/config` then `/etc/ssh/ssh_config`. Missing or unreadable
+    /// files are ignored.
+    pub fn load() -> Self {
+        let mut config = Self::default();
+        if let Some(home) = std::env::var_os("HOME").filter(|home| !home.is_empty()) {
+            let ssh_dir = PathBuf::from(home).join(".ssh");
+            config.read_file(&ssh_dir.join("config"), &ssh_dir, &[], 0);
+        }
+        let system_dir = Path::new("/etc/ssh");
+        config.read_file(&system_dir.join("ssh_config"), system_dir, &[], 0);
+        config
+    }
+
+    /// Parses config text; relative `Include` paths resolve against
+    /// `include_dir`.
+    #[cfg(test)]
+    pub fn parse(text: &str, include_dir: &Path) -> Self {
+        let mut config = Self::default();
+        config.read_text(text, include_dir, &[], 0);
+        config
+    }
+
+    /// The real host behind `alias`: the first matching `HostName` (with `%h`
+    /// expanded), or `alias` itself.
+    pub fn hostname(&self, alias: &str) -> String {
+        self.entries
+            .iter()
+            .find(|entry| {
+                entry
+                    .conditions
+                    .iter()
+                    .all(|patterns| host_matches(alias, patterns))
+            })
+            .map(|entry| expand_tokens(&entry.hostname, alias))
+            .unwrap_or_else(|| alias.to_string())
+    }
+
+    fn read_file(&mut self, path: &Path, include_dir: &Path, outer: &[Vec<String>], depth: usize) {
+        if let Ok(text) = fs::read_to_string(path) {
+            self.read_text(&text, include_dir, outer, depth);
+        }
+    }
+
+    fn read_text(&mut self, text: &str, include_dir: &Path, outer: &[Vec<String>], depth: usize) {
+        let mut conditions = outer.to_vec();
+        for line in text.lines() {
+            let Some((keyword, args)) = split_directive(line) else {
+                continue;
+            };
+            match keyword.to_ascii_lowercase().as_str() {
+                "host" => {
+                    conditions = outer.to_vec();
+                    conditions.push(args);
+                }
+                // Match criteria (exec, user, ...) are not evaluated, so a
+                // pattern that never matches keeps the block inert.
+                "match" => {
+                    conditions = outer.to_vec();
+                    conditions.push(Vec::new());
+                }
+                "hostname" => {
+                    if let Some(hostname) = args.into_iter().next() {
+                        self.entries.push(SshHostEntry {
+                            conditions: conditions.clone(),
+                            hostname,
+                        });
+                    }
+                }
+                "include" if depth < MAX_INCLUDE_DEPTH => {
+                    for pattern in args {
+                        for path in expand_include(&pattern, include_dir) {
+                            self.read_file(&path, include_dir, &conditions, depth + 1);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Splits `Keyword args...` or `Keyword=args...`, honouring double quotes and
+/// skipping blank and comment lines.
+fn split_directive(line: &str) -> Option<(String, Vec<String>)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let split_at = line.find(|c: char| c.is_whitespace() || c == '=')?;
+    let (keyword, rest) = line.split_at(split_at);
+    let rest = rest.trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest);
+
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for ch in rest.chars() {
+        match ch {
+            '"' => quoted = !quoted,
+            '#' if !quoted && current.is_empty() => break,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        args.push(current);
+    }
+    Some((keyword.to_string(), args))
+}
+
+/// OpenSSH pattern-list semantics: some pattern matches and no `!pattern`
+/// does.
+fn host_matches(host: &str, patterns: &[String]) -> bool {
+    let host = host.to_ascii_lowercase();
+    let mut matched = false;
+    for pattern in patterns {
+        let pattern = pattern.to_ascii_lowercase();
+        match pattern.strip_prefix('!') {
+            Some(negated) if wildcard_match(negated, &host) => return false,
+            Some(_) => {}
+            None => matched |= wildcard_match(&pattern, &host),
+        }
+    }
+    matched
+}
+
+/// Matches `*` (any run) and `?` (one character).
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn expand_tokens(hostname: &str, alias: &str) -> String {
+    let mut out = String::with_capacity(hostname.len());
+    let mut chars = hostname.chars();
+    while let Some(ch) = chars.next() {
+        match (ch, chars.clone().next()) {
+            ('%', Some('h')) => {
+                out.push_str(alias);
+                chars.next();
+            }
+            ('%', Some('%')) => {
+                out.push('%');
+                chars.next();
+            }
+            (c, _) => out.push(c),
+        }
+    }
+    out
+}
+
+/// Resolves an `Include` argument (`~/`, relative or absolute, with wildcards
+/// in the file name) to existing files in lexical order.
+fn expand_include(pattern: &str, include_dir: &Path) -> Vec<PathBuf> {
+    let path = match pattern.strip_prefix("~/") {
+        Some(rest) => match std::env::var_os("HOME") {
+            Some(home) => PathBuf::from(home).join(rest),
+            None => return Vec::new(),
+        },
+        None => include_dir.join(pattern),
+    };
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return Vec::new();
+    };
+    if !name.contains(['*', '?']) {
+        return vec![path];
+    }
+    let Some(dir) = path.parent() else {
+        return Vec::new();
+    };
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut matches = read_dir
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|file| wildcard_match(name, file))
+        })
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+    matches.sort();
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hostname_resolves_aliases_wildcards_negations_and_includes() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        fs::create_dir_all(dir.path().join("conf.d")).expect("create conf.d");
+        fs::write(
+            dir.path().join("conf.d").join("10-corp.conf"),
+            "Host corp-*\n  HostName %h.git.corp.example\n",
+        )
+        .expect("write include");
+
+        let config = SshConfig::parse(
+            "Include conf.d/*.conf\n# personal\nHost github-work gh-*  !gh-skip\n  User git\n  HostName=github.com\n\
+             Host \"quoted\"\n  HostName \"quoted.example\" # trailing\n\
+             Match exec \"true\"\n  HostName never.example\n\
+             Host *\n  HostName fallback-%%.example\n",
+            dir.path(),
+        );
+        assert_eq!(config.hostname("github-work"), "github.com");
+        assert_eq!(config.hostname("GH-Personal"), "github.com");
+        assert_eq!(config.hostname("gh-skip"), "fallback-%.example");
+        assert_eq!(config.hostname("quoted"), "quoted.example");
+        assert_eq!(config.hostname("corp-eu"), "corp-eu.git.corp.example");
+        assert_eq!(
+            SshConfig::default().hostname("github.com"),
+            "github.com".to_string()
+        );
+    }
+
+    #[test]
+    fn include_inside_host_block_only_applies_to_that_host() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        fs::write(dir.path().join("work"), "HostName github.com\n").expect("write include");
+        let config = SshConfig::parse("Host work\n  Include work\n", dir.path());
+        assert_eq!(config.hostname("work"), "github.com");
+        assert_eq!(config.hostname("home"), "home");
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow};
+
+/// Renders a small mustache-style template.
+///
+/// `{{name}}` inserts a variable, `{{#name}}...{{/name}}` keeps its body only
+/// when the variable is non-empty and `{{^name}}...{{/name}}` only when it is
+/// empty. Unknown variables are errors so typos surface immediately.
+pub fn render_template(template: &str, vars: &HashMap<&str, String>) -> Result<String> {
+    render(template, vars, false)
+}
+
+/// Checks `template` against `vars` without rendering it. Unlike
+/// [`render_template`], section bodies are checked whether or not they would
+/// be kept, so a typo inside `{{#name}}...{{/name}}` is caught up front.
+pub fn check_template(template: &str, vars: &HashMap<&str, String>) -> Result<()> {
+    render(template, vars, true).map(|_| ())
+}
+
+fn render(template: &str, vars: &HashMap<&str, String>, check_all: bool) -> Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find("{{") {
+        out.push_str(&rest[..open]);
+        let after_open = &rest[open + 2..];
+        let close = after_open
+            .find("}}")
+            .ok_or_else(|| anyhow!("unterminated '{{{{' in template"))?;
+        let tag = after_open[..close].trim();
+        rest = &after_open[close + 2..];
+
+        if let Some(name) = tag.strip_prefix('#').or_else(|| tag.strip_prefix('^')) {
+            let name = name.trim();
+            let end_tag = format!("{{{{/{name}}}}}");
+            let end = rest
+                .find(&end_tag)
+                .ok_or_else(|| anyhow!("missing '{end_tag}' in template"))?;
+            let inner = &rest[..end];
+            rest = &rest[end + end_tag.len()..];
+            let present = !lookup(vars, name)?.is_empty();
+            if present == tag.starts_with('#') {
+                out.push_str(&render(inner, vars, check_all)?);
+            } else if check_all {
+                render(inner, vars, check_all)?;
+            }
+        } else if let Some(name) = tag.strip_prefix('/') {
+            return Err(anyhow!("unexpected '{{{{/{}}}}}' in template", name.trim()));
+        } else {
+            out.push_str(lookup(vars, tag)?);
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn lookup<'a>(vars: &'a HashMap<&str, String>, name: &str) -> Result<&'a str> {
+    vars.get(name)
+        .map(String::as_str)
+        .ok_or_else(|| anyhow!("unknown template variable '{name}'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> HashMap<&'static str, String> {
+        HashMap::from([
+            ("parent_pr", "#12".to_string()),
+            ("base", "main".to_string()),
+            ("empty", String::new()),
+        ])
+    }
+
+    #[test]
+    fn render_template_substitutes_variables_and_sections() {
+        let out = render_template(
+            "into {{ base }}{{#parent_pr}}, merge after {{parent_pr}}{{/parent_pr}}{{^empty}}!{{/empty}}{{#empty}}?{{/empty}}",
+            &vars(),
+        )
+        .unwrap();
+        assert_eq!(out, "into main, merge after #12!");
+    }
+
+    #[test]
+    fn render_template_rejects_unknown_variables_and_unbalanced_tags() {
+        let err = render_template("{{nope}}", &vars()).unwrap_err();
+        assert!(err.to_string().contains("unknown template variable 'nope'"));
+        assert!(render_template("{{#base}}open", &vars()).is_err());
+        assert!(render_template("{{/base}}", &vars()).is_err());
+        assert!(render_template("{{base", &vars()).is_err());
+    }
+
+    #[test]
+    fn check_template_looks_inside_skipped_sections() {
+        let template = "{{#empty}}{{nope}}{{/empty}}";
+        assert!(render_template(template, &vars()).is_ok());
+        let err = check_template(template, &vars()).unwrap_err();
+        assert!(err.to_string().contains("unknown template variable 'nope'"));
+        assert!(check_template("{{^base}}{{#empty}}{{base}}{{/empty}}{{/base}}", &vars()).is_ok());
+    }
+}
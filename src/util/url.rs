@@ -1,3 +1,76 @@
+use anyhow::{Result, anyhow};
+
+pub const GITHUB_HOST: &str = "github.com";
+
+/// Maps git remote hosts to the web host of the GitHub instance serving them,
+/// configured as `stack.hostMapping = <remote-host>=<web-host>` entries.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HostMap {
+    entries: Vec<(String, String)>,
+}
+
+impl HostMap {
+    /// Parses mapping entries; the target may be a hostname, a web URL or a
+    /// GHES API base such as `https://github.corp.example/api/v3`.
+    pub fn parse<'a>(values: impl IntoIterator<Item = &'a str>) -> Result<Self> {
+        let mut entries = Vec::new();
+        for value in values {
+            let (from, to) = value
+                .split_once('=')
+                .map(|(from, to)| (from.trim(), normalize_host(to)))
+                .filter(|(from, to)| !from.is_empty() && !to.is_empty())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "invalid stack.hostMapping '{value}'; expected <remote-host>=<web-host>"
+                    )
+                })?;
+            entries.push((from.to_ascii_lowercase(), to));
+        }
+        Ok(Self { entries })
+    }
+
+    /// The web host for `remote_host`; the last matching entry wins.
+    pub fn web_host<'a>(&'a self, remote_host: &'a str) -> &'a str {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(from, _)| from.eq_ignore_ascii_case(remote_host))
+            .map_or(remote_host, |(_, to)| to.as_str())
+    }
+
+    /// Rewrites the host of a web URL produced from a remote.
+    pub fn apply(&self, web_url: &str) -> String {
+        match web_url_host(web_url) {
+            Some(host) => {
+                let mapped = self.web_host(host);
+                if mapped == host {
+                    web_url.to_string()
+                } else {
+                    web_url.replacen(host, mapped, 1)
+                }
+            }
+            None => web_url.to_string(),
+        }
+    }
+}
+
+fn normalize_host(raw: &str) -> String {
+    let raw = raw.trim();
+    let without_scheme = raw.split_once("://").map_or(raw, |(_, rest)| rest);
+    without_scheme
+        .split('/')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// Host part of an `https://host/...` web URL.
+pub fn web_url_host(url: &str) -> Option<&str> {
+    let (_, rest) = url.split_once("://")?;
+    let host = rest.split('/').next()?;
+    (!host.is_empty()).then_some(host)
+}
+
 pub fn url_encode_component(value: &str) -> String {
     let mut out = String::with_capacity(value.len());
     for b in value.bytes() {
@@ -70,6 +143,29 @@ pub fn github_repo_slug_from_web_url(url: &str) -> Option<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn host_map_rewrites_mapped_hosts_and_accepts_api_bases() {
+        let map = HostMap::parse([
+            "git.corp.example=https://github.corp.example/api/v3",
+            "ssh.other.example = code.other.example",
+        ])
+        .expect("valid mappings");
+        assert_eq!(
+            map.apply("https://git.corp.example/acme/repo"),
+            "https://github.corp.example/acme/repo"
+        );
+        assert_eq!(
+            map.apply("https://ssh.other.example/acme/repo"),
+            "https://code.other.example/acme/repo"
+        );
+        assert_eq!(
+            map.apply("https://github.com/acme/repo"),
+            "https://github.com/acme/repo"
+        );
+        assert!(HostMap::parse(["git.corp.example"]).is_err());
+        assert!(HostMap::parse(["=github.corp.example"]).is_err());
+    }
+
     #[test]
     fn github_repo_slug_from_web_url_parses_owner_and_repo() {
         let slug = github_repo_slug_from_web_url("https://github.com/acme/repo")
@@ -8,6 +8,11 @@ pub struct BranchView {
     pub cached_pr_number: Option<i64>,
     pub cached_pr_state: Option<String>,
     pub exists_in_git: bool,
+    pub codeowners: Vec<String>,
+    pub description: Option<String>,
+    pub ticket: Option<String>,
+    pub owner: Option<String>,
+    pub frozen: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
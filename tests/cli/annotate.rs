@@ -0,0 +1,119 @@
+#[test]
+fn annotate_sets_and_clears_fields_shown_in_tree_and_porcelain() {
+    let repo = init_repo();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/parser"])
+        .assert()
+        .success();
+
+    stack_cmd(repo.path())
+        .args([
+            "annotate",
+            "--description",
+            "Parser rewrite",
+            "--ticket",
+            "ABC-123",
+            "--owner",
+            "alice",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "updated annotations for 'feat/parser'",
+        ))
+        .stdout(predicate::str::contains("ticket: ABC-123"));
+
+    stack_cmd(repo.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "feat/parser [SYNC:tracked] [ABC-123] @alice — Parser rewrite",
+        ));
+
+    stack_cmd(repo.path())
+        .args(["annotate", "feat/parser", "--owner", "", "--porcelain"])
+        .assert()
+        .success();
+    let output = stack_cmd(repo.path())
+        .arg("--porcelain")
+        .output()
+        .expect("run stack --porcelain");
+    let rows: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    let parser = rows
+        .as_array()
+        .expect("branch array")
+        .iter()
+        .find(|row| row["name"] == "feat/parser")
+        .expect("feat/parser entry")
+        .clone();
+    assert_eq!(parser["description"], "Parser rewrite");
+    assert_eq!(parser["ticket"], "ABC-123");
+    assert_eq!(parser["owner"], Value::Null);
+
+    stack_cmd(repo.path())
+        .args(["annotate", "--clear"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("'feat/parser' has no annotations"));
+
+    stack_cmd(repo.path())
+        .args(["annotate", "feat/missing", "--ticket", "X-1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("branch 'feat/missing' is not tracked"));
+}
+#[test]
+fn pr_preview_body_links_ticket_through_configured_url() {
+    let repo = init_repo();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/parser"])
+        .assert()
+        .success();
+    stack_cmd(repo.path())
+        .args(["annotate", "--ticket", "ABC-123", "--owner", "alice"])
+        .assert()
+        .success();
+    run_git(
+        repo.path(),
+        &[
+            "config",
+            "stack.ticketUrl",
+            "https://issues.example.com/browse/{ticket}",
+        ],
+    );
+
+    let output = stack_cmd(repo.path())
+        .args(["pr", "--preview-body", "--porcelain"])
+        .output()
+        .expect("run stack pr --preview-body");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    let body = json["body"].as_str().expect("body string");
+    assert!(
+        body.contains(
+            "**Ticket:** [ABC-123](https://issues.example.com/browse/ABC-123) · **Owner:** alice"
+        ),
+        "unexpected body: {body}"
+    );
+
+    run_git(
+        repo.path(),
+        &["config", "stack.prBodyTemplate", "Fixes {{ticket_link}}"],
+    );
+    let output = stack_cmd(repo.path())
+        .args(["pr", "--preview-body"])
+        .output()
+        .expect("run stack pr --preview-body");
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .contains("Fixes [ABC-123](https://issues.example.com/browse/ABC-123)"));
+
+    run_git(
+        repo.path(),
+        &["config", "stack.ticketUrl", "https://issues.example.com/"],
+    );
+    stack_cmd(repo.path())
+        .args(["pr", "--preview-body"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid stack.ticketUrl"));
+}
@@ -0,0 +1,425 @@
+#[cfg(unix)]
+fn init_repo_with_release_branch() -> TempDir {
+    let repo = init_repo_without_origin();
+    let origin_bare = repo.path().join("origin.git");
+    run_git(
+        repo.path(),
+        &["init", "--bare", origin_bare.to_str().expect("origin bare")],
+    );
+    run_git(
+        repo.path(),
+        &[
+            "remote",
+            "add",
+            "origin",
+            origin_bare.to_str().expect("origin bare"),
+        ],
+    );
+    run_git(repo.path(), &["push", "--set-upstream", "origin", "main"]);
+    run_git(repo.path(), &["branch", "release/1.0"]);
+    run_git(repo.path(), &["push", "origin", "release/1.0"]);
+
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/a"])
+        .assert()
+        .success();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "fix/r"])
+        .assert()
+        .success();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "fix/r", "--name", "fix/child"])
+        .assert()
+        .success();
+    repo
+}
+
+#[cfg(unix)]
+#[test]
+fn base_set_moves_a_stack_onto_a_release_branch() {
+    let repo = init_repo_with_release_branch();
+
+    stack_cmd(repo.path())
+        .args(["base", "set", "release/1.0", "--root", "fix/child"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "stack 'fix/r' now targets 'release/1.0' (was 'main')",
+        ));
+
+    let output = stack_cmd(repo.path())
+        .args(["base", "list", "--porcelain"])
+        .output()
+        .expect("run base list");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(
+        json,
+        serde_json::json!([
+            { "base": "main", "default": true, "roots": ["feat/a"] },
+            { "base": "release/1.0", "default": false, "roots": ["fix/r"] },
+        ])
+    );
+
+    let output = stack_cmd(repo.path())
+        .args(["push", "--porcelain"])
+        .output()
+        .expect("run push");
+    assert!(
+        output.status.success(),
+        "push failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    let pushed = json["pushed"]
+        .as_array()
+        .expect("pushed")
+        .iter()
+        .map(|p| p["branch"].as_str().expect("branch").to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(pushed, ["feat/a", "fix/child", "fix/r"]);
+
+    run_git(repo.path(), &["checkout", "fix/child"]);
+    stack_cmd(repo.path())
+        .arg("bottom")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("switched: fix/child -> fix/r"));
+    run_git(repo.path(), &["checkout", "release/1.0"]);
+    stack_cmd(repo.path())
+        .arg("bottom")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "base branch 'release/1.0' is not part of stack navigation",
+        ));
+
+    stack_cmd(repo.path())
+        .args(["base", "set", "fix/r", "--root", "feat/a"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'fix/r' is a stacked branch"));
+
+    stack_cmd(repo.path())
+        .args(["base", "set", "main", "--root", "fix/r"])
+        .assert()
+        .success();
+    let output = stack_cmd(repo.path())
+        .args(["base", "list", "--porcelain"])
+        .output()
+        .expect("run base list");
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(json.as_array().expect("bases").len(), 1);
+    assert_eq!(json[0]["roots"], serde_json::json!(["feat/a", "fix/r"]));
+}
+
+#[cfg(unix)]
+#[test]
+fn sync_restacks_children_of_merged_roots_onto_their_own_base() {
+    let repo = init_repo_with_release_branch();
+    stack_cmd(repo.path())
+        .args([
+            "base",
+            "set",
+            "release/1.0",
+            "--root",
+            "fix/r",
+            "--restack",
+        ])
+        .assert()
+        .success();
+    run_git(repo.path(), &["checkout", "release/1.0"]);
+    fs::write(repo.path().join("RELEASE.md"), "1.0.1\n").expect("write release note");
+    run_git(repo.path(), &["add", "RELEASE.md"]);
+    run_git(repo.path(), &["commit", "-m", "release fix"]);
+    run_git(repo.path(), &["push", "origin", "release/1.0"]);
+    run_git(repo.path(), &["checkout", "main"]);
+
+    let fake_bin = repo.path().join("fake-bin-release");
+    fs::create_dir_all(&fake_bin).expect("create fake bin dir");
+    let fake_gh = fake_bin.join("gh");
+    fs::write(
+        &fake_gh,
+        "#!/usr/bin/env bash\nif [[ \"$1\" == \"pr\" && \"$2\" == \"list\" ]]; then\n  echo '[{\"number\":21,\"state\":\"MERGED\",\"baseRefName\":\"release/1.0\",\"headRefName\":\"fix/r\",\"mergeCommit\":null,\"body\":\"\",\"url\":\"https://github.com/acme/stack-test/pull/21\"}]'\n  exit 0\nfi\necho '[]'\n",
+    )
+    .expect("write fake gh");
+    fs::set_permissions(&fake_gh, fs::Permissions::from_mode(0o755)).expect("chmod fake gh");
+    let test_path = format!(
+        "{}:{}",
+        fake_bin.display(),
+        env::var("PATH").unwrap_or_default()
+    );
+
+    let output = stack_cmd(repo.path())
+        .env("PATH", &test_path)
+        .args(["sync", "--dry-run", "--porcelain"])
+        .output()
+        .expect("run sync dry-run");
+    assert!(
+        output.status.success(),
+        "sync dry-run failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    let ops = json["operations"].as_array().expect("operations array");
+    assert!(
+        ops.iter().any(|op| op["kind"] == "restack"
+            && op["branch"] == "fix/child"
+            && op["onto"] == "origin/release/1.0"),
+        "expected fix/child to restack onto origin/release/1.0: {ops:?}"
+    );
+    assert!(
+        !ops.iter()
+            .any(|op| op["kind"] == "restack" && op["branch"] == "release/1.0"),
+        "release base must never be restacked: {ops:?}"
+    );
+}
+
+#[test]
+fn init_chooses_the_base_branch_once() {
+    let repo = init_repo_without_origin();
+    run_git(repo.path(), &["branch", "trunk"]);
+
+    stack_cmd(repo.path())
+        .args(["init", "--base", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "base branch 'missing' does not exist locally",
+        ));
+    stack_cmd(repo.path())
+        .args(["init", "--base", "trunk"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "initialized stack with base branch 'trunk'",
+        ));
+
+    let output = stack_cmd(repo.path())
+        .args(["init", "--porcelain"])
+        .output()
+        .expect("run init");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(
+        json,
+        serde_json::json!({ "base_branch": "trunk", "initialized": false })
+    );
+
+    stack_cmd(repo.path())
+        .args(["init", "--base", "main"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("use `stack base set <branch>`"));
+}
+
+#[test]
+fn base_set_without_root_migrates_the_trunk_after_a_rename() {
+    let repo = init_repo();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/a"])
+        .assert()
+        .success();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "feat/a", "--name", "feat/b"])
+        .assert()
+        .success();
+    run_git(repo.path(), &["branch", "-m", "main", "develop"]);
+
+    let output = stack_cmd(repo.path())
+        .args(["base", "set", "develop", "--porcelain"])
+        .output()
+        .expect("run base set");
+    assert!(
+        output.status.success(),
+        "base set failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(json["previous_base"], "main");
+    assert_eq!(json["reparented"], serde_json::json!(["feat/a"]));
+
+    let output = stack_cmd(repo.path())
+        .arg("--porcelain")
+        .output()
+        .expect("run stack porcelain");
+    let branches: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    let branches = branches.as_array().expect("branches");
+    assert!(branches.iter().all(|b| b["name"] != "main"));
+    assert!(
+        branches
+            .iter()
+            .any(|b| b["name"] == "feat/a" && b["parent"] == "develop")
+    );
+
+    stack_cmd(repo.path())
+        .args(["base", "set", "develop"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("trunk is already 'develop'"));
+}
+
+#[test]
+fn base_set_restack_rebases_roots_onto_the_new_trunk() {
+    let repo = init_repo();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/a"])
+        .assert()
+        .success();
+    fs::write(repo.path().join("a.txt"), "a\n").expect("write a");
+    run_git(repo.path(), &["add", "a.txt"]);
+    run_git(repo.path(), &["commit", "-m", "feat a"]);
+    run_git(repo.path(), &["checkout", "-b", "next", "main"]);
+    fs::write(repo.path().join("next.txt"), "next\n").expect("write next");
+    run_git(repo.path(), &["add", "next.txt"]);
+    run_git(repo.path(), &["commit", "-m", "next"]);
+
+    stack_cmd(repo.path())
+        .args(["base", "set", "next", "--restack"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "trunk changed from 'main' to 'next'; reparented 1 stack root(s)",
+        ))
+        .stdout(predicate::str::contains("feat/a (rebased onto 'next')"));
+    run_git(repo.path(), &["merge-base", "--is-ancestor", "next", "feat/a"]);
+}
+
+#[test]
+fn base_set_without_root_retargets_open_root_prs_except_frozen_ones() {
+    let repo = init_repo();
+    let provider_file = repo.path().join(".git").join("stack-provider.json");
+    fs::write(
+        &provider_file,
+        r#"{"prs":[
+            {"number":1,"head":"feat/a","base":"main"},
+            {"number":2,"head":"feat/b","base":"feat/a"},
+            {"number":3,"head":"feat/frozen","base":"main"}
+        ]}"#,
+    )
+    .expect("write provider file");
+    let stack = || {
+        let mut cmd = stack_cmd(repo.path());
+        cmd.env("STACK_PROVIDER", "file:.git/stack-provider.json");
+        cmd
+    };
+    for (parent, name) in [
+        ("main", "feat/a"),
+        ("feat/a", "feat/b"),
+        ("main", "feat/frozen"),
+    ] {
+        stack()
+            .args(["create", "--parent", parent, "--name", name])
+            .assert()
+            .success();
+    }
+    stack().args(["freeze", "feat/frozen"]).assert().success();
+    run_git(repo.path(), &["branch", "-m", "main", "develop"]);
+
+    let output = stack()
+        .args(["base", "set", "develop", "--porcelain"])
+        .output()
+        .expect("run base set");
+    assert!(
+        output.status.success(),
+        "base set failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(json["retargeted"], serde_json::json!([1]));
+
+    let state: Value =
+        serde_json::from_str(&fs::read_to_string(&provider_file).expect("read provider file"))
+            .expect("provider json");
+    let bases = state["prs"]
+        .as_array()
+        .expect("prs array")
+        .iter()
+        .map(|pr| (pr["number"].as_i64().expect("number"), pr["base"].clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        bases,
+        [
+            (1, Value::from("develop")),
+            (2, Value::from("feat/a")),
+            (3, Value::from("main")),
+        ]
+    );
+}
+
+#[test]
+fn base_set_with_root_retargets_the_root_pr() {
+    let repo = init_repo();
+    let provider_file = repo.path().join(".git").join("stack-provider.json");
+    fs::write(
+        &provider_file,
+        r#"{"prs":[
+            {"number":1,"head":"feat/a","base":"main"},
+            {"number":2,"head":"feat/b","base":"feat/a"}
+        ]}"#,
+    )
+    .expect("write provider file");
+    let stack = || {
+        let mut cmd = stack_cmd(repo.path());
+        cmd.env("STACK_PROVIDER", "file:.git/stack-provider.json");
+        cmd
+    };
+    for (parent, name) in [("main", "feat/a"), ("feat/a", "feat/b")] {
+        stack()
+            .args(["create", "--parent", parent, "--name", name])
+            .assert()
+            .success();
+    }
+    run_git(repo.path(), &["branch", "release/1.0", "main"]);
+
+    let output = stack()
+        .args(["base", "set", "release/1.0", "--root", "feat/b", "--porcelain"])
+        .output()
+        .expect("run base set");
+    assert!(
+        output.status.success(),
+        "base set failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(json["root"], "feat/a");
+    assert_eq!(json["retargeted"], 1);
+
+    let state: Value =
+        serde_json::from_str(&fs::read_to_string(&provider_file).expect("read provider file"))
+            .expect("provider json");
+    assert_eq!(state["prs"][0]["base"], "release/1.0");
+    assert_eq!(state["prs"][1]["base"], "feat/a");
+}
+
+#[test]
+fn base_set_restack_aborts_a_conflicting_rebase() {
+    let repo = init_repo();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/a"])
+        .assert()
+        .success();
+    fs::write(repo.path().join("README.md"), "feature\n").expect("write readme");
+    run_git(repo.path(), &["commit", "-am", "feat a"]);
+    run_git(repo.path(), &["checkout", "-b", "release/1.0", "main"]);
+    fs::write(repo.path().join("README.md"), "release\n").expect("write readme");
+    run_git(repo.path(), &["commit", "-am", "release"]);
+    run_git(repo.path(), &["checkout", "main"]);
+
+    stack_cmd(repo.path())
+        .args(["base", "set", "release/1.0", "--root", "feat/a", "--restack"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "failed to rebase 'feat/a' onto 'release/1.0'; the rebase was aborted",
+        ));
+
+    assert!(!repo.path().join(".git").join("rebase-merge").exists());
+    assert!(!repo.path().join(".git").join("rebase-apply").exists());
+    let head = Command::new("git")
+        .current_dir(repo.path())
+        .args(["branch", "--show-current"])
+        .output()
+        .expect("show current branch");
+    assert_eq!(String::from_utf8_lossy(&head.stdout).trim(), "main");
+}
@@ -192,3 +192,54 @@ fn create_insert_before_without_child_in_non_interactive_mode_assumes_only_viabl
             "assuming child branch 'feat/child' (only viable branch)",
         ));
 }
+
+#[test]
+fn create_insert_refreshes_only_the_stack_it_changes() {
+    let repo = init_repo();
+    run_git(repo.path(), &["config", "stack.prBodyFormat", "list"]);
+    let provider_file = repo.path().join(".git").join("stack-provider.json");
+    fs::write(
+        &provider_file,
+        r#"{"prs":[
+            {"number":1,"head":"feat/a","base":"main","body":"a body"},
+            {"number":2,"head":"feat/b","base":"feat/a","body":"b body"},
+            {"number":3,"head":"feat/x","base":"main","body":"x body"}
+        ]}"#,
+    )
+    .expect("write provider file");
+    let stack = || {
+        let mut cmd = stack_cmd(repo.path());
+        cmd.env("STACK_PROVIDER", "file:.git/stack-provider.json");
+        cmd
+    };
+    for (parent, name) in [("main", "feat/a"), ("feat/a", "feat/b"), ("main", "feat/x")] {
+        run_git(repo.path(), &["branch", name, parent]);
+        stack()
+            .args(["track", name, "--parent", parent])
+            .assert()
+            .success();
+    }
+
+    stack()
+        .args(["create", "--insert", "feat/b", "--name", "feat/mid"])
+        .assert()
+        .success();
+
+    let state: Value =
+        serde_json::from_str(&fs::read_to_string(&provider_file).expect("read provider file"))
+            .expect("provider json");
+    let body = |number: i64| {
+        state["prs"]
+            .as_array()
+            .expect("prs array")
+            .iter()
+            .find(|pr| pr["number"] == number)
+            .expect("pr")["body"]
+            .as_str()
+            .expect("body")
+            .to_string()
+    };
+    assert!(body(1).contains("feat/mid"), "got: {}", body(1));
+    assert!(body(2).contains("feat/mid"), "got: {}", body(2));
+    assert_eq!(body(3), "x body");
+}
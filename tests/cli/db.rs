@@ -0,0 +1,131 @@
+#[test]
+fn db_info_reports_pending_migrations_without_applying_them() {
+    let repo = init_repo();
+    let db_info = |repo: &Path| -> Value {
+        let output = stack_cmd(repo)
+            .args(["db", "info", "--porcelain"])
+            .output()
+            .expect("run stack db info");
+        assert!(output.status.success());
+        serde_json::from_slice(&output.stdout).expect("valid json")
+    };
+
+    let before = db_info(repo.path());
+    assert_eq!(before["exists"], false);
+    assert_eq!(before["schema_version"], 0);
+    let latest = before["latest_version"].as_i64().expect("latest version");
+    assert_eq!(
+        before["pending"].as_array().expect("pending").len() as i64,
+        latest
+    );
+    assert!(!repo.path().join(".git").join("stack.db").exists());
+
+    stack_cmd(repo.path()).assert().success();
+    let after = db_info(repo.path());
+    assert_eq!(after["schema_version"], latest);
+    assert!(after["pending"].as_array().expect("pending").is_empty());
+    assert_eq!(after["applied"][0]["version"], 1);
+
+    stack_cmd(repo.path())
+        .args(["db", "info"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "schema version: {latest} (latest {latest})"
+        )))
+        .stdout(predicate::str::contains("pending migrations: none"));
+}
+
+#[test]
+fn newer_schema_version_is_refused_with_a_clear_error() {
+    let repo = init_repo();
+    stack_cmd(repo.path()).assert().success();
+    let conn = Connection::open(repo.path().join(".git").join("stack.db")).expect("open db");
+    conn.execute(
+        "INSERT INTO schema_migrations(version, description) VALUES (999, 'from the future')",
+        [],
+    )
+    .expect("insert future migration");
+
+    stack_cmd(repo.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "stack.db schema version 999 is newer than this stack supports",
+        ));
+    stack_cmd(repo.path())
+        .args(["db", "info"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "schema version: 999 (newer than this stack supports",
+        ));
+}
+
+#[test]
+fn db_restore_previews_parent_link_changes_and_keeps_the_replaced_db() {
+    let repo = init_repo();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/a"])
+        .assert()
+        .success();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "feat/a", "--name", "feat/b"])
+        .assert()
+        .success();
+    stack_cmd(repo.path())
+        .args(["db", "backups"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("no backups yet"));
+
+    stack_cmd(repo.path())
+        .args(["doctor", "--fix"])
+        .assert()
+        .success();
+    stack_cmd(repo.path())
+        .args(["track", "feat/b", "--parent", "main", "--force"])
+        .assert()
+        .success();
+
+    let output = stack_cmd(repo.path())
+        .args(["db", "backups", "--porcelain"])
+        .output()
+        .expect("run stack db backups");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(json["backups"][0]["id"], 1);
+    assert_eq!(json["backups"][0]["reason"], "doctor-fix");
+
+    stack_cmd(repo.path())
+        .args(["db", "restore", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("feat/b: main -> feat/a"))
+        .stdout(predicate::str::contains("restore not applied"));
+    stack_cmd(repo.path())
+        .args(["db", "restore", "7"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no backup #7"));
+
+    let output = stack_cmd(repo.path())
+        .args(["db", "restore", "1", "--yes", "--porcelain"])
+        .output()
+        .expect("run stack db restore");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(json["restored"], true);
+    assert_eq!(json["saved_backup"], 2);
+    assert_eq!(json["changes"][0]["branch"], "feat/b");
+    assert_eq!(json["changes"][0]["parent"], "main");
+    assert_eq!(json["changes"][0]["restored_parent"], "feat/a");
+
+    // Restoring the pre-restore snapshot undoes the restore.
+    stack_cmd(repo.path())
+        .args(["db", "restore", "2", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("feat/b: feat/a -> main"))
+        .stdout(predicate::str::contains("saved as backup #3"));
+}
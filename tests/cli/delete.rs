@@ -90,3 +90,57 @@ fn delete_without_branch_in_non_interactive_mode_requires_argument_when_multiple
             "branch required in non-interactive mode",
         ));
 }
+
+#[test]
+fn delete_refreshes_sections_of_the_deleted_branch_stack_only() {
+    let repo = init_repo_without_origin();
+    run_git(
+        repo.path(),
+        &[
+            "remote",
+            "add",
+            "origin",
+            "git@github.com:acme/stack-test.git",
+        ],
+    );
+    run_git(repo.path(), &["config", "branch.main.remote", "no-fetch"]);
+    let provider_file = repo.path().join(".git").join("stack-provider.json");
+    fs::write(
+        &provider_file,
+        r#"{"prs":[
+            {"number":71,"head":"feat/a","base":"main"},
+            {"number":72,"head":"feat/b","base":"feat/a"},
+            {"number":73,"head":"feat/c","base":"feat/b"},
+            {"number":79,"head":"feat/z","base":"main"}
+        ]}"#,
+    )
+    .expect("write provider file");
+    let stack = |args: &[&str]| {
+        stack_cmd(repo.path())
+            .env("STACK_PROVIDER", "file:.git/stack-provider.json")
+            .args(args)
+            .assert()
+            .success()
+    };
+    stack(&["create", "--parent", "main", "--name", "feat/a"]);
+    stack(&["create", "--parent", "feat/a", "--name", "feat/b"]);
+    stack(&["create", "--parent", "feat/b", "--name", "feat/c"]);
+    stack(&["create", "--parent", "main", "--name", "feat/z"]);
+    stack(&["sync", "--yes"]);
+
+    let read_state = || -> Value {
+        serde_json::from_str(&fs::read_to_string(&provider_file).expect("read provider file"))
+            .expect("provider json")
+    };
+    let mut state = read_state();
+    state["prs"][3]["body"] = Value::from("left alone");
+    fs::write(&provider_file, state.to_string()).expect("write provider file");
+
+    stack(&["--yes", "delete", "feat/b"]);
+
+    let state = read_state();
+    let body_c = state["prs"][2]["body"].as_str().expect("feat/c body");
+    assert!(body_c.contains("/pull/71"), "got: {body_c}");
+    assert!(!body_c.contains("/pull/72"), "got: {body_c}");
+    assert_eq!(state["prs"][3]["body"], "left alone");
+}
@@ -133,3 +133,32 @@ fn doctor_fix_breaks_parent_cycles() {
         .any(|issue| issue["code"] == "cycle");
     assert!(!has_cycle, "expected cycle issues to be fixed");
 }
+
+#[test]
+fn doctor_flags_unknown_remotes_and_mismatched_pr_repo() {
+    let repo = init_repo();
+    run_git(repo.path(), &["config", "stack.pushRemote", "missing"]);
+    run_git(repo.path(), &["config", "stack.prRepo", "other/stack-test"]);
+    stack_cmd(repo.path()).assert().success();
+
+    let output = stack_cmd(repo.path())
+        .args(["doctor", "--porcelain"])
+        .output()
+        .expect("run doctor");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    let codes = json["issues"]
+        .as_array()
+        .expect("issues")
+        .iter()
+        .map(|issue| issue["code"].as_str().unwrap_or_default().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(codes, ["unknown_remote", "pr_repo_mismatch"]);
+
+    run_git(repo.path(), &["config", "stack.prRepo", "not-a-repo"]);
+    stack_cmd(repo.path())
+        .arg("doctor")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid stack.prRepo"));
+}
@@ -0,0 +1,125 @@
+#[test]
+fn export_and_import_rebuild_links_in_a_fresh_database() {
+    let repo = init_repo();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/a"])
+        .assert()
+        .success();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "feat/a", "--name", "feat/b"])
+        .assert()
+        .success();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/other"])
+        .assert()
+        .success();
+
+    run_git(repo.path(), &["checkout", "feat/b"]);
+    let output = stack_cmd(repo.path())
+        .args(["export", "--stack"])
+        .output()
+        .expect("run stack export");
+    assert!(output.status.success());
+    let doc: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(doc["version"], 1);
+    assert_eq!(doc["base_branch"], "main");
+    let names = doc["branches"]
+        .as_object()
+        .expect("branches")
+        .keys()
+        .cloned()
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["feat/a", "feat/b", "main"]);
+
+    let export_path = repo.path().join("stack-export.json");
+    stack_cmd(repo.path())
+        .args(["export", "--output", export_path.to_str().expect("path")])
+        .assert()
+        .success();
+    fs::remove_file(repo.path().join(".git").join("stack.db")).expect("remove db");
+
+    stack_cmd(repo.path())
+        .args(["import", export_path.to_str().expect("path"), "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "would link 'feat/b' -> 'feat/a' (newly tracked)",
+        ))
+        .stdout(predicate::str::contains("dry run: 4 link(s) validated"));
+    let output = stack_cmd(repo.path())
+        .arg("--porcelain")
+        .output()
+        .expect("run stack porcelain");
+    let branches: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert!(
+        branches
+            .as_array()
+            .expect("branches")
+            .iter()
+            .all(|b| b["name"] != "feat/b" || b["parent"].is_null())
+    );
+
+    stack_cmd(repo.path())
+        .args(["import", export_path.to_str().expect("path")])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("imported 4 link(s)"));
+    let output = stack_cmd(repo.path())
+        .arg("--porcelain")
+        .output()
+        .expect("run stack porcelain");
+    let branches: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    let parent = |name: &str| {
+        branches
+            .as_array()
+            .expect("branches")
+            .iter()
+            .find(|b| b["name"] == name)
+            .map(|b| b["parent"].clone())
+    };
+    assert_eq!(parent("feat/b"), Some(Value::from("feat/a")));
+    assert_eq!(parent("feat/other"), Some(Value::from("main")));
+
+    stack_cmd(repo.path())
+        .args(["import", export_path.to_str().expect("path")])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("nothing to import"));
+}
+
+#[test]
+fn import_rejects_missing_branches_and_cycles() {
+    let repo = init_repo();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/a"])
+        .assert()
+        .success();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "feat/a", "--name", "feat/b"])
+        .assert()
+        .success();
+
+    let missing = repo.path().join("missing.json");
+    fs::write(
+        &missing,
+        r#"{"version": 1, "base_branch": "main", "branches": {"feat/ghost": {"parent": "feat/a"}}}"#,
+    )
+    .expect("write export");
+    stack_cmd(repo.path())
+        .args(["import", missing.to_str().expect("path"), "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'feat/ghost' missing in this clone"));
+
+    let cycle = repo.path().join("cycle.json");
+    fs::write(
+        &cycle,
+        r#"{"version": 1, "base_branch": "main", "branches": {"feat/a": {"parent": "feat/b"}}}"#,
+    )
+    .expect("write export");
+    stack_cmd(repo.path())
+        .args(["import", cycle.to_str().expect("path")])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("link would create a cycle"));
+}
@@ -0,0 +1,181 @@
+#[test]
+fn sync_fast_forwards_frozen_branch_and_restacks_its_children_only() {
+    let repo = init_repo_without_origin();
+    let origin_bare = repo.path().join("origin.git");
+    run_git(
+        repo.path(),
+        &["init", "--bare", origin_bare.to_str().expect("origin bare")],
+    );
+    run_git(
+        repo.path(),
+        &[
+            "remote",
+            "add",
+            "origin",
+            origin_bare.to_str().expect("origin bare"),
+        ],
+    );
+    run_git(repo.path(), &["push", "--set-upstream", "origin", "main"]);
+
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/teammate"])
+        .assert()
+        .success();
+    fs::write(repo.path().join("teammate.txt"), "one\n").expect("write teammate file");
+    run_git(repo.path(), &["add", "teammate.txt"]);
+    run_git(repo.path(), &["commit", "-m", "teammate work"]);
+    run_git(
+        repo.path(),
+        &["push", "--set-upstream", "origin", "feat/teammate"],
+    );
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "feat/teammate", "--name", "feat/mine"])
+        .assert()
+        .success();
+    fs::write(repo.path().join("mine.txt"), "mine\n").expect("write mine file");
+    run_git(repo.path(), &["add", "mine.txt"]);
+    run_git(repo.path(), &["commit", "-m", "my work"]);
+
+    stack_cmd(repo.path())
+        .args(["freeze", "feat/teammate"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("froze 'feat/teammate'"));
+
+    // main moves on, and the teammate pushes a follow-up we have not fetched.
+    run_git(repo.path(), &["checkout", "main"]);
+    fs::write(repo.path().join("base.txt"), "base\n").expect("write base file");
+    run_git(repo.path(), &["add", "base.txt"]);
+    run_git(repo.path(), &["commit", "-m", "base update"]);
+    run_git(repo.path(), &["push", "origin", "main"]);
+    let teammate_work = repo.path().join("teammate-work");
+    run_git(
+        repo.path(),
+        &[
+            "clone",
+            "--branch",
+            "feat/teammate",
+            origin_bare.to_str().expect("origin bare"),
+            teammate_work.to_str().expect("teammate work"),
+        ],
+    );
+    run_git(&teammate_work, &["config", "user.email", "mate@example.com"]);
+    run_git(&teammate_work, &["config", "user.name", "Teammate"]);
+    run_git(&teammate_work, &["config", "commit.gpgsign", "false"]);
+    fs::write(teammate_work.join("teammate.txt"), "two\n").expect("update teammate file");
+    run_git(&teammate_work, &["commit", "-am", "teammate follow-up"]);
+    run_git(&teammate_work, &["push", "origin", "feat/teammate"]);
+
+    let output = stack_cmd(repo.path())
+        .args(["sync", "--dry-run", "--porcelain"])
+        .output()
+        .expect("run stack sync");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    let ops = json["operations"].as_array().expect("operations array");
+    assert!(ops.iter().any(|op| op["kind"] == "fast_forward"
+        && op["branch"] == "feat/teammate"
+        && op["onto"] == "origin/feat/teammate"));
+    assert!(ops.iter().any(|op| op["kind"] == "skip"
+        && op["branch"] == "feat/teammate"
+        && op["details"]
+            .as_str()
+            .is_some_and(|d| d.starts_with("restack skipped: branch is frozen"))));
+    assert!(!ops
+        .iter()
+        .any(|op| op["kind"] == "restack" && op["branch"] == "feat/teammate"));
+    assert!(ops.iter().any(|op| op["kind"] == "restack"
+        && op["branch"] == "feat/mine"
+        && op["onto"] == "feat/teammate"));
+
+    // One sync fetches the teammate's push and fast-forwards onto it.
+    stack_cmd(repo.path())
+        .args(["sync", "--yes"])
+        .assert()
+        .success();
+
+    let rev_parse = |dir: &Path, rev: &str| {
+        let output = Command::new("git")
+            .current_dir(dir)
+            .args(["rev-parse", rev])
+            .output()
+            .expect("run rev-parse");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    let is_ancestor = |ancestor: &str, branch: &str| {
+        Command::new("git")
+            .current_dir(repo.path())
+            .args(["merge-base", "--is-ancestor", ancestor, branch])
+            .status()
+            .expect("run merge-base")
+            .success()
+    };
+    assert_eq!(
+        rev_parse(repo.path(), "feat/teammate"),
+        rev_parse(&teammate_work, "HEAD")
+    );
+    assert!(!is_ancestor("main", "feat/teammate"));
+    assert!(is_ancestor("feat/teammate", "feat/mine"));
+
+    // Nothing new upstream: the next sync leaves both branches alone.
+    let mine_before = rev_parse(repo.path(), "feat/mine");
+    stack_cmd(repo.path())
+        .args(["sync", "--yes"])
+        .assert()
+        .success();
+    assert_eq!(rev_parse(repo.path(), "feat/mine"), mine_before);
+}
+#[test]
+fn frozen_branch_is_not_pushed_or_given_a_pr() {
+    let repo = init_repo();
+    configure_local_push_url(repo.path());
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/teammate"])
+        .assert()
+        .success();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "feat/teammate", "--name", "feat/mine"])
+        .assert()
+        .success();
+
+    stack_cmd(repo.path())
+        .args(["freeze", "main"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'main' is a base branch"));
+    stack_cmd(repo.path())
+        .args(["freeze", "feat/teammate", "--porcelain"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"changed\": true"));
+    stack_cmd(repo.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("feat/teammate [SYNC:tracked] [FROZEN]"));
+
+    let output = stack_cmd(repo.path())
+        .args(["push", "--porcelain"])
+        .output()
+        .expect("run stack push");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(json["skipped_frozen"], serde_json::json!(["feat/teammate"]));
+    assert_eq!(json["pushed"][0]["branch"], "feat/mine");
+
+    run_git(repo.path(), &["checkout", "feat/teammate"]);
+    stack_cmd(repo.path())
+        .arg("pr")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'feat/teammate' is frozen"));
+
+    stack_cmd(repo.path())
+        .arg("unfreeze")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("unfroze 'feat/teammate'"));
+    stack_cmd(repo.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[FROZEN]").not());
+}
@@ -0,0 +1,71 @@
+#[test]
+fn history_lists_sync_runs_with_per_op_shas() {
+    let repo = init_repo_without_origin();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/a"])
+        .assert()
+        .success();
+    fs::write(repo.path().join("a.txt"), "a\n").expect("write a file");
+    run_git(repo.path(), &["add", "a.txt"]);
+    run_git(repo.path(), &["commit", "-m", "feature a"]);
+    run_git(repo.path(), &["checkout", "main"]);
+    fs::write(repo.path().join("base.txt"), "base\n").expect("write base file");
+    run_git(repo.path(), &["add", "base.txt"]);
+    run_git(repo.path(), &["commit", "-m", "base update"]);
+
+    stack_cmd(repo.path())
+        .arg("history")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("no sync runs recorded"));
+
+    stack_cmd(repo.path())
+        .args(["sync", "--yes"])
+        .assert()
+        .success();
+
+    let output = stack_cmd(repo.path())
+        .args(["history", "--porcelain"])
+        .output()
+        .expect("run stack history");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    let runs = json["runs"].as_array().expect("runs array");
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0]["status"], "success");
+    let restack = runs[0]["ops"]
+        .as_array()
+        .expect("ops array")
+        .iter()
+        .find(|op| op["kind"] == "restack" && op["branch"] == "feat/a")
+        .expect("restack op recorded");
+    assert_eq!(restack["status"], "done");
+    assert!(restack["duration_ms"].as_i64().is_some());
+    let after = restack["after_sha"].as_str().expect("after sha");
+    assert_ne!(restack["before_sha"].as_str().expect("before sha"), after);
+    let head = Command::new("git")
+        .current_dir(repo.path())
+        .args(["rev-parse", "feat/a"])
+        .output()
+        .expect("run rev-parse");
+    assert_eq!(after, String::from_utf8_lossy(&head.stdout).trim());
+
+    let id = runs[0]["id"].as_i64().expect("run id").to_string();
+    stack_cmd(repo.path())
+        .arg("history")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("#{id}")))
+        .stdout(predicate::str::contains("success"));
+    stack_cmd(repo.path())
+        .args(["history", "--run", &id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("sync run #{id}: success")))
+        .stdout(predicate::str::contains("done     restack feat/a onto main"));
+    stack_cmd(repo.path())
+        .args(["history", "--run", "999"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no sync run #999"));
+}
@@ -0,0 +1,83 @@
+fn hold_stack_lock(repo: &Path, command: &str) -> fs::File {
+    let mut file = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(repo.join(".git").join("stack.db.lock"))
+        .expect("open lock file");
+    file.try_lock().expect("take stack lock");
+    std::io::Write::write_all(&mut file, format!("4242\n{command}\n").as_bytes())
+        .expect("write lock holder");
+    file
+}
+
+#[test]
+fn mutating_command_fails_fast_naming_the_lock_holder() {
+    let repo = init_repo();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/a"])
+        .assert()
+        .success();
+    let lock = hold_stack_lock(repo.path(), "stack sync --yes");
+
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "feat/a", "--name", "feat/b"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "another stack command is running (pid 4242: stack sync --yes); rerun with --wait",
+        ));
+
+    // Read-only views and previews do not need the lock.
+    stack_cmd(repo.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("feat/a"));
+    stack_cmd(repo.path())
+        .args(["sync", "--dry-run", "--porcelain"])
+        .assert()
+        .success();
+
+    drop(lock);
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "feat/a", "--name", "feat/b"])
+        .assert()
+        .success();
+
+    let journal_mode: String = Connection::open(repo.path().join(".git").join("stack.db"))
+        .expect("open db")
+        .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+        .expect("read journal mode");
+    assert_eq!(journal_mode, "wal");
+}
+
+#[test]
+fn wait_flag_blocks_until_the_lock_is_released() {
+    let repo = init_repo();
+    stack_cmd(repo.path()).assert().success();
+    let lock = hold_stack_lock(repo.path(), "stack merge");
+
+    let mut child = stack_cmd(repo.path())
+        .args(["--wait", "create", "--parent", "main", "--name", "feat/a"])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .expect("spawn stack create --wait");
+    let mut stderr = std::io::BufReader::new(child.stderr.take().expect("child stderr"));
+    let mut waiting = String::new();
+    std::io::BufRead::read_line(&mut stderr, &mut waiting).expect("read waiting message");
+    assert!(
+        waiting.contains("waiting for another stack command to finish (pid 4242: stack merge)"),
+        "unexpected stderr: {waiting}"
+    );
+    drop(lock);
+
+    let status = child.wait().expect("wait for stack create");
+    assert!(status.success());
+    let output = stack_cmd(repo.path())
+        .arg("--porcelain")
+        .output()
+        .expect("run stack --porcelain");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\"feat/a\""));
+}
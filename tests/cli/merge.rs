@@ -0,0 +1,462 @@
+#[cfg(unix)]
+fn install_fake_merge_gh(repo: &Path, checks_conclusion: &str) -> (String, PathBuf) {
+    let fake_bin = repo.join("fake-bin");
+    let state_dir = repo.join("fake-gh-state");
+    let gh_log = repo.join("gh.log");
+    fs::create_dir_all(&fake_bin).expect("create fake bin dir");
+    fs::create_dir_all(&state_dir).expect("create fake gh state dir");
+    let script = format!(
+        r#"#!/usr/bin/env bash
+echo "$@" >> '{log}'
+state_dir='{state}'
+pr_state() {{ if [[ -f "$state_dir/merged-$1" ]]; then echo MERGED; else echo OPEN; fi; }}
+pr_oid() {{ if [[ -f "$state_dir/merged-$1" ]]; then echo "{{\"oid\":\"$(cat "$state_dir/merged-$1")\"}}"; else echo null; fi; }}
+pr_json() {{
+  echo "{{\"number\":$1,\"state\":\"$(pr_state $1)\",\"baseRefName\":\"$3\",\"headRefName\":\"$2\",\"mergeCommit\":$(pr_oid $1),\"body\":\"\"}}"
+}}
+args="$*"
+if [[ "$1 $2" == "pr merge" ]]; then
+  case "$3" in 11) head=feat/a;; 12) head=feat/b;; esac
+  git push -q origin "$head:main" && git rev-parse "$head" > "$state_dir/merged-$3"
+  exit $?
+fi
+if [[ "$1 $2" == "pr edit" ]]; then
+  exit 0
+fi
+if [[ "$1 $2" == "pr view" ]]; then
+  if [[ "$args" == *reviewDecision* ]]; then
+    echo '{{"reviewDecision":"APPROVED","statusCheckRollup":[{{"status":"COMPLETED","conclusion":"{conclusion}"}}]}}'
+    exit 0
+  fi
+  case "$3" in 11) pr_json 11 feat/a main;; 12) pr_json 12 feat/b feat/a;; esac
+  exit 0
+fi
+if [[ "$1 $2" == "pr list" ]]; then
+  if [[ "$args" == *"--head feat/a"* ]]; then
+    echo "[$(pr_json 11 feat/a main)]"
+  elif [[ "$args" == *"--head feat/b"* ]]; then
+    echo "[$(pr_json 12 feat/b feat/a)]"
+  elif [[ "$args" == *"--head"* ]]; then
+    echo '[]'
+  else
+    echo "[$(pr_json 11 feat/a main),$(pr_json 12 feat/b feat/a)]"
+  fi
+  exit 0
+fi
+echo '[]'
+"#,
+        log = gh_log.display(),
+        state = state_dir.display(),
+        conclusion = checks_conclusion,
+    );
+    let fake_gh = fake_bin.join("gh");
+    fs::write(&fake_gh, script).expect("write fake gh");
+    fs::set_permissions(&fake_gh, fs::Permissions::from_mode(0o755)).expect("chmod fake gh");
+
+    let current_path = env::var("PATH").unwrap_or_default();
+    (format!("{}:{}", fake_bin.display(), current_path), gh_log)
+}
+
+#[cfg(unix)]
+fn init_merge_stack_repo() -> TempDir {
+    let repo = init_repo_without_origin();
+    let bare = repo.path().join("origin.git");
+    run_git(
+        repo.path(),
+        &["init", "--bare", bare.to_str().expect("bare path")],
+    );
+    run_git(
+        repo.path(),
+        &["remote", "add", "origin", bare.to_str().expect("bare path")],
+    );
+    run_git(repo.path(), &["push", "--set-upstream", "origin", "main"]);
+
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/a"])
+        .assert()
+        .success();
+    fs::write(repo.path().join("a.txt"), "a\n").expect("write a");
+    run_git(repo.path(), &["add", "a.txt"]);
+    run_git(repo.path(), &["commit", "-m", "feat/a"]);
+
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "feat/a", "--name", "feat/b"])
+        .assert()
+        .success();
+    fs::write(repo.path().join("b.txt"), "b\n").expect("write b");
+    run_git(repo.path(), &["add", "b.txt"]);
+    run_git(repo.path(), &["commit", "-m", "feat/b"]);
+    repo
+}
+
+#[cfg(unix)]
+#[test]
+fn merge_lands_stack_bottom_up_and_retargets_next_pr() {
+    let repo = init_merge_stack_repo();
+    let (test_path, gh_log) = install_fake_merge_gh(repo.path(), "SUCCESS");
+
+    stack_cmd(repo.path())
+        .env("PATH", &test_path)
+        .args(["--yes", "merge", "--method", "squash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("merged PR #11 ('feat/a') via squash"))
+        .stdout(predicate::str::contains("merged PR #12 ('feat/b') via squash"))
+        .stdout(predicate::str::contains("merged 2 PR(s) up to 'feat/b'"));
+
+    let gh_calls = fs::read_to_string(&gh_log).expect("read gh log");
+    let first_merge = gh_calls.find("pr merge 11 --squash").expect("merge #11");
+    let retarget = gh_calls.find("pr edit 12 --base main").expect("retarget #12");
+    let second_merge = gh_calls.find("pr merge 12 --squash").expect("merge #12");
+    assert!(first_merge < retarget && retarget < second_merge);
+
+    let main_sha = Command::new("git")
+        .current_dir(repo.path())
+        .args(["rev-parse", "main"])
+        .output()
+        .expect("rev-parse main");
+    let feat_b_sha = Command::new("git")
+        .current_dir(repo.path())
+        .args(["rev-parse", "feat/b"])
+        .output()
+        .expect("rev-parse feat/b");
+    assert_eq!(main_sha.stdout, feat_b_sha.stdout);
+
+    let db_path = repo.path().join(".git").join("stack.db");
+    let conn = Connection::open(&db_path).expect("open db");
+    let parent_name: String = conn
+        .query_row(
+            "SELECT p.name FROM branches c JOIN branches p ON p.id = c.parent_branch_id
+             WHERE c.name = 'feat/b'",
+            [],
+            |row| row.get(0),
+        )
+        .expect("query feat/b parent");
+    assert_eq!(parent_name, "main");
+}
+
+#[cfg(unix)]
+#[test]
+fn merge_stops_at_first_pr_with_failing_checks() {
+    let repo = init_merge_stack_repo();
+    let (test_path, gh_log) = install_fake_merge_gh(repo.path(), "FAILURE");
+
+    stack_cmd(repo.path())
+        .env("PATH", &test_path)
+        .args(["--yes", "merge"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "merge stopped at 'feat/a' (PR #11): checks failing",
+        ));
+
+    let gh_calls = fs::read_to_string(&gh_log).expect("read gh log");
+    assert!(
+        !gh_calls.contains("pr merge"),
+        "expected no merge calls, got: {gh_calls}"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn merge_dry_run_porcelain_lists_merge_order_without_merging() {
+    let repo = init_merge_stack_repo();
+    let (test_path, gh_log) = install_fake_merge_gh(repo.path(), "SUCCESS");
+
+    let output = stack_cmd(repo.path())
+        .env("PATH", &test_path)
+        .args(["merge", "--dry-run", "--porcelain", "--until", "feat/b"])
+        .output()
+        .expect("run stack merge");
+    assert!(
+        output.status.success(),
+        "merge dry-run failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    let order: Vec<&str> = json["would_merge"]
+        .as_array()
+        .expect("would_merge array")
+        .iter()
+        .map(|step| step["branch"].as_str().expect("branch"))
+        .collect();
+    assert_eq!(order, vec!["feat/a", "feat/b"]);
+    assert_eq!(json["method"], "merge");
+    assert!(json["stopped"].is_null());
+
+    let gh_calls = fs::read_to_string(&gh_log).expect("read gh log");
+    assert!(!gh_calls.contains("pr merge"));
+}
+
+#[test]
+fn merge_refreshes_surviving_pr_bodies_against_new_parents() {
+    let repo = init_repo_without_origin();
+    run_git(
+        repo.path(),
+        &[
+            "remote",
+            "add",
+            "origin",
+            "git@github.com:acme/stack-test.git",
+        ],
+    );
+    run_git(repo.path(), &["config", "branch.main.remote", "no-fetch"]);
+    let provider_file = repo.path().join(".git").join("stack-provider.json");
+    fs::write(
+        &provider_file,
+        r#"{"prs":[
+            {"number":41,"head":"feat/a","base":"main","url":"https://github.com/acme/stack-test/pull/41"},
+            {"number":42,"head":"feat/b","base":"feat/a","url":"https://github.com/acme/stack-test/pull/42"},
+            {"number":43,"head":"feat/c","base":"feat/b","url":"https://github.com/acme/stack-test/pull/43"}
+        ]}"#,
+    )
+    .expect("write provider file");
+    let stack = || {
+        let mut cmd = stack_cmd(repo.path());
+        cmd.env("STACK_PROVIDER", "file:.git/stack-provider.json");
+        cmd
+    };
+    for (parent, name) in [("main", "feat/a"), ("feat/a", "feat/b"), ("feat/b", "feat/c")] {
+        stack()
+            .args(["create", "--parent", parent, "--name", name])
+            .assert()
+            .success();
+        let file = format!("{}.txt", name.replace('/', "-"));
+        fs::write(repo.path().join(&file), format!("{name}\n")).expect("write branch file");
+        run_git(repo.path(), &["add", &file]);
+        run_git(repo.path(), &["commit", "-m", name]);
+    }
+    stack().args(["sync", "--yes"]).assert().success();
+
+    stack()
+        .args(["--yes", "merge", "--until", "feat/a"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("merged PR #41 ('feat/a')"));
+
+    let state: Value =
+        serde_json::from_str(&fs::read_to_string(&provider_file).expect("read provider file"))
+            .expect("provider json");
+    let pr = |number: i64| {
+        state["prs"]
+            .as_array()
+            .expect("prs array")
+            .iter()
+            .find(|pr| pr["number"] == number)
+            .expect("pr")
+            .clone()
+    };
+    assert_eq!(pr(42)["base"], "main");
+    for number in [42, 43] {
+        let body = pr(number)["body"].as_str().expect("body").to_string();
+        assert!(body.contains("stack:managed:start"), "got: {body}");
+        assert!(!body.contains("/pull/41"), "got: {body}");
+    }
+    let body = pr(43)["body"].as_str().expect("body").to_string();
+    assert!(body.contains("/pull/42"), "got: {body}");
+}
+
+#[cfg(unix)]
+#[test]
+fn merge_readies_the_next_draft_under_the_draft_policy() {
+    let repo = init_repo_without_origin();
+    run_git(
+        repo.path(),
+        &[
+            "remote",
+            "add",
+            "origin",
+            "git@github.com:acme/stack-test.git",
+        ],
+    );
+    run_git(repo.path(), &["config", "branch.main.remote", "no-fetch"]);
+    run_git(repo.path(), &["config", "stack.prDraftPolicy", "true"]);
+    let provider_file = repo.path().join(".git").join("stack-provider.json");
+    fs::write(
+        &provider_file,
+        r#"{"prs":[
+            {"number":51,"head":"feat/a","base":"main","draft":true},
+            {"number":52,"head":"feat/b","base":"feat/a","draft":true}
+        ]}"#,
+    )
+    .expect("write provider file");
+    let stack = || {
+        let mut cmd = stack_cmd(repo.path());
+        cmd.env("STACK_PROVIDER", "file:.git/stack-provider.json");
+        cmd
+    };
+    for (parent, name) in [("main", "feat/a"), ("feat/a", "feat/b")] {
+        stack()
+            .args(["create", "--parent", parent, "--name", name])
+            .assert()
+            .success();
+        let file = format!("{}.txt", name.replace('/', "-"));
+        fs::write(repo.path().join(&file), format!("{name}\n")).expect("write branch file");
+        run_git(repo.path(), &["add", &file]);
+        run_git(repo.path(), &["commit", "-m", name]);
+    }
+
+    let output = stack()
+        .args(["--yes", "--porcelain", "merge", "--until", "feat/b"])
+        .output()
+        .expect("run merge");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(json["stopped"]["pr_number"], 51);
+    assert_eq!(json["stopped"]["reason"], "PR is a draft");
+
+    // The policy readies the bottom PR; merging it must ready the next one.
+    stack().args(["sync", "--yes"]).assert().success();
+    let output = stack()
+        .args(["--yes", "--porcelain", "merge", "--until", "feat/b"])
+        .output()
+        .expect("run merge");
+    assert!(
+        output.status.success(),
+        "merge failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(json["stopped"], Value::Null);
+    assert_eq!(
+        json["merged"],
+        serde_json::json!([
+            { "branch": "feat/a", "pr_number": 51 },
+            { "branch": "feat/b", "pr_number": 52 },
+        ])
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn merge_leaves_a_blocked_pr_on_its_original_base() {
+    let repo = init_repo_without_origin();
+    run_git(
+        repo.path(),
+        &[
+            "remote",
+            "add",
+            "origin",
+            "git@github.com:acme/stack-test.git",
+        ],
+    );
+    run_git(repo.path(), &["config", "branch.main.remote", "no-fetch"]);
+    let provider_file = repo.path().join(".git").join("stack-provider.json");
+    fs::write(
+        &provider_file,
+        r#"{"prs":[{"number":61,"head":"feat/a","base":"develop","checks":"failing"}]}"#,
+    )
+    .expect("write provider file");
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/a"])
+        .assert()
+        .success();
+    fs::write(repo.path().join("a.txt"), "a\n").expect("write a");
+    run_git(repo.path(), &["add", "a.txt"]);
+    run_git(repo.path(), &["commit", "-m", "feat/a"]);
+
+    let output = stack_cmd(repo.path())
+        .env("STACK_PROVIDER", "file:.git/stack-provider.json")
+        .args(["--yes", "--porcelain", "merge"])
+        .output()
+        .expect("run merge");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(json["stopped"]["reason"], "checks failing");
+
+    let state: Value =
+        serde_json::from_str(&fs::read_to_string(&provider_file).expect("read provider file"))
+            .expect("valid provider json");
+    assert_eq!(state["prs"][0]["base"], "develop");
+}
+
+#[cfg(unix)]
+#[test]
+fn merge_leaves_unrelated_stacks_alone() {
+    let repo = init_merge_stack_repo();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/c"])
+        .assert()
+        .success();
+    fs::write(repo.path().join("c.txt"), "c\n").expect("write c");
+    run_git(repo.path(), &["add", "c.txt"]);
+    run_git(repo.path(), &["commit", "-m", "feat/c"]);
+    let feat_c_sha = || {
+        Command::new("git")
+            .current_dir(repo.path())
+            .args(["rev-parse", "feat/c"])
+            .output()
+            .expect("rev-parse feat/c")
+            .stdout
+    };
+    let feat_c_before = feat_c_sha();
+    run_git(repo.path(), &["checkout", "feat/b"]);
+    let (test_path, _gh_log) = install_fake_merge_gh(repo.path(), "SUCCESS");
+
+    stack_cmd(repo.path())
+        .env("PATH", &test_path)
+        .args(["--yes", "merge", "--method", "squash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("merged 2 PR(s) up to 'feat/b'"));
+
+    assert_eq!(feat_c_sha(), feat_c_before);
+    let remote_c = Command::new("git")
+        .current_dir(repo.path())
+        .args(["ls-remote", "--heads", "origin", "feat/c"])
+        .output()
+        .expect("ls-remote");
+    assert!(remote_c.stdout.is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn merge_stops_at_a_frozen_branch() {
+    let repo = init_repo_without_origin();
+    run_git(
+        repo.path(),
+        &[
+            "remote",
+            "add",
+            "origin",
+            "git@github.com:acme/stack-test.git",
+        ],
+    );
+    run_git(repo.path(), &["config", "branch.main.remote", "no-fetch"]);
+    let provider_file = repo.path().join(".git").join("stack-provider.json");
+    fs::write(
+        &provider_file,
+        r#"{"prs":[
+            {"number":81,"head":"feat/a","base":"main"},
+            {"number":82,"head":"feat/b","base":"feat/a"}
+        ]}"#,
+    )
+    .expect("write provider file");
+    let stack = || {
+        let mut cmd = stack_cmd(repo.path());
+        cmd.env("STACK_PROVIDER", "file:.git/stack-provider.json");
+        cmd
+    };
+    for (parent, name) in [("main", "feat/a"), ("feat/a", "feat/b")] {
+        stack()
+            .args(["create", "--parent", parent, "--name", name])
+            .assert()
+            .success();
+    }
+    stack().args(["freeze", "feat/a"]).assert().success();
+
+    let output = stack()
+        .args(["--yes", "--porcelain", "merge", "--until", "feat/b"])
+        .output()
+        .expect("run merge");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(json["merged"], serde_json::json!([]));
+    assert_eq!(json["stopped"]["branch"], "feat/a");
+    assert_eq!(json["stopped"]["reason"], "branch is frozen");
+
+    let state: Value =
+        serde_json::from_str(&fs::read_to_string(&provider_file).expect("read provider file"))
+            .expect("valid provider json");
+    assert!(state["prs"][0]["state"].is_null() || state["prs"][0]["state"] == "open");
+}
@@ -0,0 +1,188 @@
+fn set_parent_in_db(repo: &Path, child: &str, parent: &str) {
+    let conn = Connection::open(repo.join(".git").join("stack.db")).expect("open db");
+    conn.execute(
+        "INSERT INTO branches(name) VALUES (?1) ON CONFLICT(name) DO NOTHING",
+        [child],
+    )
+    .expect("insert child");
+    conn.execute(
+        "UPDATE branches SET parent_branch_id = (SELECT id FROM branches WHERE name = ?1) WHERE name = ?2",
+        [parent, child],
+    )
+    .expect("set parent");
+}
+
+fn parent_of(repo: &Path, branch: &str) -> Option<String> {
+    let output = stack_cmd(repo)
+        .arg("--porcelain")
+        .output()
+        .expect("run stack porcelain");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    json.as_array()
+        .expect("branches")
+        .iter()
+        .find(|b| b["name"] == branch)
+        .and_then(|b| b["parent"].as_str().map(str::to_string))
+}
+
+#[test]
+fn metadata_push_and_pull_share_parent_links_and_merge_concurrent_edits() {
+    let root = tempfile::tempdir().expect("tempdir");
+    let bare = root.path().join("remote.git");
+    run_git(
+        root.path(),
+        &["init", "--bare", bare.to_str().expect("bare path")],
+    );
+
+    let alice = init_repo_without_origin();
+    run_git(
+        alice.path(),
+        &["remote", "add", "origin", bare.to_str().expect("bare path")],
+    );
+    run_git(alice.path(), &["push", "--set-upstream", "origin", "main"]);
+    for (name, parent) in [
+        ("feat/a", "main"),
+        ("feat/b", "feat/a"),
+        ("feat/c", "main"),
+    ] {
+        stack_cmd(alice.path())
+            .args(["create", "--parent", parent, "--name", name])
+            .assert()
+            .success();
+    }
+
+    let bob = root.path().join("bob");
+    run_git(
+        root.path(),
+        &[
+            "clone",
+            bare.to_str().expect("bare path"),
+            bob.to_str().expect("bob path"),
+        ],
+    );
+    run_git(&bob, &["config", "user.email", "bob@example.com"]);
+    run_git(&bob, &["config", "user.name", "Bob"]);
+    stack_cmd(&bob)
+        .args(["metadata", "pull"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("no stack metadata on 'origin' yet"));
+
+    stack_cmd(alice.path())
+        .args(["metadata", "push"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "pushed stack metadata for 4 branch(es) to 'origin'",
+        ));
+    stack_cmd(&bob)
+        .args(["metadata", "pull"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("4 branch(es) updated"));
+    assert_eq!(parent_of(&bob, "feat/b").as_deref(), Some("feat/a"));
+
+    set_parent_in_db(alice.path(), "feat/c", "feat/b");
+    stack_cmd(alice.path())
+        .args(["metadata", "push"])
+        .assert()
+        .success();
+    set_parent_in_db(&bob, "feat/c", "feat/a");
+    set_parent_in_db(&bob, "feat/b", "main");
+    stack_cmd(&bob)
+        .args(["metadata", "push"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("run `stack metadata pull` first"));
+
+    let output = stack_cmd(&bob)
+        .args(["metadata", "pull", "--porcelain"])
+        .output()
+        .expect("run metadata pull");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(json["conflicts"][0]["branch"], "feat/c");
+    assert_eq!(json["conflicts"][0]["local_parent"], "feat/a");
+    assert_eq!(json["conflicts"][0]["remote_parent"], "feat/b");
+    assert_eq!(parent_of(&bob, "feat/c").as_deref(), Some("feat/a"));
+    assert_eq!(parent_of(&bob, "feat/b").as_deref(), Some("main"));
+
+    stack_cmd(&bob)
+        .args(["metadata", "push"])
+        .assert()
+        .success();
+    stack_cmd(alice.path())
+        .args(["metadata", "pull"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2 branch(es) updated"));
+    assert_eq!(parent_of(alice.path(), "feat/c").as_deref(), Some("feat/a"));
+    assert_eq!(parent_of(alice.path(), "feat/b").as_deref(), Some("main"));
+}
+
+#[test]
+fn metadata_pull_reports_opposite_reparents_as_a_conflict() {
+    let root = tempfile::tempdir().expect("tempdir");
+    let bare = root.path().join("remote.git");
+    run_git(
+        root.path(),
+        &["init", "--bare", bare.to_str().expect("bare path")],
+    );
+
+    let alice = init_repo_without_origin();
+    run_git(
+        alice.path(),
+        &["remote", "add", "origin", bare.to_str().expect("bare path")],
+    );
+    run_git(alice.path(), &["push", "--set-upstream", "origin", "main"]);
+    for name in ["feat/a", "feat/b"] {
+        stack_cmd(alice.path())
+            .args(["create", "--parent", "main", "--name", name])
+            .assert()
+            .success();
+    }
+    stack_cmd(alice.path())
+        .args(["metadata", "push"])
+        .assert()
+        .success();
+
+    let bob = root.path().join("bob");
+    run_git(
+        root.path(),
+        &[
+            "clone",
+            bare.to_str().expect("bare path"),
+            bob.to_str().expect("bob path"),
+        ],
+    );
+    run_git(&bob, &["config", "user.email", "bob@example.com"]);
+    run_git(&bob, &["config", "user.name", "Bob"]);
+    stack_cmd(&bob)
+        .args(["metadata", "pull"])
+        .assert()
+        .success();
+
+    set_parent_in_db(alice.path(), "feat/a", "feat/b");
+    stack_cmd(alice.path())
+        .args(["metadata", "push"])
+        .assert()
+        .success();
+    set_parent_in_db(&bob, "feat/b", "feat/a");
+
+    let output = stack_cmd(&bob)
+        .args(["metadata", "pull", "--porcelain"])
+        .output()
+        .expect("run metadata pull");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(json["conflicts"][0]["branch"], "feat/a");
+    assert_eq!(json["conflicts"][0]["local_parent"], "main");
+    assert_eq!(json["conflicts"][0]["remote_parent"], "feat/b");
+    assert_eq!(parent_of(&bob, "feat/a").as_deref(), Some("main"));
+    assert_eq!(parent_of(&bob, "feat/b").as_deref(), Some("feat/a"));
+}
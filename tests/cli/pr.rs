@@ -654,3 +654,526 @@ fn pr_on_base_branch_porcelain_reports_blocked_state() {
             .contains("cannot open PR from 'main' into itself")
     );
 }
+
+#[test]
+fn pr_preview_body_renders_configured_template() {
+    let repo = init_repo();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/parent"])
+        .assert()
+        .success();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "feat/parent", "--name", "feat/child"])
+        .assert()
+        .success();
+    run_git(repo.path(), &["checkout", "feat/child"]);
+    // Only branches with open PRs count towards the position.
+    Connection::open(repo.path().join(".git").join("stack.db"))
+        .expect("open db")
+        .execute(
+            "UPDATE branches SET cached_pr_number = 7, cached_pr_state = 'OPEN'
+             WHERE name = 'feat/parent'",
+            [],
+        )
+        .expect("cache parent PR");
+    run_git(
+        repo.path(),
+        &[
+            "config",
+            "stack.prBodyTemplate",
+            "Part {{stack_index}} of {{stack_size}}{{#parent_branch}}, after `{{parent_branch}}`{{/parent_branch}}",
+        ],
+    );
+
+    let output = stack_cmd(repo.path())
+        .args(["pr", "--preview-body", "--porcelain", "--body", "Details"])
+        .output()
+        .expect("run stack pr --preview-body");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    let body = json["body"].as_str().expect("body string");
+    assert!(
+        body.contains("<!-- stack:managed:start -->\nPart 2 of 2, after `feat/parent`\n<!-- stack:managed:end -->"),
+        "unexpected body: {body}"
+    );
+    assert!(body.ends_with("Details"));
+}
+
+#[test]
+fn pr_rejects_template_with_unknown_variable() {
+    let repo = init_repo();
+    run_git(
+        repo.path(),
+        &["config", "stack.prBodyTemplate", "{{parent_title}}"],
+    );
+
+    stack_cmd(repo.path())
+        .args(["pr", "--preview-body"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown template variable 'parent_title'"));
+}
+
+#[test]
+fn pr_dry_run_prefixes_title_with_stack_position() {
+    let repo = init_repo();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/parent"])
+        .assert()
+        .success();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "feat/parent", "--name", "feat/child"])
+        .assert()
+        .success();
+    run_git(repo.path(), &["checkout", "feat/child"]);
+    // Only branches with open PRs count towards the position.
+    Connection::open(repo.path().join(".git").join("stack.db"))
+        .expect("open db")
+        .execute(
+            "UPDATE branches SET cached_pr_number = 7, cached_pr_state = 'OPEN'
+             WHERE name = 'feat/parent'",
+            [],
+        )
+        .expect("cache parent PR");
+    run_git(repo.path(), &["config", "stack.prTitlePrefix", "({index} of {total})"]);
+
+    let output = stack_cmd(repo.path())
+        .args(["pr", "--dry-run", "--porcelain", "--title", "Add child"])
+        .output()
+        .expect("run stack pr --dry-run");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(json["title"], "(2 of 2) Add child");
+}
+
+#[test]
+fn pr_defaults_title_and_body_from_commits_and_repo_template() {
+    let repo = init_repo();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/add-parser"])
+        .assert()
+        .success();
+    run_git(
+        repo.path(),
+        &[
+            "commit",
+            "--allow-empty",
+            "-m",
+            "Parse stack files",
+            "-m",
+            "Handles nested stacks.",
+        ],
+    );
+    fs::create_dir_all(repo.path().join(".github")).expect("create .github");
+    fs::write(
+        repo.path().join(".github").join("pull_request_template.md"),
+        "## Checklist\n- [ ] tests\n",
+    )
+    .expect("write PR template");
+
+    let output = stack_cmd(repo.path())
+        .args(["pr", "--dry-run", "--porcelain"])
+        .output()
+        .expect("run stack pr --dry-run");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(json["title"], "Parse stack files");
+
+    let output = stack_cmd(repo.path())
+        .args(["pr", "--preview-body"])
+        .output()
+        .expect("run stack pr --preview-body");
+    assert!(output.status.success());
+    let body = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        body.contains("<!-- stack:managed:end -->\n\nHandles nested stacks.\n\n## Checklist\n- [ ] tests"),
+        "unexpected body: {body}"
+    );
+
+    run_git(
+        repo.path(),
+        &["commit", "--allow-empty", "-m", "Add parser tests"],
+    );
+    let output = stack_cmd(repo.path())
+        .args(["pr", "--dry-run", "--porcelain"])
+        .output()
+        .expect("run stack pr --dry-run");
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(json["title"], "Add parser");
+    let output = stack_cmd(repo.path())
+        .args(["pr", "--preview-body"])
+        .output()
+        .expect("run stack pr --preview-body");
+    let body = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        body.contains("- Parse stack files\n- Add parser tests\n\n## Checklist"),
+        "unexpected body: {body}"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn pr_edit_opens_editor_on_composed_body_before_pushing() {
+    let repo = init_repo();
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/pr-edit"])
+        .assert()
+        .success();
+    run_git(repo.path(), &["checkout", "feat/pr-edit"]);
+    configure_local_push_url(repo.path());
+
+    let fake_bin = repo.path().join("fake-bin");
+    let open_log = repo.path().join("open.log");
+    fs::create_dir_all(&fake_bin).expect("create fake bin dir");
+    install_fake_browser_openers(&fake_bin, &open_log);
+    fs::write(fake_bin.join("gh"), "#!/usr/bin/env bash\necho '[]'\n").expect("write fake gh");
+    fs::set_permissions(fake_bin.join("gh"), fs::Permissions::from_mode(0o755))
+        .expect("chmod fake gh");
+    let editor = fake_bin.join("fake-editor");
+    fs::write(
+        &editor,
+        "#!/usr/bin/env bash\ngrep -q 'stack:managed:start' \"$1\" || exit 1\necho 'EditedByReviewer' > \"$1\"\n",
+    )
+    .expect("write fake editor");
+    fs::set_permissions(&editor, fs::Permissions::from_mode(0o755)).expect("chmod fake editor");
+
+    let current_path = env::var("PATH").unwrap_or_default();
+    stack_cmd(repo.path())
+        .env("PATH", format!("{}:{}", fake_bin.display(), current_path))
+        .env_remove("STACK_MOCK_BROWSER_OPEN")
+        .env_remove("GIT_EDITOR")
+        .env_remove("VISUAL")
+        .env("EDITOR", &editor)
+        .args(["--yes", "pr", "--edit"])
+        .assert()
+        .success();
+
+    let open_calls = fs::read_to_string(&open_log).expect("read open log");
+    assert!(
+        open_calls.contains("body=EditedByReviewer"),
+        "expected edited body in PR link, got: {open_calls}"
+    );
+    assert!(!open_calls.contains("stack%3Amanaged"), "got: {open_calls}");
+}
+
+#[test]
+fn pr_create_inherits_reviewers_and_labels_from_parent_pr() {
+    let repo = init_repo();
+    configure_local_push_url(repo.path());
+    let provider_file = repo.path().join(".git").join("stack-provider.json");
+    fs::write(
+        &provider_file,
+        r#"{"prs":[{"number":41,"head":"feat/parent","base":"main","reviewers":["alice","acme/core"],"labels":["backend"],"milestone":"v1"}]}"#,
+    )
+    .expect("write provider file");
+    let stack = |args: &[&str]| {
+        stack_cmd(repo.path())
+            .env("STACK_PROVIDER", "file:.git/stack-provider.json")
+            .args(args)
+            .assert()
+            .success()
+    };
+    stack(&["create", "--parent", "main", "--name", "feat/parent"]);
+    stack(&["create", "--parent", "feat/parent", "--name", "feat/child"]);
+
+    stack(&[
+        "--yes",
+        "pr",
+        "--create",
+        "--inherit",
+        "--label",
+        "stacked,backend",
+        "--assignee",
+        "bob",
+    ])
+    .stdout(predicate::str::contains("created PR #42"));
+
+    let state: Value =
+        serde_json::from_str(&fs::read_to_string(&provider_file).expect("read provider file"))
+            .expect("provider json");
+    let child = &state["prs"][1];
+    assert_eq!(child["head"], "feat/child");
+    assert_eq!(child["base"], "feat/parent");
+    assert_eq!(child["reviewers"], serde_json::json!(["alice", "acme/core"]));
+    assert_eq!(child["labels"], serde_json::json!(["backend", "stacked"]));
+    assert_eq!(child["assignees"], serde_json::json!(["bob"]));
+    assert_eq!(child["milestone"], "v1");
+
+    stack(&["pr", "--dry-run", "--porcelain"])
+        .stdout(predicate::str::contains("\"existing_pr_number\": 42"));
+}
+
+#[test]
+fn pr_lifecycle_subcommands_change_state_for_branch_or_whole_stack() {
+    let repo = init_repo();
+    run_git(repo.path(), &["config", "branch.main.remote", "no-fetch"]);
+    let provider_file = repo.path().join(".git").join("stack-provider.json");
+    fs::write(
+        &provider_file,
+        r#"{"prs":[
+            {"number":61,"head":"feat/a","base":"main"},
+            {"number":62,"head":"feat/b","base":"feat/a"},
+            {"number":63,"head":"feat/c","base":"feat/b","state":"merged"}
+        ]}"#,
+    )
+    .expect("write provider file");
+    let pr_field = |field: &str| -> Vec<Value> {
+        let state: Value =
+            serde_json::from_str(&fs::read_to_string(&provider_file).expect("read provider file"))
+                .expect("provider json");
+        state["prs"]
+            .as_array()
+            .expect("prs array")
+            .iter()
+            .map(|pr| pr[field].clone())
+            .collect()
+    };
+    let stack = |args: &[&str]| {
+        let mut cmd = stack_cmd(repo.path());
+        cmd.env("STACK_PROVIDER", "file:.git/stack-provider.json")
+            .args(args);
+        cmd.assert()
+    };
+
+    stack(&["create", "--parent", "main", "--name", "feat/a"]).success();
+    stack(&["create", "--parent", "feat/a", "--name", "feat/b"]).success();
+    stack(&["create", "--parent", "feat/b", "--name", "feat/c"]).success();
+    stack(&["create", "--parent", "main", "--name", "feat/other"]).success();
+    run_git(repo.path(), &["checkout", "feat/b"]);
+
+    stack(&["pr", "draft", "--stack"])
+        .success()
+        .stdout(predicate::str::contains(
+            "converted PR #61 ('feat/a') to draft",
+        ))
+        .stdout(predicate::str::contains("skipped 'feat/c': PR #63 is merged"));
+    assert_eq!(
+        pr_field("draft"),
+        [Value::from(true), Value::from(true), Value::Null]
+    );
+
+    stack(&["pr", "ready", "--branch", "feat/a"])
+        .success()
+        .stdout("marked PR #61 ('feat/a') ready for review\n");
+    let output = stack_cmd(repo.path())
+        .env("STACK_PROVIDER", "file:.git/stack-provider.json")
+        .args(["--porcelain", "pr", "ready"])
+        .output()
+        .expect("run pr ready");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(json["action"], "ready");
+    assert_eq!(json["results"][0]["branch"], "feat/b");
+    assert_eq!(json["results"][0]["changed"], true);
+    assert_eq!(pr_field("draft"), [Value::Null, Value::Null, Value::Null]);
+
+    stack(&["pr", "close"]).success();
+    stack(&["pr", "ready"])
+        .success()
+        .stdout("skipped 'feat/b': PR #62 is closed\n");
+    stack(&["pr", "reopen", "-b", "feat/b"]).success();
+    assert_eq!(
+        pr_field("state"),
+        [
+            Value::from("open"),
+            Value::from("open"),
+            Value::from("merged")
+        ]
+    );
+
+    stack(&["pr", "close", "--branch", "feat/other"])
+        .failure()
+        .stderr(predicate::str::contains("no PR found for 'feat/other'"));
+    stack(&["pr", "close", "--title", "nope"]).failure();
+}
+
+#[test]
+fn pr_create_posts_order_status_waiting_on_parent_pr() {
+    let repo = init_repo();
+    configure_local_push_url(repo.path());
+    run_git(repo.path(), &["config", "stack.orderStatus", "true"]);
+    let provider_file = repo.path().join(".git").join("stack-provider.json");
+    fs::write(
+        &provider_file,
+        r#"{"prs":[{"number":41,"head":"feat/parent","base":"main"}]}"#,
+    )
+    .expect("write provider file");
+    let stack = |args: &[&str]| {
+        stack_cmd(repo.path())
+            .env("STACK_PROVIDER", "file:.git/stack-provider.json")
+            .args(args)
+            .assert()
+            .success()
+    };
+    stack(&["create", "--parent", "main", "--name", "feat/parent"]);
+    stack(&["create", "--parent", "feat/parent", "--name", "feat/child"]);
+
+    stack(&["--yes", "pr", "--create", "--title", "Child"]).stdout(predicate::str::contains(
+        "set stack/order pending: merge #41 (feat/parent) first",
+    ));
+
+    let state: Value =
+        serde_json::from_str(&fs::read_to_string(&provider_file).expect("read provider file"))
+            .expect("provider json");
+    assert_eq!(
+        state["prs"][1]["statuses"],
+        serde_json::json!([{
+            "context": "stack/order",
+            "state": "pending",
+            "description": "merge #41 (feat/parent) first",
+        }])
+    );
+}
+
+#[test]
+fn pr_links_use_configured_enterprise_host_mapping() {
+    let repo = init_repo_without_origin();
+    run_git(
+        repo.path(),
+        &[
+            "remote",
+            "add",
+            "origin",
+            "ssh://git@git.corp.example:7999/acme/stack-test.git",
+        ],
+    );
+    run_git(
+        repo.path(),
+        &[
+            "config",
+            "--add",
+            "stack.hostMapping",
+            "git.corp.example=https://github.corp.example/api/v3",
+        ],
+    );
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/ghes"])
+        .assert()
+        .success();
+
+    let output = stack_cmd(repo.path())
+        .args(["pr", "--preview-body", "--porcelain"])
+        .output()
+        .expect("run stack pr --preview-body");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    let body = json["body"].as_str().expect("body");
+    assert!(
+        body.contains("](https://github.corp.example/acme/stack-test/commit/"),
+        "unexpected body: {body}"
+    );
+
+    run_git(
+        repo.path(),
+        &["config", "stack.hostMapping", "not-a-mapping"],
+    );
+    stack_cmd(repo.path())
+        .args(["pr", "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid stack.hostMapping"));
+}
+
+#[test]
+fn pr_links_resolve_ssh_host_aliases_and_instead_of_rewrites() {
+    let repo = init_repo_without_origin();
+    let home = repo.path().join("home");
+    fs::create_dir_all(home.join(".ssh").join("config.d")).expect("create ssh dir");
+    fs::write(
+        home.join(".ssh").join("config"),
+        "Include config.d/*\nHost *\n  User git\n",
+    )
+    .expect("write ssh config");
+    fs::write(
+        home.join(".ssh").join("config.d").join("work"),
+        "Host github-*\n  HostName github.com\n",
+    )
+    .expect("write included ssh config");
+    run_git(
+        repo.path(),
+        &["remote", "add", "origin", "work:acme/stack-test.git"],
+    );
+    run_git(
+        repo.path(),
+        &["config", "url.git@github-work:.insteadOf", "work:"],
+    );
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/alias"])
+        .assert()
+        .success();
+
+    let output = stack_cmd(repo.path())
+        .env("HOME", &home)
+        .args(["pr", "--preview-body", "--porcelain"])
+        .output()
+        .expect("run stack pr --preview-body");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    let body = json["body"].as_str().expect("body");
+    assert!(
+        body.contains("](https://github.com/acme/stack-test/commit/"),
+        "unexpected body: {body}"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn pr_uses_configured_push_remote_and_pr_repo() {
+    let repo = init_repo();
+    let bare = repo.path().join("fork-push.git");
+    run_git(
+        repo.path(),
+        &["init", "--bare", bare.to_str().expect("bare path")],
+    );
+    run_git(
+        repo.path(),
+        &["remote", "add", "mine", "git@github.com:me/stack-test.git"],
+    );
+    run_git(
+        repo.path(),
+        &[
+            "remote",
+            "set-url",
+            "--push",
+            "mine",
+            bare.to_str().expect("bare path"),
+        ],
+    );
+    run_git(repo.path(), &["config", "stack.pushRemote", "mine"]);
+    run_git(repo.path(), &["config", "stack.prRepo", "acme/stack-test"]);
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/fork"])
+        .assert()
+        .success();
+
+    let fake_bin = repo.path().join("fake-bin");
+    let open_log = repo.path().join("open.log");
+    fs::create_dir_all(&fake_bin).expect("create fake bin dir");
+    install_fake_browser_openers(&fake_bin, &open_log);
+    let fake_gh = fake_bin.join("gh");
+    fs::write(&fake_gh, "#!/usr/bin/env bash\necho '[]'\n").expect("write fake gh");
+    fs::set_permissions(&fake_gh, fs::Permissions::from_mode(0o755)).expect("chmod fake gh");
+    let current_path = env::var("PATH").unwrap_or_default();
+    let test_path = format!("{}:{}", fake_bin.display(), current_path);
+
+    stack_cmd(repo.path())
+        .env("PATH", test_path)
+        .env_remove("STACK_MOCK_BROWSER_OPEN")
+        .args(["--yes", "pr"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pushed 'feat/fork' to 'mine'"));
+
+    let open_calls = fs::read_to_string(&open_log).expect("read open log");
+    assert!(
+        open_calls
+            .contains("https://github.com/acme/stack-test/compare/main...me:feat/fork?expand=1"),
+        "expected browser opener call, got: {open_calls}"
+    );
+    let pushed = Command::new("git")
+        .current_dir(&bare)
+        .args(["show-ref", "--verify", "--quiet", "refs/heads/feat/fork"])
+        .status()
+        .expect("check pushed branch");
+    assert!(pushed.success());
+}
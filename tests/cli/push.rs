@@ -160,3 +160,96 @@ fn push_skips_merged_branches() {
         .expect("verify feat/b push");
     assert!(feat_b_exists.success(), "expected feat/b on remote");
 }
+
+#[test]
+fn push_falls_back_to_base_remote_for_new_branches_without_origin() {
+    let repo = init_repo_with_named_remote("upstream");
+    let bare = repo.path().join("upstream-push.git");
+    run_git(
+        repo.path(),
+        &["init", "--bare", bare.to_str().expect("bare path")],
+    );
+    run_git(
+        repo.path(),
+        &[
+            "remote",
+            "set-url",
+            "--push",
+            "upstream",
+            bare.to_str().expect("bare path"),
+        ],
+    );
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/a"])
+        .assert()
+        .success();
+
+    let output = stack_cmd(repo.path())
+        .args(["push", "--porcelain"])
+        .output()
+        .expect("run stack push");
+    assert!(
+        output.status.success(),
+        "push failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(json["pushed"][0]["branch"], "feat/a");
+    assert_eq!(json["pushed"][0]["remote"], "upstream");
+    run_git(&bare, &["rev-parse", "--verify", "refs/heads/feat/a"]);
+}
+
+#[test]
+fn push_sends_new_branches_to_the_fork_when_base_remote_is_upstream() {
+    let repo = init_repo();
+    let fork = configure_local_push_url(repo.path());
+    run_git(
+        repo.path(),
+        &[
+            "remote",
+            "add",
+            "upstream",
+            "git@github.com:canonical/stack-test.git",
+        ],
+    );
+    let canonical = repo.path().join("upstream-push.git");
+    run_git(
+        repo.path(),
+        &["init", "--bare", canonical.to_str().expect("bare path")],
+    );
+    run_git(
+        repo.path(),
+        &[
+            "remote",
+            "set-url",
+            "--push",
+            "upstream",
+            canonical.to_str().expect("bare path"),
+        ],
+    );
+    run_git(repo.path(), &["config", "stack.baseRemote", "upstream"]);
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/a"])
+        .assert()
+        .success();
+
+    let output = stack_cmd(repo.path())
+        .args(["push", "--porcelain"])
+        .output()
+        .expect("run stack push");
+    assert!(
+        output.status.success(),
+        "push failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(json["pushed"][0]["branch"], "feat/a");
+    assert_eq!(json["pushed"][0]["remote"], "origin");
+    run_git(&fork, &["rev-parse", "--verify", "refs/heads/feat/a"]);
+    let canonical_refs = Command::new("git")
+        .current_dir(&canonical)
+        .args(["for-each-ref", "refs/heads"])
+        .output()
+        .expect("list canonical refs");
+    assert!(canonical_refs.stdout.is_empty());
+}
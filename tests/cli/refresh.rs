@@ -0,0 +1,231 @@
+#[cfg(unix)]
+fn install_fake_cache_gh(repo: &Path) -> (String, PathBuf, PathBuf) {
+    let fake_bin = repo.join("fake-bin");
+    let gh_log = repo.join("gh.log");
+    let unreachable_flag = repo.join("gh-unreachable");
+    fs::create_dir_all(&fake_bin).expect("create fake bin dir");
+    let fake_gh = fake_bin.join("gh");
+    fs::write(
+        &fake_gh,
+        format!(
+            "#!/usr/bin/env bash\nif [[ -f '{}' ]]; then\n  echo 'error connecting to api.github.com' >&2\n  exit 1\nfi\necho \"$@\" >> '{}'\nif [[ \"$1\" == \"pr\" && \"$2\" == \"list\" ]]; then\n  echo '[{{\"number\":7,\"state\":\"OPEN\",\"baseRefName\":\"main\",\"headRefName\":\"feat/a\",\"mergeCommit\":null,\"body\":\"\",\"url\":\"https://github.com/acme/stack-test/pull/7\"}}]'\n  exit 0\nfi\necho '[]'\n",
+            unreachable_flag.display(),
+            gh_log.display()
+        ),
+    )
+    .expect("write fake gh");
+    fs::set_permissions(&fake_gh, fs::Permissions::from_mode(0o755)).expect("chmod fake gh");
+
+    let current_path = env::var("PATH").unwrap_or_default();
+    (
+        format!("{}:{}", fake_bin.display(), current_path),
+        gh_log,
+        unreachable_flag,
+    )
+}
+
+#[cfg(unix)]
+fn init_cached_pr_repo() -> (TempDir, String, PathBuf, PathBuf) {
+    let repo = init_repo();
+    let (path, gh_log, unreachable_flag) = install_fake_cache_gh(repo.path());
+    stack_cmd(repo.path())
+        .env("PATH", &path)
+        .args(["create", "--parent", "main", "--name", "feat/a"])
+        .assert()
+        .success();
+    stack_cmd(repo.path())
+        .env("PATH", &path)
+        .arg("refresh")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("'feat/a': PR #7 (open)"))
+        .stderr(predicate::str::contains("PR data: fresh (fetched live)"));
+    fs::remove_file(&gh_log).expect("reset gh log");
+    (repo, path, gh_log, unreachable_flag)
+}
+
+#[cfg(unix)]
+#[test]
+fn offline_sync_uses_cached_pr_metadata_without_calling_gh() {
+    let (repo, path, gh_log, _) = init_cached_pr_repo();
+
+    stack_cmd(repo.path())
+        .env("PATH", &path)
+        .args(["--offline", "sync", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "skip: feat/a update_pr_body skipped: offline mode",
+        ))
+        .stderr(predicate::str::contains("PR data: stale (cached"));
+
+    assert!(
+        !gh_log.exists(),
+        "expected no gh calls in offline mode, got: {}",
+        fs::read_to_string(&gh_log).unwrap_or_default()
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn cache_ttl_serves_recent_pr_metadata_as_fresh() {
+    let (repo, path, gh_log, _) = init_cached_pr_repo();
+    run_git(repo.path(), &["config", "stack.cacheTtl", "3600"]);
+
+    stack_cmd(repo.path())
+        .env("PATH", &path)
+        .args(["sync", "--dry-run"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("PR data: fresh (cached"));
+
+    assert!(
+        !gh_log.exists(),
+        "expected cached PR metadata within TTL, got: {}",
+        fs::read_to_string(&gh_log).unwrap_or_default()
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn sync_falls_back_to_cache_when_provider_is_unreachable() {
+    let (repo, path, _, unreachable_flag) = init_cached_pr_repo();
+    fs::write(&unreachable_flag, "").expect("write unreachable flag");
+
+    stack_cmd(repo.path())
+        .env("PATH", &path)
+        .args(["sync", "--dry-run"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("PR data: stale (cached"));
+}
+
+#[cfg(unix)]
+#[test]
+fn offline_sync_in_comment_mode_skips_comment_lookups() {
+    let (repo, path, gh_log, _) = init_cached_pr_repo();
+    run_git(repo.path(), &["config", "stack.prSectionMode", "comment"]);
+
+    stack_cmd(repo.path())
+        .env("PATH", &path)
+        .args(["--offline", "sync", "--dry-run"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("could not load comments").not());
+
+    assert!(
+        !gh_log.exists(),
+        "expected no gh calls in offline mode, got: {}",
+        fs::read_to_string(&gh_log).unwrap_or_default()
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn refresh_is_rejected_in_offline_mode() {
+    let (repo, path, gh_log, _) = init_cached_pr_repo();
+
+    stack_cmd(repo.path())
+        .env("PATH", &path)
+        .args(["--offline", "refresh"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("offline mode"));
+    assert!(!gh_log.exists(), "expected no gh calls in offline mode");
+}
+
+#[cfg(unix)]
+#[test]
+fn provider_calls_target_mapped_enterprise_host_or_explicit_hostname() {
+    let repo = init_repo_without_origin();
+    run_git(
+        repo.path(),
+        &[
+            "remote",
+            "add",
+            "origin",
+            "git@git.corp.example:acme/stack-test.git",
+        ],
+    );
+    run_git(
+        repo.path(),
+        &[
+            "config",
+            "stack.hostMapping",
+            "git.corp.example=github.corp.example",
+        ],
+    );
+    let fake_bin = repo.path().join("fake-bin");
+    let gh_log = repo.path().join("gh.log");
+    fs::create_dir_all(&fake_bin).expect("create fake bin dir");
+    let fake_gh = fake_bin.join("gh");
+    fs::write(
+        &fake_gh,
+        format!(
+            "#!/usr/bin/env bash\necho \"GH_HOST=$GH_HOST GH_REPO=$GH_REPO $*\" >> '{}'\necho '[]'\n",
+            gh_log.display()
+        ),
+    )
+    .expect("write fake gh");
+    fs::set_permissions(&fake_gh, fs::Permissions::from_mode(0o755)).expect("chmod fake gh");
+    let path = format!(
+        "{}:{}",
+        fake_bin.display(),
+        env::var("PATH").unwrap_or_default()
+    );
+
+    stack_cmd(repo.path())
+        .env("PATH", &path)
+        .args(["create", "--parent", "main", "--name", "feat/a"])
+        .assert()
+        .success();
+    stack_cmd(repo.path())
+        .env("PATH", &path)
+        .arg("refresh")
+        .assert()
+        .success();
+    let calls = fs::read_to_string(&gh_log).expect("read gh log");
+    assert!(
+        calls.contains(
+            "GH_HOST=github.corp.example GH_REPO=github.corp.example/acme/stack-test pr list"
+        ),
+        "unexpected gh calls: {calls}"
+    );
+    assert!(
+        calls.contains("--repo github.corp.example/acme/stack-test"),
+        "unexpected gh calls: {calls}"
+    );
+
+    fs::remove_file(&gh_log).expect("reset gh log");
+    stack_cmd(repo.path())
+        .env("PATH", &path)
+        .args(["--hostname", "ghe.other.example", "refresh"])
+        .assert()
+        .success();
+    let calls = fs::read_to_string(&gh_log).expect("read gh log");
+    assert!(
+        calls.contains("GH_HOST=ghe.other.example GH_REPO=ghe.other.example/acme/stack-test"),
+        "unexpected gh calls: {calls}"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn refresh_looks_up_pr_by_head_when_cached_number_is_stale() {
+    let (repo, path, gh_log, _) = init_cached_pr_repo();
+    let conn = Connection::open(repo.path().join(".git").join("stack.db")).expect("open db");
+    conn.execute(
+        "UPDATE branches SET cached_pr_number = 99 WHERE name = 'feat/a'",
+        [],
+    )
+    .expect("set stale cached PR number");
+
+    stack_cmd(repo.path())
+        .env("PATH", &path)
+        .arg("refresh")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("'feat/a': PR #7 (open)"));
+    let log = fs::read_to_string(&gh_log).expect("read gh log");
+    assert!(log.contains("pr view 99"), "{log}");
+}
@@ -160,3 +160,100 @@ fn current_branch(repo: &std::path::Path) -> String {
         .trim()
         .to_string()
 }
+
+#[test]
+fn stack_porcelain_reads_codeowners_from_the_fork_point() {
+    let repo = init_repo();
+    fs::write(repo.path().join("CODEOWNERS"), "* @acme/core\n").expect("write CODEOWNERS");
+    run_git(repo.path(), &["add", "CODEOWNERS"]);
+    run_git(repo.path(), &["commit", "-m", "add owners"]);
+
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/owners"])
+        .assert()
+        .success();
+    fs::write(repo.path().join("CODEOWNERS"), "* @acme/branch\n").expect("edit CODEOWNERS");
+    run_git(repo.path(), &["commit", "-am", "take ownership"]);
+    fs::write(repo.path().join("CODEOWNERS"), "* @acme/worktree\n").expect("edit CODEOWNERS");
+
+    let output = stack_cmd(repo.path())
+        .arg("--porcelain")
+        .output()
+        .expect("run stack --porcelain");
+    assert!(output.status.success());
+    let branches: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    let branch = branches
+        .as_array()
+        .expect("branch array")
+        .iter()
+        .find(|b| b["name"] == "feat/owners")
+        .expect("branch present");
+    assert_eq!(branch["codeowners"], serde_json::json!(["@acme/core"]));
+}
+
+#[test]
+fn stack_porcelain_lists_codeowners_for_each_branch_diff() {
+    let repo = init_repo();
+    fs::create_dir_all(repo.path().join(".github")).expect("create .github");
+    fs::write(
+        repo.path().join(".github").join("CODEOWNERS"),
+        "* @acme/core\n/api/ @acme/api\n*.md @acme/docs\n",
+    )
+    .expect("write CODEOWNERS");
+    run_git(repo.path(), &["add", ".github/CODEOWNERS"]);
+    run_git(repo.path(), &["commit", "-m", "add owners"]);
+
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "main", "--name", "feat/api"])
+        .assert()
+        .success();
+    fs::create_dir_all(repo.path().join("api")).expect("create api dir");
+    fs::write(repo.path().join("api").join("handler.rs"), "fn main() {}\n").expect("write api");
+    run_git(repo.path(), &["add", "api"]);
+    run_git(repo.path(), &["commit", "-m", "api change"]);
+
+    stack_cmd(repo.path())
+        .args(["create", "--parent", "feat/api", "--name", "feat/docs"])
+        .assert()
+        .success();
+    fs::write(repo.path().join("GUIDE.md"), "guide\n").expect("write guide");
+    fs::write(repo.path().join("tool.sh"), "echo\n").expect("write tool");
+    run_git(repo.path(), &["add", "GUIDE.md", "tool.sh"]);
+    run_git(repo.path(), &["commit", "-m", "docs change"]);
+
+    let output = stack_cmd(repo.path())
+        .arg("--porcelain")
+        .output()
+        .expect("run stack --porcelain");
+    assert!(output.status.success());
+    let branches: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    let owners_of = |name: &str| {
+        branches
+            .as_array()
+            .expect("branch array")
+            .iter()
+            .find(|b| b["name"] == name)
+            .map(|b| b["codeowners"].clone())
+            .expect("branch present")
+    };
+    assert_eq!(owners_of("feat/api"), serde_json::json!(["@acme/api"]));
+    assert_eq!(
+        owners_of("feat/docs"),
+        serde_json::json!(["@acme/core", "@acme/docs"])
+    );
+    assert_eq!(owners_of("main"), serde_json::json!([]));
+
+    stack_cmd(repo.path())
+        .args(["pr", "--dry-run"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "suggested reviewers from CODEOWNERS: @acme/core, @acme/docs",
+        ));
+    let output = stack_cmd(repo.path())
+        .args(["pr", "--dry-run", "--porcelain", "--codeowners"])
+        .output()
+        .expect("run stack pr --codeowners");
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    assert_eq!(json["reviewers"], serde_json::json!(["acme/core", "acme/docs"]));
+}
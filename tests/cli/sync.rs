@@ -853,6 +853,373 @@ fn sync_updates_existing_pr_body_with_managed_section() {
     );
 }
 
+#[cfg(unix)]
+#[test]
+fn sync_with_file_provider_updates_pr_body_without_gh() {
+    let repo = init_repo_without_origin();
+    run_git(
+        repo.path(),
+        &[
+            "remote",
+            "add",
+            "origin",
+            "git@github.com:acme/stack-test.git",
+        ],
+    );
+    run_git(repo.path(), &["config", "branch.main.remote", "no-fetch"]);
+
+    let provider_file = repo.path().join(".git").join("stack-provider.json");
+    fs::write(
+        &provider_file,
+        r#"{"prs":[
+            {"number":41,"head":"feat/parent","base":"main","state":"open","body":""},
+            {"number":42,"head":"feat/child","base":"feat/parent","state":"open","body":"Existing reviewer notes"}
+        ]}"#,
+    )
+    .expect("write provider file");
+
+    let fake_bin = repo.path().join("fake-bin");
+    let gh_log = repo.path().join("gh.log");
+    fs::create_dir_all(&fake_bin).expect("create fake bin dir");
+    let fake_gh = fake_bin.join("gh");
+    fs::write(
+        &fake_gh,
+        format!(
+            "#!/usr/bin/env bash\necho \"$@\" >> '{}'\nexit 1\n",
+            gh_log.display()
+        ),
+    )
+    .expect("write fake gh");
+    fs::set_permissions(&fake_gh, fs::Permissions::from_mode(0o755)).expect("chmod fake gh");
+    let current_path = env::var("PATH").unwrap_or_default();
+    let test_path = format!("{}:{}", fake_bin.display(), current_path);
+
+    for args in [
+        ["create", "--parent", "main", "--name", "feat/parent"],
+        ["create", "--parent", "feat/parent", "--name", "feat/child"],
+    ] {
+        stack_cmd(repo.path())
+            .env("PATH", &test_path)
+            .env("STACK_PROVIDER", "file:.git/stack-provider.json")
+            .args(args)
+            .assert()
+            .success();
+    }
+    run_git(repo.path(), &["checkout", "main"]);
+
+    stack_cmd(repo.path())
+        .env("PATH", &test_path)
+        .env("STACK_PROVIDER", "file:.git/stack-provider.json")
+        .args(["sync", "--yes"])
+        .assert()
+        .success();
+
+    assert!(!gh_log.exists(), "expected file provider not to call gh");
+    let state: Value =
+        serde_json::from_str(&fs::read_to_string(&provider_file).expect("read provider file"))
+            .expect("provider json");
+    let child = state["prs"]
+        .as_array()
+        .expect("prs array")
+        .iter()
+        .find(|pr| pr["number"] == 42)
+        .expect("child pr");
+    let body = child["body"].as_str().expect("child body");
+    assert!(body.contains("Existing reviewer notes"), "got: {body}");
+    assert!(body.contains("stack:managed:start"), "got: {body}");
+    assert!(body.contains("/pull/41"), "got: {body}");
+}
+
+#[test]
+fn sync_moves_base_to_file_provider_merge_commit() {
+    let repo = init_repo_without_origin();
+    run_git(
+        repo.path(),
+        &[
+            "remote",
+            "add",
+            "origin",
+            "git@github.com:acme/stack-test.git",
+        ],
+    );
+    run_git(repo.path(), &["config", "branch.main.remote", "no-fetch"]);
+    let provider_file = repo.path().join(".git").join("stack-provider.json");
+    fs::write(
+        &provider_file,
+        r#"{"prs":[
+            {"number":41,"head":"feat/parent","base":"main"},
+            {"number":42,"head":"feat/child","base":"feat/parent"}
+        ]}"#,
+    )
+    .expect("write provider file");
+    let stack = || {
+        let mut cmd = stack_cmd(repo.path());
+        cmd.env("STACK_PROVIDER", "file:.git/stack-provider.json");
+        cmd
+    };
+
+    stack()
+        .args(["create", "--parent", "main", "--name", "feat/parent"])
+        .assert()
+        .success();
+    fs::write(repo.path().join("parent.txt"), "parent\n").expect("write parent file");
+    run_git(repo.path(), &["add", "parent.txt"]);
+    run_git(repo.path(), &["commit", "-m", "parent work"]);
+    stack()
+        .args(["create", "--parent", "feat/parent", "--name", "feat/child"])
+        .assert()
+        .success();
+    fs::write(repo.path().join("child.txt"), "child\n").expect("write child file");
+    run_git(repo.path(), &["add", "child.txt"]);
+    run_git(repo.path(), &["commit", "-m", "child work"]);
+
+    stack()
+        .args(["--yes", "merge", "--until", "feat/parent"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("merged PR #41 ('feat/parent')"));
+
+    let rev_parse = |rev: &str| {
+        let output = Command::new("git")
+            .current_dir(repo.path())
+            .args(["rev-parse", rev])
+            .output()
+            .expect("run rev-parse");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    let state: Value =
+        serde_json::from_str(&fs::read_to_string(&provider_file).expect("read provider file"))
+            .expect("provider json");
+    let merge_commit = state["prs"][0]["merge_commit"]
+        .as_str()
+        .expect("merge commit recorded");
+    assert_eq!(merge_commit, rev_parse("feat/parent"));
+    // The sync run after the merge fast-forwarded main to the merge commit.
+    assert_eq!(rev_parse("main"), merge_commit);
+
+    let output = stack()
+        .args(["sync", "--dry-run", "--porcelain"])
+        .output()
+        .expect("run stack sync");
+    assert!(output.status.success());
+    let json: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    let ops = json["operations"].as_array().expect("operations array");
+    assert!(!ops.iter().any(|op| op["kind"] == "update_base"));
+    assert!(!ops.iter().any(|op| op["kind"] == "restack"));
+}
+
+#[test]
+fn sync_renders_full_stack_table_when_configured() {
+    let repo = init_repo_without_origin();
+    run_git(
+        repo.path(),
+        &[
+            "remote",
+            "add",
+            "origin",
+            "git@github.com:acme/stack-test.git",
+        ],
+    );
+    run_git(repo.path(), &["config", "branch.main.remote", "no-fetch"]);
+    run_git(repo.path(), &["config", "stack.prBodyFormat", "table"]);
+
+    let provider_file = repo.path().join(".git").join("stack-provider.json");
+    fs::write(
+        &provider_file,
+        r#"{"prs":[
+            {"number":41,"head":"feat/parent","base":"main","title":"Parent work"},
+            {"number":42,"head":"feat/child","base":"feat/parent","title":"Child work"},
+            {"number":43,"head":"feat/sibling","base":"feat/parent","title":"Sibling work"}
+        ]}"#,
+    )
+    .expect("write provider file");
+
+    for args in [
+        ["create", "--parent", "main", "--name", "feat/parent"],
+        ["create", "--parent", "feat/parent", "--name", "feat/child"],
+        ["create", "--parent", "feat/parent", "--name", "feat/sibling"],
+    ] {
+        stack_cmd(repo.path())
+            .env("STACK_PROVIDER", "file:.git/stack-provider.json")
+            .args(args)
+            .assert()
+            .success();
+    }
+    run_git(repo.path(), &["checkout", "main"]);
+
+    stack_cmd(repo.path())
+        .env("STACK_PROVIDER", "file:.git/stack-provider.json")
+        .args(["sync", "--yes"])
+        .assert()
+        .success();
+
+    let state: Value =
+        serde_json::from_str(&fs::read_to_string(&provider_file).expect("read provider file"))
+            .expect("provider json");
+    let prs = state["prs"].as_array().expect("prs array");
+    for pr in prs {
+        let body = pr["body"].as_str().expect("managed body");
+        assert!(body.contains("| | PR | Branch | Title | State |"), "got: {body}");
+        assert!(body.contains("`feat/parent` | Parent work | open |"), "got: {body}");
+        assert!(body.contains("↳ `feat/child` | Child work | open |"), "got: {body}");
+        assert!(body.contains("↳ `feat/sibling` | Sibling work | open |"), "got: {body}");
+    }
+    let child_body = prs
+        .iter()
+        .find(|pr| pr["number"] == 42)
+        .and_then(|pr| pr["body"].as_str())
+        .expect("child body");
+    assert!(
+        child_body.contains("| → | [#42](https://github.com/acme/stack-test/pull/42) |"),
+        "got: {child_body}"
+    );
+}
+
+#[test]
+fn sync_posts_managed_section_as_sticky_comment_in_comment_mode() {
+    let repo = init_repo_without_origin();
+    run_git(
+        repo.path(),
+        &[
+            "remote",
+            "add",
+            "origin",
+            "git@github.com:acme/stack-test.git",
+        ],
+    );
+    run_git(repo.path(), &["config", "branch.main.remote", "no-fetch"]);
+    run_git(repo.path(), &["config", "stack.prSectionMode", "comment"]);
+
+    let provider_file = repo.path().join(".git").join("stack-provider.json");
+    fs::write(
+        &provider_file,
+        r#"{"prs":[
+            {"number":41,"head":"feat/parent","base":"main"},
+            {"number":42,"head":"feat/child","base":"feat/parent","body":"<!-- stack:managed:start -->\nold\n<!-- stack:managed:end -->\n\nReviewer notes","comments":[{"id":7,"body":"LGTM"}]}
+        ]}"#,
+    )
+    .expect("write provider file");
+
+    for args in [
+        ["create", "--parent", "main", "--name", "feat/parent"],
+        ["create", "--parent", "feat/parent", "--name", "feat/child"],
+    ] {
+        stack_cmd(repo.path())
+            .env("STACK_PROVIDER", "file:.git/stack-provider.json")
+            .args(args)
+            .assert()
+            .success();
+    }
+    run_git(repo.path(), &["checkout", "main"]);
+
+    let sync = || {
+        stack_cmd(repo.path())
+            .env("STACK_PROVIDER", "file:.git/stack-provider.json")
+            .args(["sync", "--yes"])
+            .assert()
+            .success();
+    };
+    sync();
+    sync();
+
+    let state: Value =
+        serde_json::from_str(&fs::read_to_string(&provider_file).expect("read provider file"))
+            .expect("provider json");
+    let child = state["prs"]
+        .as_array()
+        .expect("prs array")
+        .iter()
+        .find(|pr| pr["number"] == 42)
+        .expect("child pr");
+    assert_eq!(child["body"], "Reviewer notes");
+    let comments = child["comments"].as_array().expect("comments");
+    assert_eq!(comments.len(), 2, "expected one sticky comment: {comments:?}");
+    assert_eq!(comments[0]["body"], "LGTM");
+    let sticky = comments[1]["body"].as_str().expect("sticky body");
+    assert!(sticky.starts_with("<!-- stack:managed:start -->"), "got: {sticky}");
+    assert!(sticky.contains("/pull/41"), "got: {sticky}");
+
+    stack_cmd(repo.path())
+        .env("STACK_PROVIDER", "file:.git/stack-provider.json")
+        .args(["sync", "--dry-run", "--porcelain"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("update_pr_comment").not());
+}
+
+#[test]
+fn title_prefix_tracks_stack_position_through_insert_and_delete() {
+    let repo = init_repo_without_origin();
+    run_git(
+        repo.path(),
+        &[
+            "remote",
+            "add",
+            "origin",
+            "git@github.com:acme/stack-test.git",
+        ],
+    );
+    run_git(repo.path(), &["config", "branch.main.remote", "no-fetch"]);
+    run_git(repo.path(), &["config", "stack.prTitlePrefix", "true"]);
+
+    let provider_file = repo.path().join(".git").join("stack-provider.json");
+    fs::write(
+        &provider_file,
+        r#"{"prs":[
+            {"number":41,"head":"feat/a","base":"main","title":"Add a"},
+            {"number":42,"head":"feat/b","base":"feat/a","title":"[9/9] Add b"},
+            {"number":43,"head":"feat/c","base":"feat/b","title":"Add c"}
+        ]}"#,
+    )
+    .expect("write provider file");
+    let titles = || -> Vec<String> {
+        let state: Value =
+            serde_json::from_str(&fs::read_to_string(&provider_file).expect("read provider file"))
+                .expect("provider json");
+        state["prs"]
+            .as_array()
+            .expect("prs array")
+            .iter()
+            .map(|pr| pr["title"].as_str().unwrap_or_default().to_string())
+            .collect()
+    };
+    let stack = |args: &[&str]| {
+        stack_cmd(repo.path())
+            .env("STACK_PROVIDER", "file:.git/stack-provider.json")
+            .args(args)
+            .assert()
+            .success()
+    };
+
+    stack(&["create", "--parent", "main", "--name", "feat/a"]);
+    stack(&["create", "--parent", "feat/a", "--name", "feat/b"]);
+    stack(&["create", "--parent", "feat/b", "--name", "feat/c"]);
+    stack(&["sync", "--yes"]);
+    assert_eq!(titles(), ["[1/3] Add a", "[2/3] Add b", "[3/3] Add c"]);
+    stack(&["sync", "--dry-run", "--porcelain"])
+        .stdout(predicate::str::contains("update_pr_title").not());
+
+    // feat/x has no PR yet, so it does not take a position.
+    stack(&["create", "--insert", "feat/b", "--name", "feat/x"]);
+    assert_eq!(titles(), ["[1/3] Add a", "[2/3] Add b", "[3/3] Add c"]);
+
+    stack(&["--yes", "delete", "feat/x"]);
+    assert_eq!(titles(), ["[1/3] Add a", "[2/3] Add b", "[3/3] Add c"]);
+}
+
+#[test]
+fn unsupported_provider_selector_is_rejected() {
+    let repo = init_repo();
+    stack_cmd(repo.path())
+        .env("STACK_PROVIDER", "gitlab")
+        .args(["sync", "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "unsupported STACK_PROVIDER 'gitlab'",
+        ));
+}
+
 #[test]
 fn sync_yes_does_not_push_in_non_interactive_mode() {
     let repo = init_repo_without_origin();
@@ -951,3 +1318,158 @@ fn sync_without_yes_does_not_push_in_non_interactive_mode() {
         "expected non-interactive sync without --yes not to push"
     );
 }
+
+#[test]
+fn draft_policy_keeps_upper_prs_drafted_until_parent_merges() {
+    let repo = init_repo_without_origin();
+    run_git(
+        repo.path(),
+        &[
+            "remote",
+            "add",
+            "origin",
+            "git@github.com:acme/stack-test.git",
+        ],
+    );
+    run_git(repo.path(), &["config", "branch.main.remote", "no-fetch"]);
+    run_git(repo.path(), &["config", "stack.prDraftPolicy", "true"]);
+
+    let provider_file = repo.path().join(".git").join("stack-provider.json");
+    fs::write(
+        &provider_file,
+        r#"{"prs":[
+            {"number":51,"head":"feat/a","base":"main"},
+            {"number":52,"head":"feat/b","base":"feat/a"},
+            {"number":53,"head":"feat/c","base":"feat/b","draft":true}
+        ]}"#,
+    )
+    .expect("write provider file");
+    let drafts = || -> Vec<bool> {
+        let state: Value =
+            serde_json::from_str(&fs::read_to_string(&provider_file).expect("read provider file"))
+                .expect("provider json");
+        state["prs"]
+            .as_array()
+            .expect("prs array")
+            .iter()
+            .map(|pr| pr["draft"].as_bool().unwrap_or(false))
+            .collect()
+    };
+    let stack = |args: &[&str]| {
+        stack_cmd(repo.path())
+            .env("STACK_PROVIDER", "file:.git/stack-provider.json")
+            .args(args)
+            .assert()
+            .success()
+    };
+
+    stack(&["create", "--parent", "main", "--name", "feat/a"]);
+    stack(&["create", "--parent", "feat/a", "--name", "feat/b"]);
+    stack(&["create", "--parent", "feat/b", "--name", "feat/c"]);
+    stack(&["sync", "--yes"]).stdout(predicate::str::contains("mark_pr_draft: feat/b"));
+    assert_eq!(drafts(), [false, true, true]);
+    stack(&["sync", "--dry-run"]).stdout(predicate::str::contains("mark_pr_").not());
+
+    let state = fs::read_to_string(&provider_file)
+        .expect("read provider file")
+        .replacen(r#""state": "open""#, r#""state": "merged""#, 1);
+    fs::write(&provider_file, state).expect("merge feat/a");
+    let output = stack_cmd(repo.path())
+        .env("STACK_PROVIDER", "file:.git/stack-provider.json")
+        .args(["--porcelain", "sync", "--dry-run"])
+        .output()
+        .expect("run sync");
+    assert!(output.status.success());
+    let plan: Value = serde_json::from_slice(&output.stdout).expect("valid json");
+    let draft_ops = plan["operations"]
+        .as_array()
+        .expect("operations")
+        .iter()
+        .filter(|op| op["kind"].as_str().is_some_and(|k| k.starts_with("mark_pr_")))
+        .map(|op| (op["kind"].clone(), op["branch"].clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        draft_ops,
+        [(
+            Value::from("mark_pr_ready"),
+            Value::from("feat/b")
+        )]
+    );
+}
+
+#[test]
+fn sync_posts_order_statuses_that_clear_when_parent_merges() {
+    let repo = init_repo_without_origin();
+    run_git(
+        repo.path(),
+        &[
+            "remote",
+            "add",
+            "origin",
+            "git@github.com:acme/stack-test.git",
+        ],
+    );
+    run_git(repo.path(), &["config", "branch.main.remote", "no-fetch"]);
+    run_git(repo.path(), &["config", "stack.orderStatus", "true"]);
+
+    let provider_file = repo.path().join(".git").join("stack-provider.json");
+    fs::write(
+        &provider_file,
+        r#"{"prs":[
+            {"number":71,"head":"feat/a","base":"main"},
+            {"number":72,"head":"feat/b","base":"feat/a"},
+            {"number":73,"head":"feat/c","base":"feat/b"}
+        ]}"#,
+    )
+    .expect("write provider file");
+    let statuses = || -> Vec<String> {
+        let state: Value =
+            serde_json::from_str(&fs::read_to_string(&provider_file).expect("read provider file"))
+                .expect("provider json");
+        state["prs"]
+            .as_array()
+            .expect("prs array")
+            .iter()
+            .map(|pr| {
+                let status = &pr["statuses"][0];
+                format!(
+                    "{} {}: {}",
+                    status["context"].as_str().unwrap_or_default(),
+                    status["state"].as_str().unwrap_or_default(),
+                    status["description"].as_str().unwrap_or_default()
+                )
+            })
+            .collect()
+    };
+    let stack = |args: &[&str]| {
+        stack_cmd(repo.path())
+            .env("STACK_PROVIDER", "file:.git/stack-provider.json")
+            .args(args)
+            .assert()
+            .success()
+    };
+
+    stack(&["create", "--parent", "main", "--name", "feat/a"]);
+    stack(&["create", "--parent", "feat/a", "--name", "feat/b"]);
+    stack(&["create", "--parent", "feat/b", "--name", "feat/c"]);
+    stack(&["sync", "--yes"]);
+    assert_eq!(
+        statuses(),
+        [
+            "stack/order success: targets main",
+            "stack/order pending: merge #71 (feat/a) first",
+            "stack/order pending: merge #72 (feat/b) first",
+        ]
+    );
+    stack(&["sync", "--dry-run"]).stdout(predicate::str::contains("set_order_status").not());
+
+    let state = fs::read_to_string(&provider_file)
+        .expect("read provider file")
+        .replacen(r#""state": "open""#, r#""state": "merged""#, 1);
+    fs::write(&provider_file, state).expect("merge feat/a");
+    stack(&["sync", "--dry-run"])
+        .stdout(predicate::str::contains(
+            "set_order_status: feat/b pr #72 success: parent #71 merged",
+        ))
+        .stdout(predicate::str::contains("set_order_status: feat/c").not());
+}
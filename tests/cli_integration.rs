@@ -124,8 +124,18 @@ include!("cli/stack.rs");
 include!("cli/sync.rs");
 include!("cli/pr.rs");
 include!("cli/push.rs");
+include!("cli/merge.rs");
+include!("cli/refresh.rs");
 include!("cli/completions.rs");
 include!("cli/delete.rs");
 include!("cli/doctor.rs");
 include!("cli/untrack.rs");
 include!("cli/track.rs");
+include!("cli/db.rs");
+include!("cli/metadata.rs");
+include!("cli/export.rs");
+include!("cli/base.rs");
+include!("cli/annotate.rs");
+include!("cli/freeze.rs");
+include!("cli/lock.rs");
+include!("cli/history.rs");